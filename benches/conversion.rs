@@ -0,0 +1,31 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use gdal_typed_rasterband::simd::*;
+
+const PIXELS: usize = 1 << 16;
+
+fn conversion(c: &mut Criterion) {
+    let values: Vec<f64> = (0..PIXELS).map(|v| (v % 251) as f64 * 1.5).collect();
+    let pixels: Vec<u16> = (0..PIXELS).map(|v| (v % 251) as u16).collect();
+
+    c.bench_function("convert_from_f64/u16", |b| {
+        b.iter(|| convert_from_f64::<u16>(black_box(&values)))
+    });
+    c.bench_function("convert_from_f64_scalar/u16", |b| {
+        b.iter(|| convert_from_f64_scalar::<u16>(black_box(&values)))
+    });
+    c.bench_function("convert_from_f64/i32", |b| {
+        b.iter(|| convert_from_f64::<i32>(black_box(&values)))
+    });
+    c.bench_function("convert_from_f64_scalar/i32", |b| {
+        b.iter(|| convert_from_f64_scalar::<i32>(black_box(&values)))
+    });
+    c.bench_function("apply_scale_offset", |b| {
+        b.iter(|| apply_scale_offset(black_box(&pixels), 0.01, -5.0))
+    });
+    c.bench_function("nodata_mask", |b| {
+        b.iter(|| nodata_mask(black_box(&pixels), 42))
+    });
+}
+
+criterion_group!(benches, conversion);
+criterion_main!(benches);
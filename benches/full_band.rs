@@ -0,0 +1,35 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use gdal_typed_rasterband::dataset::TypedDataset;
+use std::path::Path;
+
+/// Benchmarks both full-band reads of `ds`, whose blocks are `layout`.
+fn bench_layout(c: &mut Criterion, layout: &str, ds: &TypedDataset<u16>) {
+    ds.with_band(1, |band| {
+        c.bench_function(&format!("read_band/{}", layout), |b| {
+            b.iter(|| band.read_band().unwrap())
+        });
+        c.bench_function(&format!("read_band_fast/{}", layout), |b| {
+            b.iter(|| band.read_band_fast().unwrap())
+        });
+    })
+    .unwrap();
+}
+
+fn full_band(c: &mut Criterion) {
+    // The fixture is striped; a tiled copy is made in memory so that both
+    // layouts are measured on the same pixels.
+    let striped = TypedDataset::<u16>::open(Path::new("testdata/test_u16.tif")).unwrap();
+    let tiled_bytes = striped
+        .to_bytes(
+            "GTiff",
+            &[("TILED", "YES"), ("BLOCKXSIZE", "64"), ("BLOCKYSIZE", "64")],
+        )
+        .unwrap();
+    let tiled = TypedDataset::<u16>::from_bytes(&tiled_bytes).unwrap();
+
+    bench_layout(c, "striped", &striped);
+    bench_layout(c, "tiled", &tiled);
+}
+
+criterion_group!(benches, full_band);
+criterion_main!(benches);
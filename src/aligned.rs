@@ -0,0 +1,211 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::{check_cpl_err, Result};
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+use gdal_sys::{GDALRWFlag, GDALRasterIO};
+use std::alloc::{self, Layout};
+use std::fmt;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::os::raw::c_void;
+use std::ptr::{self, NonNull};
+use std::slice;
+
+/// A row-major block of pixels whose storage starts on an `alignment`-byte
+/// boundary, for SIMD kernels and GPU uploads that need aligned memory.
+pub struct AlignedBuffer<T: Copy> {
+    ptr: NonNull<T>,
+    size: (usize, usize),
+    alignment: usize,
+}
+
+unsafe impl<T: Copy + Send> Send for AlignedBuffer<T> {}
+unsafe impl<T: Copy + Sync> Sync for AlignedBuffer<T> {}
+
+impl<T: Copy> AlignedBuffer<T> {
+    fn layout(size: (usize, usize), alignment: usize) -> Layout {
+        let bytes = size.0 * size.1 * mem::size_of::<T>();
+        Layout::from_size_align(bytes, alignment).expect("invalid buffer alignment")
+    }
+
+    /// Allocates storage for `size` pixels without initializing it.
+    fn allocate(size: (usize, usize), alignment: usize) -> AlignedBuffer<T> {
+        let alignment = alignment.max(mem::align_of::<T>());
+        let layout = AlignedBuffer::<T>::layout(size, alignment);
+        let ptr = if layout.size() == 0 {
+            // An address equal to the alignment is suitably aligned and
+            // never dereferenced.
+            NonNull::new(alignment as *mut T).unwrap()
+        } else {
+            let raw = unsafe { alloc::alloc(layout) as *mut T };
+            NonNull::new(raw).unwrap_or_else(|| alloc::handle_alloc_error(layout))
+        };
+        AlignedBuffer {
+            ptr,
+            size,
+            alignment,
+        }
+    }
+
+    /// Allocates an aligned buffer filled with `value`.
+    ///
+    /// `alignment` must be a power of two; it's raised to the alignment of
+    /// `T` if smaller.
+    pub fn filled(size: (usize, usize), value: T, alignment: usize) -> AlignedBuffer<T> {
+        let buffer = AlignedBuffer::<T>::allocate(size, alignment);
+        for i in 0..size.0 * size.1 {
+            unsafe { ptr::write(buffer.ptr.as_ptr().add(i), value) };
+        }
+        buffer
+    }
+
+    /// Copies `data` into a new aligned buffer.
+    pub fn from_slice(size: (usize, usize), data: &[T], alignment: usize) -> AlignedBuffer<T> {
+        assert_eq!(
+            size.0 * size.1,
+            data.len(),
+            "buffer size doesn't match length of data"
+        );
+        let buffer = AlignedBuffer::<T>::allocate(size, alignment);
+        unsafe { ptr::copy_nonoverlapping(data.as_ptr(), buffer.ptr.as_ptr(), data.len()) };
+        buffer
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    pub fn width(&self) -> usize {
+        self.size.0
+    }
+
+    pub fn height(&self) -> usize {
+        self.size.1
+    }
+
+    pub fn alignment(&self) -> usize {
+        self.alignment
+    }
+
+    pub fn as_aligned_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.size.0 * self.size.1) }
+    }
+
+    pub fn as_aligned_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.size.0 * self.size.1) }
+    }
+
+    pub fn to_typed_buffer(&self) -> TypedBuffer<T> {
+        TypedBuffer::new(self.size, self.as_aligned_slice().to_vec())
+    }
+}
+
+impl<T: Copy> Drop for AlignedBuffer<T> {
+    fn drop(&mut self) {
+        let layout = AlignedBuffer::<T>::layout(self.size, self.alignment);
+        if layout.size() != 0 {
+            unsafe { alloc::dealloc(self.ptr.as_ptr() as *mut u8, layout) };
+        }
+    }
+}
+
+impl<T: Copy> Deref for AlignedBuffer<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_aligned_slice()
+    }
+}
+
+impl<T: Copy> DerefMut for AlignedBuffer<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_aligned_mut_slice()
+    }
+}
+
+impl<T: Copy> Clone for AlignedBuffer<T> {
+    fn clone(&self) -> AlignedBuffer<T> {
+        AlignedBuffer::from_slice(self.size, self, self.alignment)
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for AlignedBuffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AlignedBuffer")
+            .field("size", &self.size)
+            .field("alignment", &self.alignment)
+            .field("data", &self.as_aligned_slice())
+            .finish()
+    }
+}
+
+impl<T: Copy> TypedBuffer<T> {
+    /// Copies the buffer into storage aligned to `alignment` bytes.
+    pub fn to_aligned(&self, alignment: usize) -> AlignedBuffer<T> {
+        AlignedBuffer::from_slice(self.size, &self.data, alignment)
+    }
+}
+
+impl<'a, T: Copy + GdalType + GdalFrom<f64>, A: Access> TypedRasterBand<'a, T, A> {
+    /// Reads `window`, resampled to `size`, directly into aligned storage.
+    pub fn read_aligned(
+        &self,
+        window: Window,
+        size: (usize, usize),
+        alignment: usize,
+    ) -> Result<AlignedBuffer<T>> {
+        let mut buffer = AlignedBuffer::filled(size, T::from(0.0), alignment);
+        let rv = unsafe {
+            GDALRasterIO(
+                self.rasterband()._c_ptr(),
+                GDALRWFlag::GF_Read,
+                window.offset.0 as i32,
+                window.offset.1 as i32,
+                window.size.0 as i32,
+                window.size.1 as i32,
+                buffer.as_aligned_mut_slice().as_mut_ptr() as *mut c_void,
+                size.0 as i32,
+                size.1 as i32,
+                T::gdal_type(),
+                0,
+                0,
+            )
+        };
+        check_cpl_err(rv)?;
+        Ok(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::typed_rasterband::TypedRasterBand;
+    use crate::window::Window;
+    use gdal::raster::dataset::Dataset;
+    use std::path::Path;
+
+    #[test]
+    fn aligned_copy() {
+        let buffer = TypedBuffer::new((3, 1), vec![1u16, 2, 3]);
+        let aligned = buffer.to_aligned(64);
+
+        assert_eq!(aligned.as_aligned_slice().as_ptr() as usize % 64, 0);
+        assert_eq!(aligned.to_typed_buffer(), buffer);
+        assert_eq!(aligned.clone()[2], 3);
+    }
+
+    #[test]
+    fn read_band_aligned() {
+        let path = Path::new("testdata/test_u16.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u16>::from_rasterband(&band).unwrap();
+
+        let aligned = typed_band
+            .read_aligned(Window::new((100, 100), (2, 1)), (2, 1), 32)
+            .unwrap();
+        assert_eq!(aligned.alignment(), 32);
+        assert_eq!(&aligned[..], &[6656, 6764]);
+    }
+}
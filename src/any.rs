@@ -0,0 +1,135 @@
+use crate::typed_rasterband::{TypeError, TypedRasterBand};
+use gdal::raster::rasterband::RasterBand;
+use gdal_sys::GDALDataType;
+
+/// A typed band whose pixel type is only known at runtime.
+pub enum AnyTypedBand<'a> {
+    U8(TypedRasterBand<'a, u8>),
+    U16(TypedRasterBand<'a, u16>),
+    U32(TypedRasterBand<'a, u32>),
+    I16(TypedRasterBand<'a, i16>),
+    I32(TypedRasterBand<'a, i32>),
+    F32(TypedRasterBand<'a, f32>),
+    F64(TypedRasterBand<'a, f64>),
+}
+
+impl<'a> AnyTypedBand<'a> {
+    /// Wraps a band in the variant matching its GDAL type. Fails for types
+    /// without a Rust pixel type, such as complex types.
+    pub fn from_rasterband(rasterband: &'a RasterBand) -> Result<AnyTypedBand<'a>, TypeError> {
+        Ok(match rasterband.band_type() {
+            GDALDataType::GDT_Byte => {
+                AnyTypedBand::U8(TypedRasterBand::from_rasterband(rasterband)?)
+            }
+            GDALDataType::GDT_UInt16 => {
+                AnyTypedBand::U16(TypedRasterBand::from_rasterband(rasterband)?)
+            }
+            GDALDataType::GDT_UInt32 => {
+                AnyTypedBand::U32(TypedRasterBand::from_rasterband(rasterband)?)
+            }
+            GDALDataType::GDT_Int16 => {
+                AnyTypedBand::I16(TypedRasterBand::from_rasterband(rasterband)?)
+            }
+            GDALDataType::GDT_Int32 => {
+                AnyTypedBand::I32(TypedRasterBand::from_rasterband(rasterband)?)
+            }
+            GDALDataType::GDT_Float32 => {
+                AnyTypedBand::F32(TypedRasterBand::from_rasterband(rasterband)?)
+            }
+            GDALDataType::GDT_Float64 => {
+                AnyTypedBand::F64(TypedRasterBand::from_rasterband(rasterband)?)
+            }
+            _ => return Err(TypeError {}),
+        })
+    }
+
+    pub fn rasterband(&self) -> &'a RasterBand<'a> {
+        match self {
+            AnyTypedBand::U8(b) => b.rasterband(),
+            AnyTypedBand::U16(b) => b.rasterband(),
+            AnyTypedBand::U32(b) => b.rasterband(),
+            AnyTypedBand::I16(b) => b.rasterband(),
+            AnyTypedBand::I32(b) => b.rasterband(),
+            AnyTypedBand::F32(b) => b.rasterband(),
+            AnyTypedBand::F64(b) => b.rasterband(),
+        }
+    }
+
+    pub fn band_type(&self) -> GDALDataType::Type {
+        self.rasterband().band_type()
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        self.rasterband().size()
+    }
+}
+
+/// Runs an expression once per supported pixel type, with `$tb` bound to
+/// a `TypedRasterBand` of the band's actual type.
+///
+/// The body is expanded for every type, so it can call generic code that
+/// needs a concrete `T`. Evaluates to `Err(TypeError)` if the band's type
+/// has no Rust pixel type.
+///
+/// ```ignore
+/// let total = with_typed_band!(&band, |tb| sum_band(&tb))?;
+/// ```
+#[macro_export]
+macro_rules! with_typed_band {
+    ($band:expr, |$tb:ident| $body:expr) => {
+        match $crate::any::AnyTypedBand::from_rasterband($band) {
+            Ok($crate::any::AnyTypedBand::U8($tb)) => Ok($body),
+            Ok($crate::any::AnyTypedBand::U16($tb)) => Ok($body),
+            Ok($crate::any::AnyTypedBand::U32($tb)) => Ok($body),
+            Ok($crate::any::AnyTypedBand::I16($tb)) => Ok($body),
+            Ok($crate::any::AnyTypedBand::I32($tb)) => Ok($body),
+            Ok($crate::any::AnyTypedBand::F32($tb)) => Ok($body),
+            Ok($crate::any::AnyTypedBand::F64($tb)) => Ok($body),
+            Err(e) => Err(e),
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::any::AnyTypedBand;
+    use crate::typed_rasterband::{GdalFrom, TypedRasterBand};
+    use gdal::raster::dataset::Dataset;
+    use gdal::raster::types::GdalType;
+    use std::path::Path;
+
+    #[test]
+    fn any_typed_band_variant() {
+        let ds = Dataset::open(Path::new("testdata/test_u16.tif")).unwrap();
+        let band = ds.rasterband(1).unwrap();
+
+        match AnyTypedBand::from_rasterband(&band).unwrap() {
+            AnyTypedBand::U16(typed_band) => {
+                let buffer = typed_band.read((100, 100), (1, 1), (1, 1)).unwrap();
+                assert_eq!(buffer.data, vec![6656]);
+            }
+            _ => panic!("expected a u16 band"),
+        }
+    }
+
+    fn first_pixel<T>(band: &TypedRasterBand<T>) -> f64
+    where
+        T: Copy + GdalType + GdalFrom<f64> + Into<f64>,
+    {
+        band.read((0, 0), (1, 1), (1, 1)).unwrap().data[0].into()
+    }
+
+    #[test]
+    fn dispatch_on_band_type() {
+        for (path, expected) in &[
+            ("testdata/test_u8.tif", 152.0),
+            ("testdata/test_u16.tif", 6885.0),
+        ] {
+            let ds = Dataset::open(Path::new(path)).unwrap();
+            let band = ds.rasterband(1).unwrap();
+
+            let value = with_typed_band!(&band, |tb| first_pixel(&tb)).unwrap();
+            assert_eq!(value, *expected);
+        }
+    }
+}
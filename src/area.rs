@@ -0,0 +1,143 @@
+use crate::bitmask::PixelMask;
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::srs::SpatialRef;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use gdal::raster::dataset::Dataset;
+use gdal::raster::types::GdalType;
+
+/// The area on an ellipsoid of the cell between latitudes `lat0` and
+/// `lat1` that spans `lon_span` degrees of longitude. An inverse flattening
+/// of 0 means a sphere.
+pub fn ellipsoidal_cell_area(
+    lat0: f64,
+    lat1: f64,
+    lon_span: f64,
+    semi_major: f64,
+    inv_flattening: f64,
+) -> f64 {
+    let flattening = if inv_flattening == 0.0 {
+        0.0
+    } else {
+        1.0 / inv_flattening
+    };
+    let e2 = flattening * (2.0 - flattening);
+    let e = e2.sqrt();
+    // Twice the authalic latitude function, up to a constant factor.
+    let q = |lat: f64| {
+        let s = lat.to_radians().sin();
+        if e == 0.0 {
+            2.0 * s
+        } else {
+            s / (1.0 - e2 * s * s) + ((1.0 + e * s) / (1.0 - e * s)).ln() / (2.0 * e)
+        }
+    };
+    (semi_major * semi_major * (1.0 - e2) / 2.0 * lon_span.to_radians() * (q(lat1) - q(lat0))).abs()
+}
+
+/// The area of a pixel in each row of `dataset`: in squared map units for
+/// a projected dataset, or in squared ellipsoid units (usually metres) for a
+/// geographic one.
+///
+/// Geographic pixels are treated as spanning `|gt[1]|` by `|gt[5]|`
+/// degrees, ignoring any rotation.
+fn row_pixel_areas(dataset: &Dataset) -> Result<Vec<f64>> {
+    let gt = dataset.geo_transform()?;
+    let height = dataset.size().1;
+    let projection = dataset.projection();
+    let geographic = if projection.is_empty() {
+        None
+    } else {
+        SpatialRef::from_user_input(&projection).filter(SpatialRef::is_geographic)
+    };
+    Ok(match geographic {
+        Some(srs) => {
+            let (semi_major, inv_flattening) = srs.ellipsoid();
+            (0..height)
+                .map(|row| {
+                    let lat0 = gt[3] + row as f64 * gt[5];
+                    ellipsoidal_cell_area(
+                        lat0,
+                        lat0 + gt[5],
+                        gt[1].abs(),
+                        semi_major,
+                        inv_flattening,
+                    )
+                })
+                .collect()
+        }
+        None => vec![(gt[1] * gt[5] - gt[2] * gt[4]).abs(); height],
+    })
+}
+
+impl<'a, T, A: Access> TypedRasterBand<'a, T, A>
+where
+    T: Copy + GdalType + GdalFrom<f64>,
+{
+    /// The area covered by the band's valid pixels, according to its GDAL
+    /// mask. On a geographic grid the area is geodesic, in the units of the
+    /// ellipsoid; otherwise it's in squared map units.
+    pub fn valid_area(&self) -> Result<f64> {
+        let areas = row_pixel_areas(self.owning_dataset())?;
+        let mut total = 0.0;
+        for window in self.block_windows() {
+            let mask = self.read_mask(window)?;
+            let (width, height) = mask.size();
+            for y in 0..height {
+                let count = (0..width).filter(|&x| mask.is_set(x, y)).count();
+                total += count as f64 * areas[window.offset.1 as usize + y];
+            }
+        }
+        Ok(total)
+    }
+
+    /// The area covered by valid pixels whose values satisfy `predicate`,
+    /// in the same units as `valid_area`.
+    pub fn area_where<F: Fn(T) -> bool>(&self, predicate: F) -> Result<f64> {
+        let areas = row_pixel_areas(self.owning_dataset())?;
+        let mut total = 0.0;
+        for window in self.block_windows() {
+            let mask = self.read_mask(window)?;
+            let buffer: TypedBuffer<T> = self.read(window.offset, window.size, window.size)?.into();
+            for (x, y, value) in buffer.pixels() {
+                if mask.is_set(x, y) && predicate(value) {
+                    total += areas[window.offset.1 as usize + y];
+                }
+            }
+        }
+        Ok(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::area::ellipsoidal_cell_area;
+    use crate::typed_rasterband::TypedRasterBand;
+    use gdal::raster::dataset::Dataset;
+    use std::f64::consts::PI;
+    use std::path::Path;
+
+    #[test]
+    fn ellipsoid_areas() {
+        let sphere = ellipsoidal_cell_area(-90.0, 90.0, 360.0, 1.0, 0.0);
+        assert!((sphere - 4.0 * PI).abs() < 1e-12);
+
+        // The surface of the WGS 84 ellipsoid is about 510,065,622 km².
+        let earth = ellipsoidal_cell_area(90.0, -90.0, 360.0, 6_378_137.0, 298.257_223_563);
+        assert!((earth / 1e6 - 510_065_622.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn projected_areas() {
+        let ds = Dataset::open(Path::new("testdata/test_u8.tif")).unwrap();
+        let band = ds.rasterband(1).unwrap();
+        let typed_band = TypedRasterBand::<u8>::from_rasterband(&band).unwrap();
+        let gt = ds.geo_transform().unwrap();
+        let pixel_area = (gt[1] * gt[5]).abs();
+
+        let valid = typed_band.valid_area().unwrap();
+        assert!((valid - 333.0 * 333.0 * pixel_area).abs() < 1e-6 * valid);
+        let bright = typed_band.area_where(|v| v > 150).unwrap();
+        assert!(bright > 0.0 && bright < valid);
+    }
+}
@@ -0,0 +1,96 @@
+use crate::buffer::TypedBuffer;
+use crate::chips::Chip;
+use crate::typed_rasterband::{Access, GdalFrom, ReadWrite, TypedRasterBand};
+use crate::window::Window;
+use gdal::errors::Result as GdalResult;
+use gdal::raster::dataset::Buffer;
+use gdal::raster::types::GdalType;
+use ndarray::{Array2, Array3};
+
+impl<T: Copy> TypedBuffer<T> {
+    /// Copies the buffer into an array indexed as `[row, column]`.
+    pub fn to_array2(&self) -> Array2<T> {
+        self.clone().into_array2()
+    }
+
+    pub fn into_array2(self) -> Array2<T> {
+        let (width, height) = self.size;
+        Array2::from_shape_vec((height, width), self.data)
+            .expect("buffer size doesn't match length of data")
+    }
+
+    pub fn from_array2(array: &Array2<T>) -> TypedBuffer<T> {
+        let (height, width) = array.dim();
+        TypedBuffer::new((width, height), array.iter().cloned().collect())
+    }
+}
+
+impl<T: Copy> Chip<T> {
+    /// Stacks the bands of the chip into an array indexed as
+    /// `[band, row, column]`.
+    pub fn to_array3(&self) -> Array3<T> {
+        let (width, height) = self.window.size;
+        let data = self
+            .bands
+            .iter()
+            .flat_map(|b| b.data.iter().cloned())
+            .collect();
+        Array3::from_shape_vec((self.bands.len(), height, width), data)
+            .expect("chip bands don't match chip size")
+    }
+}
+
+impl<'a, T: Copy + GdalType + GdalFrom<f64>, A: Access> TypedRasterBand<'a, T, A> {
+    /// Reads `window`, resampled to `size`, into an array indexed as
+    /// `[row, column]`.
+    pub fn read_as_array(&self, window: Window, size: (usize, usize)) -> GdalResult<Array2<T>> {
+        let buffer: TypedBuffer<T> = self.read(window.offset, window.size, size)?.into();
+        Ok(buffer.into_array2())
+    }
+}
+
+impl<'a, T: Copy + GdalType + GdalFrom<f64>> TypedRasterBand<'a, T, ReadWrite> {
+    /// Writes an array indexed as `[row, column]` into `window`, resampling
+    /// if the array shape differs from the window size.
+    pub fn write_from_array(&self, window: Window, array: &Array2<T>) -> GdalResult<()> {
+        let buffer = TypedBuffer::from_array2(array);
+        self.write(
+            window.offset,
+            window.size,
+            &Buffer::new(buffer.size, buffer.data),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::typed_rasterband::TypedRasterBand;
+    use crate::window::Window;
+    use gdal::raster::dataset::Dataset;
+    use std::path::Path;
+
+    #[test]
+    fn buffer_array_round_trip() {
+        let buffer = TypedBuffer::new((3, 2), vec![1i16, 2, 3, 4, 5, 6]);
+        let array = buffer.to_array2();
+
+        assert_eq!(array.dim(), (2, 3));
+        assert_eq!(array[[1, 0]], 4);
+        assert_eq!(TypedBuffer::from_array2(&array), buffer);
+    }
+
+    #[test]
+    fn read_band_as_array() {
+        let path = Path::new("testdata/test_u8.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u8>::from_rasterband(&band).unwrap();
+
+        let array = typed_band
+            .read_as_array(Window::new((0, 0), (8, 2)), (8, 2))
+            .unwrap();
+        assert_eq!(array[[0, 1]], 161);
+        assert_eq!(array[[1, 0]], 139);
+    }
+}
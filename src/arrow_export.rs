@@ -0,0 +1,126 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::transform::{self, GeoTransform};
+use arrow::array::{ArrayRef, Float64Array, PrimitiveArray, UInt32Array};
+use arrow::datatypes::{
+    ArrowPrimitiveType, Field, Float32Type, Float64Type, Int16Type, Int32Type, Schema, UInt16Type,
+    UInt32Type, UInt8Type,
+};
+use arrow::record_batch::RecordBatch;
+use std::sync::Arc;
+
+/// Pixel types with a corresponding Arrow primitive type.
+pub trait ArrowPixel: Copy {
+    type ArrowType: ArrowPrimitiveType<Native = Self>;
+}
+
+impl ArrowPixel for u8 {
+    type ArrowType = UInt8Type;
+}
+impl ArrowPixel for u16 {
+    type ArrowType = UInt16Type;
+}
+impl ArrowPixel for u32 {
+    type ArrowType = UInt32Type;
+}
+impl ArrowPixel for i16 {
+    type ArrowType = Int16Type;
+}
+impl ArrowPixel for i32 {
+    type ArrowType = Int32Type;
+}
+impl ArrowPixel for f32 {
+    type ArrowType = Float32Type;
+}
+impl ArrowPixel for f64 {
+    type ArrowType = Float64Type;
+}
+
+impl<T: ArrowPixel> TypedBuffer<T> {
+    /// Copies the pixels, in row-major order, into an Arrow array.
+    pub fn to_arrow_array(&self) -> PrimitiveArray<T::ArrowType> {
+        PrimitiveArray::from(self.data.clone())
+    }
+
+    /// Builds a record batch with one row per pixel. See `pixels_to_record_batch`.
+    pub fn to_record_batch(&self, gt: Option<&GeoTransform>) -> Result<RecordBatch> {
+        pixels_to_record_batch(self.pixels(), gt)
+    }
+}
+
+/// Builds a record batch from `(column, row, value)` pixels, with `col`,
+/// `row` and `value` fields. If a geotransform is given, `x` and `y` fields
+/// hold the map coordinates of each pixel centre.
+///
+/// Filter the pixels beforehand to, e.g., drop nodata values.
+pub fn pixels_to_record_batch<T, I>(pixels: I, gt: Option<&GeoTransform>) -> Result<RecordBatch>
+where
+    T: ArrowPixel,
+    I: IntoIterator<Item = (usize, usize, T)>,
+{
+    let mut cols = Vec::new();
+    let mut rows = Vec::new();
+    let mut values = Vec::new();
+    for (col, row, value) in pixels {
+        cols.push(col as u32);
+        rows.push(row as u32);
+        values.push(value);
+    }
+
+    let mut fields = vec![
+        Field::new("col", UInt32Type::DATA_TYPE, false),
+        Field::new("row", UInt32Type::DATA_TYPE, false),
+    ];
+    let mut columns: Vec<ArrayRef> = vec![
+        Arc::new(UInt32Array::from(cols.clone())),
+        Arc::new(UInt32Array::from(rows.clone())),
+    ];
+
+    if let Some(gt) = gt {
+        let (xs, ys): (Vec<f64>, Vec<f64>) = cols
+            .iter()
+            .zip(rows.iter())
+            .map(|(&c, &r)| transform::apply(gt, c as f64 + 0.5, r as f64 + 0.5))
+            .unzip();
+        fields.push(Field::new("x", Float64Type::DATA_TYPE, false));
+        fields.push(Field::new("y", Float64Type::DATA_TYPE, false));
+        columns.push(Arc::new(Float64Array::from(xs)));
+        columns.push(Arc::new(Float64Array::from(ys)));
+    }
+
+    fields.push(Field::new("value", T::ArrowType::DATA_TYPE, false));
+    columns.push(Arc::new(PrimitiveArray::<T::ArrowType>::from(values)));
+
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(fields)),
+        columns,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use arrow::array::{Float64Array, UInt16Array};
+
+    #[test]
+    fn buffer_record_batch() {
+        let buffer = TypedBuffer::new((2, 2), vec![1u16, 2, 3, 4]);
+        let gt = [100.0, 10.0, 0.0, 200.0, 0.0, -10.0];
+        let batch = buffer.to_record_batch(Some(&gt)).unwrap();
+
+        assert_eq!(batch.num_rows(), 4);
+        assert_eq!(batch.num_columns(), 5);
+        let x = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(x.value(1), 115.0);
+        let values = batch
+            .column(4)
+            .as_any()
+            .downcast_ref::<UInt16Array>()
+            .unwrap();
+        assert_eq!(values.value(3), 4);
+    }
+}
@@ -0,0 +1,96 @@
+use crate::blocks::block_window;
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::shared::SharedTypedBand;
+use crate::typed_rasterband::GdalFrom;
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+use std::io;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task;
+
+/// An async wrapper around a `SharedTypedBand`.
+///
+/// GDAL I/O is blocking, so reads are run on tokio's blocking thread pool.
+/// A semaphore bounds how many reads are in flight at once, so that a burst
+/// of requests can't occupy every blocking thread.
+#[derive(Debug, Clone)]
+pub struct AsyncTypedBand<T> {
+    band: SharedTypedBand<T>,
+    permits: Arc<Semaphore>,
+}
+
+impl<T> AsyncTypedBand<T>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Send + 'static,
+{
+    pub fn new(band: SharedTypedBand<T>, max_concurrent_reads: usize) -> AsyncTypedBand<T> {
+        AsyncTypedBand {
+            band,
+            permits: Arc::new(Semaphore::new(max_concurrent_reads)),
+        }
+    }
+
+    pub fn band(&self) -> &SharedTypedBand<T> {
+        &self.band
+    }
+
+    async fn run_blocking<R, F>(&self, f: F) -> Result<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(SharedTypedBand<T>) -> Result<R> + Send + 'static,
+    {
+        let _permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("read semaphore closed");
+        let band = self.band.clone();
+        task::spawn_blocking(move || f(band))
+            .await
+            .map_err(io::Error::other)?
+    }
+
+    /// Reads `window`, resampled to `size`.
+    pub async fn read_async(&self, window: Window, size: (usize, usize)) -> Result<TypedBuffer<T>> {
+        self.run_blocking(move |band| band.read(window, size)).await
+    }
+
+    /// Reads the natural block at column `block.0` and row `block.1` of the
+    /// block grid, returning the block's window along with its pixels.
+    pub async fn read_block_async(
+        &self,
+        block: (usize, usize),
+    ) -> Result<(Window, TypedBuffer<T>)> {
+        self.run_blocking(move |band| {
+            let (raster_size, block_size) = band.with_band(|b| (b.size(), b.block_size()))?;
+            let window = block_window(raster_size, block_size, block);
+            Ok((window, band.read(window, window.size)?))
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::async_read::AsyncTypedBand;
+    use crate::shared::SharedTypedBand;
+    use crate::window::Window;
+
+    #[tokio::test]
+    async fn read_band_async() {
+        let shared = SharedTypedBand::<u8>::new("testdata/test_u8.tif", 1).unwrap();
+        let band = AsyncTypedBand::new(shared, 2);
+
+        let buffer = band
+            .read_async(Window::new((0, 0), (2, 2)), (2, 2))
+            .await
+            .unwrap();
+        assert_eq!(buffer.data, vec![152, 161, 139, 164]);
+
+        let (window, _) = band.read_block_async((0, 13)).await.unwrap();
+        assert_eq!(window, Window::new((0, 312), (333, 21)));
+    }
+}
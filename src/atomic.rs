@@ -0,0 +1,144 @@
+use crate::create::DatasetBuilder;
+use crate::dataset::TypedDataset;
+use crate::errors::Result;
+use crate::typed_rasterband::GdalFrom;
+use gdal::raster::types::GdalType;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// The hidden temporary path next to `path` that an `AtomicWriter` writes
+/// to, in the same directory so that renaming it into place is atomic.
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(".{}.tmp", process::id()));
+    path.with_file_name(name)
+}
+
+/// `path` with `suffix` appended, e.g. for the `.aux.xml` file GDAL writes
+/// next to a dataset.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Removes the temporary files when dropped, unless they've been moved into
+/// place, so that errors and panics in the write pipeline both clean up.
+struct Cleanup<'p> {
+    temp: &'p Path,
+    armed: bool,
+}
+
+impl Drop for Cleanup<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            let _ = fs::remove_file(self.temp);
+            let _ = fs::remove_file(with_suffix(self.temp, ".aux.xml"));
+        }
+    }
+}
+
+/// Creates a dataset at a temporary path, runs a write pipeline on it, and
+/// renames it to its real path only once it has been written and closed.
+///
+/// Other processes never see a half-written file at the real path: if the
+/// pipeline fails or panics, the temporary file is removed and anything
+/// already at the real path is left alone.
+pub struct AtomicWriter<T> {
+    builder: DatasetBuilder<T>,
+    path: PathBuf,
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64> + Into<f64>> AtomicWriter<T> {
+    /// Writes the dataset `builder` describes, which must be created with a
+    /// driver that writes to a file, such as `"GTiff"`.
+    pub fn new(builder: DatasetBuilder<T>) -> AtomicWriter<T> {
+        let path = PathBuf::from(&builder.path);
+        AtomicWriter { builder, path }
+    }
+
+    /// The path the dataset will be moved to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Creates the dataset at a temporary path, calls `write` with it, and
+    /// on success flushes and closes it and moves it to `path`, along with
+    /// any `.aux.xml` file GDAL wrote next to it.
+    pub fn write<R, F>(mut self, write: F) -> Result<R>
+    where
+        F: FnOnce(&TypedDataset<T>) -> Result<R>,
+    {
+        let temp = temp_path(&self.path);
+        self.builder.path = temp.to_string_lossy().into_owned();
+        let mut cleanup = Cleanup {
+            temp: &temp,
+            armed: true,
+        };
+
+        let dataset = self.builder.create()?;
+        let result = write(&dataset)?;
+        dataset.flush_cache();
+        drop(dataset);
+
+        let aux = with_suffix(&temp, ".aux.xml");
+        if aux.exists() {
+            fs::rename(&aux, with_suffix(&self.path, ".aux.xml"))?;
+        }
+        fs::rename(&temp, &self.path)?;
+        cleanup.armed = false;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::atomic::{temp_path, AtomicWriter};
+    use crate::buffer::TypedBuffer;
+    use crate::create::DatasetBuilder;
+    use crate::errors::Error;
+    use crate::window::Window;
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn temp_path_is_hidden_sibling() {
+        let temp = temp_path(Path::new("/data/out/dem.tif"));
+        let name = temp.file_name().unwrap().to_string_lossy().into_owned();
+
+        assert_eq!(temp.parent(), Some(Path::new("/data/out")));
+        assert!(name.starts_with(".dem.tif."));
+        assert!(name.ends_with(".tmp"));
+    }
+
+    #[test]
+    fn rename_only_on_success() {
+        let dir = env::temp_dir().join("gdal_typed_rasterband_atomic");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("out.tif");
+        let _ = fs::remove_file(&path);
+        let builder = || DatasetBuilder::<u8>::new("GTiff", &path.to_string_lossy(), (4, 4));
+
+        let failed: Result<(), Error> = AtomicWriter::new(builder())
+            .write(|_| Err(Error::Alignment("pipeline failed".to_string())));
+        assert!(failed.is_err());
+        assert!(!path.exists());
+        assert!(!temp_path(&path).exists());
+
+        AtomicWriter::new(builder())
+            .write(|ds| {
+                ds.write(
+                    1,
+                    Window::new((0, 0), (1, 1)),
+                    &TypedBuffer::new((1, 1), vec![9]),
+                )
+            })
+            .unwrap();
+        assert!(path.exists());
+        assert!(!temp_path(&path).exists());
+    }
+}
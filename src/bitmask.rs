@@ -0,0 +1,198 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::{check_cpl_err, Result};
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+use gdal_sys::{GDALGetMaskBand, GDALRWFlag, GDALRasterIO};
+use std::os::raw::c_void;
+
+/// Anything that marks a subset of the pixels of a raster.
+pub trait PixelMask {
+    fn size(&self) -> (usize, usize);
+    fn is_set(&self, x: usize, y: usize) -> bool;
+}
+
+impl PixelMask for TypedBuffer<bool> {
+    fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    fn is_set(&self, x: usize, y: usize) -> bool {
+        self.get(x, y)
+    }
+}
+
+/// A `u8` mask is set wherever it is nonzero, as in GDAL mask bands.
+impl PixelMask for TypedBuffer<u8> {
+    fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    fn is_set(&self, x: usize, y: usize) -> bool {
+        self.get(x, y) != 0
+    }
+}
+
+/// A mask stored as one bit per pixel, in row-major order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitMaskBuffer {
+    size: (usize, usize),
+    words: Vec<u64>,
+}
+
+impl BitMaskBuffer {
+    /// A mask of `size` pixels, all set to `value`.
+    pub fn filled(size: (usize, usize), value: bool) -> BitMaskBuffer {
+        let pixels = size.0 * size.1;
+        let mut words = vec![if value { u64::MAX } else { 0 }; pixels.div_ceil(64)];
+        if value && !pixels.is_multiple_of(64) {
+            // Keep the unused bits clear so that masks compare equal.
+            *words.last_mut().unwrap() = (1 << (pixels % 64)) - 1;
+        }
+        BitMaskBuffer { size, words }
+    }
+
+    /// Copies any mask into bits.
+    pub fn from_mask<M: PixelMask>(mask: &M) -> BitMaskBuffer {
+        let size = mask.size();
+        let mut bits = BitMaskBuffer::filled(size, false);
+        for y in 0..size.1 {
+            for x in 0..size.0 {
+                if mask.is_set(x, y) {
+                    bits.set(x, y, true);
+                }
+            }
+        }
+        bits
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, value: bool) {
+        assert!(x < self.size.0 && y < self.size.1, "pixel out of bounds");
+        let i = y * self.size.0 + x;
+        if value {
+            self.words[i / 64] |= 1 << (i % 64);
+        } else {
+            self.words[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    /// The number of pixels that are set.
+    pub fn count_set(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// The bytes used to store the bits.
+    pub fn memory_used(&self) -> usize {
+        self.words.len() * 8
+    }
+
+    pub fn to_bool_buffer(&self) -> TypedBuffer<bool> {
+        let (width, height) = self.size;
+        let data = (0..width * height)
+            .map(|i| self.words[i / 64] & (1 << (i % 64)) != 0)
+            .collect();
+        TypedBuffer::new(self.size, data)
+    }
+
+    /// Converts to a GDAL-style mask band buffer: 255 where set, else 0.
+    pub fn to_u8_buffer(&self) -> TypedBuffer<u8> {
+        self.to_bool_buffer().map(|v| if v { 255 } else { 0 })
+    }
+}
+
+impl PixelMask for BitMaskBuffer {
+    fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    fn is_set(&self, x: usize, y: usize) -> bool {
+        let i = y * self.size.0 + x;
+        self.words[i / 64] & (1 << (i % 64)) != 0
+    }
+}
+
+impl<T: Copy> TypedBuffer<T> {
+    /// Sets every pixel where `mask` is set to `value`.
+    pub fn fill_where<M: PixelMask>(&mut self, mask: &M, value: T) {
+        assert_eq!(self.size, mask.size(), "mask size doesn't match buffer");
+        for y in 0..self.size.1 {
+            for x in 0..self.size.0 {
+                if mask.is_set(x, y) {
+                    self.set(x, y, value);
+                }
+            }
+        }
+    }
+}
+
+impl<'a, T: Copy + GdalType + GdalFrom<f64>, A: Access> TypedRasterBand<'a, T, A> {
+    /// Reads `window` of the band's GDAL mask, which is set where pixels are
+    /// valid.
+    pub fn read_mask(&self, window: Window) -> Result<BitMaskBuffer> {
+        let mut data = vec![0u8; window.size.0 * window.size.1];
+        let rv = unsafe {
+            GDALRasterIO(
+                GDALGetMaskBand(self.rasterband()._c_ptr()),
+                GDALRWFlag::GF_Read,
+                window.offset.0 as i32,
+                window.offset.1 as i32,
+                window.size.0 as i32,
+                window.size.1 as i32,
+                data.as_mut_ptr() as *mut c_void,
+                window.size.0 as i32,
+                window.size.1 as i32,
+                u8::gdal_type(),
+                0,
+                0,
+            )
+        };
+        check_cpl_err(rv)?;
+        Ok(BitMaskBuffer::from_mask(&TypedBuffer::new(
+            window.size,
+            data,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bitmask::{BitMaskBuffer, PixelMask};
+    use crate::buffer::TypedBuffer;
+    use crate::typed_rasterband::TypedRasterBand;
+    use crate::window::Window;
+    use gdal::raster::dataset::Dataset;
+    use std::path::Path;
+
+    #[test]
+    fn bit_mask_conversions() {
+        let bools = TypedBuffer::new(
+            (3, 3),
+            vec![true, false, false, true, true, false, false, false, true],
+        );
+        let bits = BitMaskBuffer::from_mask(&bools);
+
+        assert_eq!(bits.count_set(), 4);
+        assert!(bits.is_set(1, 1));
+        assert_eq!(bits.to_bool_buffer(), bools);
+        assert_eq!(BitMaskBuffer::from_mask(&bits.to_u8_buffer()), bits);
+        assert_eq!(BitMaskBuffer::filled((3, 3), true).count_set(), 9);
+
+        let mut values = TypedBuffer::filled((3, 3), 7u16);
+        values.fill_where(&bits, 0);
+        assert_eq!(values.row(1), &[0, 0, 7]);
+    }
+
+    #[test]
+    fn read_nodata_mask() {
+        let path = Path::new("testdata/test_u16_nodata.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u16>::from_rasterband(&band).unwrap();
+
+        let window = Window::new((0, 0), (333, 333));
+        let mask = typed_band.read_mask(window).unwrap();
+        let buffer: TypedBuffer<u16> = typed_band.read_band().unwrap().into();
+        let valid = buffer.data.iter().filter(|&&v| v != 42).count();
+        assert_eq!(mask.count_set(), valid);
+    }
+}
@@ -0,0 +1,321 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::trace::{buffer_bytes, instrument};
+use crate::typed_rasterband::{Access, GdalFrom, ReadOnly, TypedRasterBand};
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+use gdal_sys::GDALGetDataCoverageStatus;
+use std::mem;
+use std::ptr;
+
+// `GDALGetDataCoverageStatus` returns this flag alone when a region has no
+// data; drivers that can't tell return an "unimplemented" flag instead.
+const COVERAGE_STATUS_EMPTY: i32 = 0x04;
+
+/// Divides a raster into windows of `block_size`, in row-major order.
+/// Windows along the right and bottom edges are clipped to the raster.
+pub fn block_windows(raster_size: (usize, usize), block_size: (usize, usize)) -> Vec<Window> {
+    assert!(
+        block_size.0 > 0 && block_size.1 > 0,
+        "block size must be nonzero"
+    );
+
+    let (width, height) = raster_size;
+    let mut windows = Vec::new();
+    for y0 in (0..height).step_by(block_size.1) {
+        for x0 in (0..width).step_by(block_size.0) {
+            windows.push(Window::new(
+                (x0 as isize, y0 as isize),
+                (block_size.0.min(width - x0), block_size.1.min(height - y0)),
+            ));
+        }
+    }
+    windows
+}
+
+/// The window of the block at column `block.0` and row `block.1` of the
+/// block grid, clipped to the raster.
+pub fn block_window(
+    raster_size: (usize, usize),
+    block_size: (usize, usize),
+    block: (usize, usize),
+) -> Window {
+    let x0 = block.0 * block_size.0;
+    let y0 = block.1 * block_size.1;
+    Window::new(
+        (x0 as isize, y0 as isize),
+        (
+            block_size.0.min(raster_size.0.saturating_sub(x0)),
+            block_size.1.min(raster_size.1.saturating_sub(y0)),
+        ),
+    )
+}
+
+/// Picks the largest chunk that keeps a buffer of `pixel_bytes`-sized
+/// pixels under `budget` bytes.
+///
+/// Chunks span the full raster width and a whole number of block rows where
+/// possible; when even one block row is over budget they fall back to fewer
+/// rows, and then to partial rows. A chunk is never smaller than one pixel.
+pub fn chunk_size_for_budget(
+    raster_size: (usize, usize),
+    block_size: (usize, usize),
+    pixel_bytes: usize,
+    budget: usize,
+) -> (usize, usize) {
+    let (width, height) = raster_size;
+    let max_pixels = (budget / pixel_bytes).max(1);
+
+    if width <= max_pixels {
+        let rows = (max_pixels / width).min(height);
+        let rows = if rows >= block_size.1 {
+            rows - rows % block_size.1
+        } else {
+            rows
+        };
+        (width, rows.max(1))
+    } else {
+        (max_pixels, 1)
+    }
+}
+
+pub struct Blocks<'a, 'b, T: Copy + GdalType, A: Access = ReadOnly> {
+    band: &'b TypedRasterBand<'a, T, A>,
+    windows: std::vec::IntoIter<Window>,
+}
+
+/// A block from `TypedRasterBand::sparse_blocks`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockStatus<T> {
+    /// The block holds no data in the file, so it wasn't read.
+    Empty,
+    Data(TypedBuffer<T>),
+}
+
+pub struct SparseBlocks<'a, 'b, T: Copy + GdalType, A: Access = ReadOnly> {
+    blocks: Blocks<'a, 'b, T, A>,
+}
+
+impl<'a, T: Copy + GdalType + GdalFrom<f64>, A: Access> TypedRasterBand<'a, T, A> {
+    /// The windows of the band's natural block layout.
+    pub fn block_windows(&self) -> Vec<Window> {
+        block_windows(self.size(), self.block_size())
+    }
+
+    /// Reads the band in chunks sized so that each buffer stays under
+    /// `budget` bytes, calling `f` with each chunk in row-major order.
+    pub fn process_with_memory_limit<F>(&self, budget: usize, mut f: F) -> Result<()>
+    where
+        F: FnMut(Window, TypedBuffer<T>) -> Result<()>,
+    {
+        let chunk_size =
+            chunk_size_for_budget(self.size(), self.block_size(), mem::size_of::<T>(), budget);
+        for window in block_windows(self.size(), chunk_size) {
+            let buffer = instrument("chunk", window, buffer_bytes::<T>(window.size), || {
+                self.read(window.offset, window.size, window.size)
+            })?;
+            f(window, buffer.into())?;
+        }
+        Ok(())
+    }
+
+    /// Reads the band one natural block at a time.
+    pub fn blocks<'b>(&'b self) -> Blocks<'a, 'b, T, A> {
+        Blocks {
+            band: self,
+            windows: self.block_windows().into_iter(),
+        }
+    }
+
+    /// Whether the file stores no data at all for `window`, as for missing
+    /// tiles in a sparse GeoTIFF. Drivers that can't tell report `false`.
+    pub fn is_empty_region(&self, window: Window) -> bool {
+        let status = unsafe {
+            GDALGetDataCoverageStatus(
+                self.rasterband()._c_ptr(),
+                window.offset.0 as i32,
+                window.offset.1 as i32,
+                window.size.0 as i32,
+                window.size.1 as i32,
+                0,
+                ptr::null_mut(),
+            )
+        };
+        status == COVERAGE_STATUS_EMPTY
+    }
+
+    /// Reads the band one natural block at a time like `blocks`, but skips
+    /// decoding blocks the file doesn't store.
+    pub fn sparse_blocks<'b>(&'b self) -> SparseBlocks<'a, 'b, T, A> {
+        SparseBlocks {
+            blocks: self.blocks(),
+        }
+    }
+}
+
+impl<'a, 'b, T: Copy + GdalType + GdalFrom<f64>, A: Access> Iterator for Blocks<'a, 'b, T, A> {
+    type Item = Result<(Window, TypedBuffer<T>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let window = self.windows.next()?;
+        let band = self.band;
+        Some(instrument(
+            "block",
+            window,
+            buffer_bytes::<T>(window.size),
+            || {
+                band.read(window.offset, window.size, window.size)
+                    .map(|buffer| (window, buffer.into()))
+                    .map_err(|e| e.into())
+            },
+        ))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.windows.size_hint()
+    }
+}
+
+impl<'a, 'b, T: Copy + GdalType + GdalFrom<f64>, A: Access> Iterator
+    for SparseBlocks<'a, 'b, T, A>
+{
+    type Item = Result<(Window, BlockStatus<T>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let window = *self.blocks.windows.as_slice().first()?;
+        if self.blocks.band.is_empty_region(window) {
+            self.blocks.windows.next();
+            return Some(Ok((window, BlockStatus::Empty)));
+        }
+        Some(
+            self.blocks
+                .next()?
+                .map(|(window, buffer)| (window, BlockStatus::Data(buffer))),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.blocks.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::blocks::{block_window, block_windows, chunk_size_for_budget, BlockStatus};
+    use crate::buffer::TypedBuffer;
+    use crate::create::DatasetBuilder;
+    use crate::dataset::TypedDataset;
+    use crate::typed_rasterband::TypedRasterBand;
+    use crate::window::Window;
+    use gdal::raster::dataset::Dataset;
+    use std::env;
+    use std::path::Path;
+
+    #[test]
+    fn clipped_block_windows() {
+        let windows = block_windows((10, 5), (4, 5));
+
+        assert_eq!(windows.len(), 3);
+        assert_eq!(windows[2], Window::new((8, 0), (2, 5)));
+        assert_eq!(block_window((10, 5), (4, 5), (2, 0)), windows[2]);
+    }
+
+    #[test]
+    fn chunk_sizes_under_budget() {
+        // Whole block rows when they fit...
+        assert_eq!(
+            chunk_size_for_budget((100, 100), (100, 8), 2, 5000),
+            (100, 24)
+        );
+        // ...then partial block rows, then partial rows.
+        assert_eq!(
+            chunk_size_for_budget((100, 100), (100, 8), 2, 1000),
+            (100, 5)
+        );
+        assert_eq!(chunk_size_for_budget((100, 100), (100, 8), 2, 100), (50, 1));
+    }
+
+    #[test]
+    fn process_band_under_budget() {
+        let path = Path::new("testdata/test_u8.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u8>::from_rasterband(&band).unwrap();
+
+        let mut chunks = 0;
+        let mut sum = 0;
+        typed_band
+            .process_with_memory_limit(333 * 50, |_, buffer| {
+                chunks += 1;
+                sum += buffer.data.iter().map(|&v| v as u64).sum::<u64>();
+                Ok(())
+            })
+            .unwrap();
+        // The budget fits two 24-row strips.
+        assert_eq!(chunks, 7);
+        assert_eq!(sum, 14130952);
+    }
+
+    #[test]
+    fn read_band_blocks() {
+        let path = Path::new("testdata/test_u8.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u8>::from_rasterband(&band).unwrap();
+
+        let blocks: Vec<_> = typed_band.blocks().map(|b| b.unwrap()).collect();
+        // The test file is stripped with 24 rows per strip.
+        assert_eq!(blocks.len(), 14);
+        assert_eq!(blocks[13].0, Window::new((0, 312), (333, 21)));
+        assert_eq!(blocks[0].1.get(0, 1), 139);
+    }
+
+    #[test]
+    fn sparse_blocks_of_dense_file() {
+        let path = Path::new("testdata/test_u8.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u8>::from_rasterband(&band).unwrap();
+
+        // Every strip of the test file is written, so none are skipped.
+        let blocks: Vec<_> = typed_band.sparse_blocks().map(|b| b.unwrap()).collect();
+        assert_eq!(blocks.len(), 14);
+        match &blocks[0].1 {
+            BlockStatus::Data(buffer) => assert_eq!(buffer.get(0, 1), 139),
+            BlockStatus::Empty => panic!("expected data in the first block"),
+        }
+    }
+
+    #[test]
+    fn sparse_blocks_of_sparse_file() {
+        let path = env::temp_dir().join("gdal_typed_rasterband_sparse.tif");
+        {
+            let ds = DatasetBuilder::<u8>::new("GTiff", &path.to_string_lossy(), (64, 32))
+                .creation_option("SPARSE_OK", "TRUE")
+                .creation_option("TILED", "YES")
+                .creation_option("BLOCKXSIZE", "16")
+                .creation_option("BLOCKYSIZE", "16")
+                .create()
+                .unwrap();
+            let tile = TypedBuffer::filled((16, 16), 7u8);
+            ds.write(1, Window::new((16, 16), (16, 16)), &tile).unwrap();
+        }
+
+        let ds = TypedDataset::<u8>::open(&path).unwrap();
+        let statuses = ds
+            .with_band(1, |band| {
+                band.sparse_blocks().map(|b| b.unwrap()).collect::<Vec<_>>()
+            })
+            .unwrap();
+        assert_eq!(statuses.len(), 8);
+        for (window, status) in statuses {
+            match status {
+                BlockStatus::Data(buffer) => {
+                    assert_eq!(window, Window::new((16, 16), (16, 16)));
+                    assert_eq!(buffer.get(0, 0), 7);
+                }
+                BlockStatus::Empty => assert_ne!(window.offset, (16, 16)),
+            }
+        }
+    }
+}
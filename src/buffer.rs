@@ -0,0 +1,94 @@
+use gdal::raster::dataset::Buffer;
+use gdal::raster::types::GdalType;
+
+/// An owned block of pixels stored in row-major order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedBuffer<T> {
+    pub size: (usize, usize),
+    pub data: Vec<T>,
+}
+
+impl<T: Copy> TypedBuffer<T> {
+    pub fn new(size: (usize, usize), data: Vec<T>) -> TypedBuffer<T> {
+        assert_eq!(
+            size.0 * size.1,
+            data.len(),
+            "buffer size doesn't match length of data"
+        );
+        TypedBuffer { size, data }
+    }
+
+    pub fn filled(size: (usize, usize), value: T) -> TypedBuffer<T> {
+        TypedBuffer {
+            size,
+            data: vec![value; size.0 * size.1],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.size.0
+    }
+
+    pub fn height(&self) -> usize {
+        self.size.1
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> T {
+        self.data[y * self.size.0 + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, value: T) {
+        self.data[y * self.size.0 + x] = value;
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.data[y * self.size.0..(y + 1) * self.size.0]
+    }
+
+    /// Iterates over `(x, y, value)` for every pixel in row-major order.
+    pub fn pixels<'a>(&'a self) -> impl Iterator<Item = (usize, usize, T)> + 'a {
+        let width = self.size.0;
+        self.data
+            .iter()
+            .enumerate()
+            .map(move |(i, &v)| (i % width, i / width, v))
+    }
+
+    /// Applies `f` to every pixel, producing a buffer of the same size.
+    pub fn map<U: Copy, F: Fn(T) -> U>(&self, f: F) -> TypedBuffer<U> {
+        TypedBuffer {
+            size: self.size,
+            data: self.data.iter().map(|&v| f(v)).collect(),
+        }
+    }
+}
+
+impl<T: Copy + GdalType> TypedBuffer<T> {
+    pub fn into_buffer(self) -> Buffer<T> {
+        Buffer::new(self.size, self.data)
+    }
+}
+
+impl<T: Copy + GdalType> From<Buffer<T>> for TypedBuffer<T> {
+    fn from(buffer: Buffer<T>) -> TypedBuffer<T> {
+        TypedBuffer {
+            size: buffer.size,
+            data: buffer.data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffer_indexing() {
+        let mut buffer = TypedBuffer::new((3, 2), vec![0u8, 1, 2, 3, 4, 5]);
+        buffer.set(2, 0, 9);
+
+        assert_eq!(buffer.get(1, 1), 4);
+        assert_eq!(buffer.row(0), &[0, 1, 9]);
+        assert_eq!(buffer.map(|v| v as u16 * 2).data, vec![0, 2, 18, 6, 8, 10]);
+    }
+}
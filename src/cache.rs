@@ -0,0 +1,196 @@
+use crate::blocks::block_window;
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::io_stats::IoStats;
+use crate::shared::SharedTypedBand;
+use crate::typed_rasterband::GdalFrom;
+use gdal::raster::types::GdalType;
+use std::collections::{BTreeMap, HashMap};
+use std::mem;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+type BlockKey = (PathBuf, isize, (usize, usize));
+
+struct Entry<T> {
+    buffer: Arc<TypedBuffer<T>>,
+    last_used: u64,
+}
+
+struct CacheState<T> {
+    entries: HashMap<BlockKey, Entry<T>>,
+    // Maps each entry's last use to its key, so the least recently used
+    // entry is always first.
+    recency: BTreeMap<u64, BlockKey>,
+    clock: u64,
+    bytes: usize,
+}
+
+impl<T> CacheState<T> {
+    fn touch(&mut self, key: &BlockKey) -> Option<Arc<TypedBuffer<T>>> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(key)?;
+        self.recency.remove(&entry.last_used);
+        entry.last_used = self.clock;
+        self.recency.insert(self.clock, key.clone());
+        Some(entry.buffer.clone())
+    }
+
+    fn evict_lru(&mut self) {
+        let oldest = match self.recency.keys().next() {
+            Some(&oldest) => oldest,
+            None => return,
+        };
+        let key = self.recency.remove(&oldest).unwrap();
+        let entry = self.entries.remove(&key).unwrap();
+        self.bytes -= buffer_bytes(&entry.buffer);
+    }
+}
+
+fn buffer_bytes<T>(buffer: &TypedBuffer<T>) -> usize {
+    buffer.data.len() * mem::size_of::<T>()
+}
+
+/// A least-recently-used cache of decoded blocks, keyed by dataset path,
+/// band and block, and bounded by the memory its buffers use.
+///
+/// The cache can be shared between threads. Blocks are returned behind an
+/// `Arc`, so evicting a block doesn't invalidate buffers still in use.
+pub struct BlockCache<T> {
+    budget: usize,
+    stats: Option<Arc<IoStats>>,
+    state: Mutex<CacheState<T>>,
+}
+
+impl<T> BlockCache<T>
+where
+    T: Copy + GdalType + GdalFrom<f64>,
+{
+    /// Creates a cache holding at most `budget` bytes of pixel data.
+    pub fn new(budget: usize) -> BlockCache<T> {
+        BlockCache {
+            budget,
+            stats: None,
+            state: Mutex::new(CacheState {
+                entries: HashMap::new(),
+                recency: BTreeMap::new(),
+                clock: 0,
+                bytes: 0,
+            }),
+        }
+    }
+
+    /// Counts lookups, and the reads done on misses, in `stats`.
+    pub fn with_io_stats(mut self, stats: Arc<IoStats>) -> BlockCache<T> {
+        self.stats = Some(stats);
+        self
+    }
+
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    /// The number of blocks currently cached.
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of bytes of pixel data currently cached.
+    pub fn memory_used(&self) -> usize {
+        self.state.lock().unwrap().bytes
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.entries.clear();
+        state.recency.clear();
+        state.bytes = 0;
+    }
+
+    /// Returns the natural block at column `block.0` and row `block.1` of
+    /// `band`, reading it on a miss.
+    ///
+    /// The cache isn't locked during the read, so concurrent misses on the
+    /// same block may each read it.
+    pub fn get_or_read(
+        &self,
+        band: &SharedTypedBand<T>,
+        block: (usize, usize),
+    ) -> Result<Arc<TypedBuffer<T>>> {
+        let key = (band.path().to_path_buf(), band.band_index(), block);
+        let cached = self.state.lock().unwrap().touch(&key);
+        if let Some(stats) = &self.stats {
+            stats.record_cache_lookup(cached.is_some());
+        }
+        if let Some(buffer) = cached {
+            return Ok(buffer);
+        }
+
+        let (window, block_size) = band.with_band(|b| {
+            (
+                block_window(b.size(), b.block_size(), block),
+                b.block_size(),
+            )
+        })?;
+        let buffer = Arc::new(band.read(window, window.size)?);
+        if let Some(stats) = &self.stats {
+            stats.record_read(window, window.size, block_size, mem::size_of::<T>());
+        }
+        self.insert(key, buffer.clone());
+        Ok(buffer)
+    }
+
+    fn insert(&self, key: BlockKey, buffer: Arc<TypedBuffer<T>>) {
+        let size = buffer_bytes(&buffer);
+        if size > self.budget {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        if state.touch(&key).is_some() {
+            // Another thread cached the block while we were reading it.
+            return;
+        }
+        while state.bytes + size > self.budget {
+            state.evict_lru();
+        }
+        let last_used = state.clock;
+        state.recency.insert(last_used, key.clone());
+        state.entries.insert(key, Entry { buffer, last_used });
+        state.bytes += size;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::BlockCache;
+    use crate::shared::SharedTypedBand;
+    use std::sync::Arc;
+
+    #[test]
+    fn cache_evicts_least_recently_used() {
+        let band = SharedTypedBand::<u8>::new("testdata/test_u8.tif", 1).unwrap();
+        // Blocks are 333x24 bytes, so the budget fits two of them.
+        let cache = BlockCache::new(2 * 333 * 24);
+
+        let first = cache.get_or_read(&band, (0, 0)).unwrap();
+        cache.get_or_read(&band, (0, 1)).unwrap();
+        assert!(Arc::ptr_eq(
+            &first,
+            &cache.get_or_read(&band, (0, 0)).unwrap()
+        ));
+
+        cache.get_or_read(&band, (0, 2)).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.memory_used(), 2 * 333 * 24);
+        assert!(Arc::ptr_eq(
+            &first,
+            &cache.get_or_read(&band, (0, 0)).unwrap()
+        ));
+        assert_eq!(first.get(0, 1), 139);
+    }
+}
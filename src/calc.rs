@@ -0,0 +1,334 @@
+use crate::blocks::block_windows;
+use crate::buffer::TypedBuffer;
+use crate::errors::{Error, Result};
+use crate::simd::{convert_from_f64, nodata_mask, SimdPixel};
+use crate::sink::RasterSink;
+use crate::source::RasterSource;
+
+/// The rows `calc` evaluates at once.
+const CHUNK_ROWS: usize = 256;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Symbol(char),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // An exponent, as in 1e-3.
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let number: String = chars[start..i].iter().collect();
+            let value = number
+                .parse()
+                .map_err(|_| Error::Expression(format!("bad number `{}`", number)))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Name(chars[start..i].iter().collect()));
+        } else if "+-*/^()".contains(c) {
+            tokens.push(Token::Symbol(c));
+            i += 1;
+        } else {
+            return Err(Error::Expression(format!("unexpected `{}`", c)));
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Number(f64),
+    /// The band at this index of `Expression::bands`.
+    Band(usize),
+    Neg(Box<Node>),
+    Binary(char, Box<Node>, Box<Node>),
+}
+
+/// A recursive descent parser, with the usual precedence: `^` binds
+/// tightest and to the right, then unary minus, then `*` and `/`, then `+`
+/// and `-`.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    bands: Vec<String>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn sum(&mut self) -> Result<Node> {
+        let mut node = self.product()?;
+        loop {
+            let op = if self.eat('+') {
+                '+'
+            } else if self.eat('-') {
+                '-'
+            } else {
+                return Ok(node);
+            };
+            node = Node::Binary(op, Box::new(node), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<Node> {
+        let mut node = self.unary()?;
+        loop {
+            let op = if self.eat('*') {
+                '*'
+            } else if self.eat('/') {
+                '/'
+            } else {
+                return Ok(node);
+            };
+            node = Node::Binary(op, Box::new(node), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Node> {
+        if self.eat('-') {
+            Ok(Node::Neg(Box::new(self.unary()?)))
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> Result<Node> {
+        let base = self.atom()?;
+        if self.eat('^') {
+            Ok(Node::Binary('^', Box::new(base), Box::new(self.unary()?)))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn atom(&mut self) -> Result<Node> {
+        let token = self.peek().cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Number(value)) => Ok(Node::Number(value)),
+            Some(Token::Name(name)) => {
+                let index = match self.bands.iter().position(|b| *b == name) {
+                    Some(index) => index,
+                    None => {
+                        self.bands.push(name);
+                        self.bands.len() - 1
+                    }
+                };
+                Ok(Node::Band(index))
+            }
+            Some(Token::Symbol('(')) => {
+                let node = self.sum()?;
+                if !self.eat(')') {
+                    return Err(Error::Expression("missing `)`".to_string()));
+                }
+                Ok(node)
+            }
+            Some(Token::Symbol(c)) => Err(Error::Expression(format!("unexpected `{}`", c))),
+            None => Err(Error::Expression("unexpected end".to_string())),
+        }
+    }
+}
+
+/// An arithmetic expression over named bands, such as
+/// `(B2 - B1) / (B2 + B1)`, with `+`, `-`, `*`, `/`, `^`, parentheses and
+/// numbers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    root: Node,
+    bands: Vec<String>,
+}
+
+impl Expression {
+    pub fn parse(text: &str) -> Result<Expression> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            pos: 0,
+            bands: Vec::new(),
+        };
+        let root = parser.sum()?;
+        if let Some(token) = parser.peek() {
+            return Err(Error::Expression(format!("unexpected {:?}", token)));
+        }
+        Ok(Expression {
+            root,
+            bands: parser.bands,
+        })
+    }
+
+    /// The names of the bands the expression refers to, in order of first
+    /// use.
+    pub fn bands(&self) -> &[String] {
+        &self.bands
+    }
+
+    /// Evaluates the expression with `values[i]` for band `bands()[i]`.
+    pub fn eval(&self, values: &[f64]) -> f64 {
+        fn eval(node: &Node, values: &[f64]) -> f64 {
+            match node {
+                Node::Number(value) => *value,
+                Node::Band(index) => values[*index],
+                Node::Neg(node) => -eval(node, values),
+                Node::Binary(op, a, b) => {
+                    let (a, b) = (eval(a, values), eval(b, values));
+                    match op {
+                        '+' => a + b,
+                        '-' => a - b,
+                        '*' => a * b,
+                        '/' => a / b,
+                        _ => a.powf(b),
+                    }
+                }
+            }
+        }
+        eval(&self.root, values)
+    }
+}
+
+/// Evaluates `expression` for every pixel, with each band name bound to a
+/// source in `bindings`, writing the results to `sink`, in the manner of
+/// `gdal_calc.py`. The sources and sink must be the same size, and are read
+/// and written a chunk of rows at a time.
+///
+/// Pixels where any source used is nodata, or where the result isn't
+/// finite, such as after dividing by zero, are set to `nodata`.
+pub fn calc<T, U, K>(
+    expression: &str,
+    bindings: &[(&str, &dyn RasterSource<T>)],
+    nodata: U,
+    sink: &mut K,
+) -> Result<()>
+where
+    T: Copy + Into<f64> + PartialEq,
+    U: SimdPixel,
+    K: RasterSink<U>,
+{
+    let expression_text = expression;
+    let expression = Expression::parse(expression)?;
+    let size = sink.size();
+    let mut sources = Vec::with_capacity(expression.bands().len());
+    for name in expression.bands() {
+        let source = match bindings.iter().find(|(n, _)| n == name) {
+            Some((_, source)) => *source,
+            None => return Err(Error::Expression(format!("`{}` is not bound", name))),
+        };
+        if source.size() != size {
+            return Err(Error::Alignment(format!(
+                "`{}` is {:?} pixels but the sink is {:?}",
+                name,
+                source.size(),
+                size
+            )));
+        }
+        sources.push((source, source.no_data_value()));
+    }
+    if size.0 * size.1 == 0 {
+        sink.flush()?;
+        return sink.record_history("calc", &[("expression", expression_text.to_string())]);
+    }
+
+    let mut values = vec![0.0; sources.len()];
+    for window in block_windows(size, (size.0, CHUNK_ROWS.min(size.1))) {
+        let inputs = sources
+            .iter()
+            .map(|(source, _)| source.read_window(window))
+            .collect::<Result<Vec<_>>>()?;
+        let masks: Vec<Option<Vec<bool>>> = inputs
+            .iter()
+            .zip(&sources)
+            .map(|(input, (_, nodata))| nodata.map(|n| nodata_mask(&input.data, n)))
+            .collect();
+        let results: Vec<f64> = (0..window.size.0 * window.size.1)
+            .map(|i| {
+                if masks.iter().flatten().any(|mask| mask[i]) {
+                    return f64::NAN;
+                }
+                for (value, input) in values.iter_mut().zip(&inputs) {
+                    *value = input.data[i].into();
+                }
+                expression.eval(&values)
+            })
+            .collect();
+        let mut output = TypedBuffer::new(window.size, convert_from_f64(&results));
+        for (pixel, result) in output.data.iter_mut().zip(&results) {
+            if !result.is_finite() {
+                *pixel = nodata;
+            }
+        }
+        sink.write_window(window, &output)?;
+    }
+    sink.flush()?;
+    sink.record_history("calc", &[("expression", expression_text.to_string())])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::calc::{calc, Expression};
+    use crate::source::RasterSource;
+
+    #[test]
+    fn parse_and_eval() {
+        let ndvi = Expression::parse("(B2 - B1) / (B2 + B1)").unwrap();
+        assert_eq!(ndvi.bands(), ["B2", "B1"]);
+        assert_eq!(ndvi.eval(&[3.0, 1.0]), 0.5);
+
+        let precedence = Expression::parse("-a^2 + 2 * a - 1e1 / 4").unwrap();
+        assert_eq!(precedence.eval(&[3.0]), -5.5);
+        assert_eq!(Expression::parse("2^3^2").unwrap().eval(&[]), 512.0);
+
+        assert!(Expression::parse("(a + b").is_err());
+        assert!(Expression::parse("a b").is_err());
+        assert!(Expression::parse("a % b").is_err());
+    }
+
+    #[test]
+    fn calc_into_buffer() {
+        let red = TypedBuffer::new((3, 1), vec![1u16, 0, 2]);
+        let nir = TypedBuffer::new((3, 1), vec![3u16, 0, 2]);
+        let mut output = TypedBuffer::filled((3, 1), 0.0f32);
+        let bindings: [(&str, &dyn RasterSource<u16>); 2] = [("B1", &red), ("B2", &nir)];
+
+        calc("(B2 - B1) / (B2 + B1)", &bindings, -9999.0, &mut output).unwrap();
+        assert_eq!(output.data, vec![0.5, -9999.0, 0.0]);
+
+        assert!(calc("B3 * 2", &bindings, -9999.0, &mut output).is_err());
+    }
+}
@@ -0,0 +1,198 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::{Error, Result};
+use crate::timeseries::same_geo_transform;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use gdal::raster::types::GdalType;
+
+/// Mask values written by `change_mask`.
+pub const UNCHANGED: u8 = 0;
+pub const INCREASED: u8 = 1;
+pub const DECREASED: u8 = 2;
+pub const NO_DATA: u8 = 255;
+
+/// An equal-width histogram of differences.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    pub min: f64,
+    pub max: f64,
+    pub counts: Vec<u64>,
+}
+
+impl Histogram {
+    const BINS: usize = 32;
+
+    fn of(values: &[f64]) -> Histogram {
+        let valid = values.iter().cloned().filter(|v| !v.is_nan());
+        let (min, max) = valid.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(v), hi.max(v))
+        });
+        let mut counts = vec![0; Histogram::BINS];
+        if min <= max {
+            let width = (max - min) / Histogram::BINS as f64;
+            for &v in values.iter().filter(|v| !v.is_nan()) {
+                let bin = if width > 0.0 {
+                    (((v - min) / width) as usize).min(Histogram::BINS - 1)
+                } else {
+                    0
+                };
+                counts[bin] += 1;
+            }
+        }
+        Histogram { min, max, counts }
+    }
+}
+
+/// Summary of a change mask.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeSummary {
+    pub increased_pixels: usize,
+    pub decreased_pixels: usize,
+    pub unchanged_pixels: usize,
+    /// The area of changed pixels in the units of the geotransform, if the
+    /// bands have one.
+    pub changed_area: Option<f64>,
+    /// The distribution of valid differences.
+    pub histogram: Histogram,
+}
+
+/// The output of `change_mask`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeMask {
+    /// One of `UNCHANGED`, `INCREASED`, `DECREASED` or `NO_DATA` per pixel.
+    pub mask: TypedBuffer<u8>,
+    pub summary: ChangeSummary,
+}
+
+impl<'a, T, A: Access> TypedRasterBand<'a, T, A>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Into<f64>,
+{
+    /// Checks that `other` has the same size and geotransform as the band.
+    pub(crate) fn check_same_grid<U, B>(&self, other: &TypedRasterBand<U, B>) -> Result<()>
+    where
+        U: Copy + GdalType + GdalFrom<f64>,
+        B: Access,
+    {
+        if self.size() != other.size() {
+            return Err(Error::Alignment(format!(
+                "band sizes {:?} and {:?} differ",
+                self.size(),
+                other.size()
+            )));
+        }
+        if !same_geo_transform(
+            self.owning_dataset().geo_transform().ok(),
+            other.owning_dataset().geo_transform().ok(),
+        ) {
+            return Err(Error::Alignment(
+                "bands have different geotransforms".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Computes `other - self` for every pixel, after checking that the
+    /// bands share a grid. Pixels that are nodata in either band are NaN.
+    pub fn diff<B: Access>(&self, other: &TypedRasterBand<T, B>) -> Result<TypedBuffer<f64>> {
+        self.check_same_grid(other)?;
+
+        let before: TypedBuffer<T> = self.read_band()?.into();
+        let after: TypedBuffer<T> = other.read_band()?.into();
+        let nodata_before = self.no_data_value().map(Into::into);
+        let nodata_after = other.no_data_value().map(Into::into);
+
+        let data = before
+            .data
+            .iter()
+            .zip(&after.data)
+            .map(|(&b, &a)| {
+                let (b, a): (f64, f64) = (b.into(), a.into());
+                if Some(b) == nodata_before || Some(a) == nodata_after {
+                    f64::NAN
+                } else {
+                    a - b
+                }
+            })
+            .collect();
+        Ok(TypedBuffer::new(before.size, data))
+    }
+
+    /// Classifies each pixel as increased, decreased or unchanged, depending
+    /// on whether `other - self` exceeds `threshold` in either direction.
+    pub fn change_mask<B: Access>(
+        &self,
+        other: &TypedRasterBand<T, B>,
+        threshold: f64,
+    ) -> Result<ChangeMask> {
+        let diff = self.diff(other)?;
+        let mask = diff.map(|d| {
+            if d.is_nan() {
+                NO_DATA
+            } else if d > threshold {
+                INCREASED
+            } else if d < -threshold {
+                DECREASED
+            } else {
+                UNCHANGED
+            }
+        });
+
+        let count = |class| mask.data.iter().filter(|&&v| v == class).count();
+        let (increased_pixels, decreased_pixels) = (count(INCREASED), count(DECREASED));
+        let changed_area = self
+            .owning_dataset()
+            .geo_transform()
+            .ok()
+            .map(|gt| (gt[1] * gt[5] - gt[2] * gt[4]).abs())
+            .map(|pixel_area| pixel_area * (increased_pixels + decreased_pixels) as f64);
+
+        Ok(ChangeMask {
+            summary: ChangeSummary {
+                increased_pixels,
+                decreased_pixels,
+                unchanged_pixels: count(UNCHANGED),
+                changed_area,
+                histogram: Histogram::of(&diff.data),
+            },
+            mask,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::change::{Histogram, NO_DATA, UNCHANGED};
+    use crate::typed_rasterband::TypedRasterBand;
+    use gdal::raster::dataset::Dataset;
+    use std::path::Path;
+
+    #[test]
+    fn difference_histogram() {
+        let histogram = Histogram::of(&[-1.0, 0.0, f64::NAN, 1.0, 1.0]);
+
+        assert_eq!((histogram.min, histogram.max), (-1.0, 1.0));
+        assert_eq!(histogram.counts[0], 1);
+        assert_eq!(histogram.counts[31], 2);
+        assert_eq!(histogram.counts.iter().sum::<u64>(), 4);
+    }
+
+    #[test]
+    fn change_against_nodata_copy() {
+        let ds = Dataset::open(Path::new("testdata/test_u16.tif")).unwrap();
+        let ds_nodata = Dataset::open(Path::new("testdata/test_u16_nodata.tif")).unwrap();
+        let (band, band_nodata) = (ds.rasterband(1).unwrap(), ds_nodata.rasterband(1).unwrap());
+        let before = TypedRasterBand::<u16>::from_rasterband(&band).unwrap();
+        let after = TypedRasterBand::<u16>::from_rasterband(&band_nodata).unwrap();
+
+        let change = before.change_mask(&after, 10.0).unwrap();
+        assert_eq!(
+            change.summary.increased_pixels + change.summary.decreased_pixels,
+            0
+        );
+        assert!(change
+            .mask
+            .data
+            .iter()
+            .all(|&v| v == UNCHANGED || v == NO_DATA));
+    }
+}
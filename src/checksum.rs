@@ -0,0 +1,103 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::pixel::PixelBytes;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// The 64-bit FNV-1a digest of `pixels` as little-endian bytes, so that the
+/// same pixels give the same digest on every platform.
+///
+/// This catches corruption, not tampering: it's fast, but easy to forge.
+pub fn checksum<T: PixelBytes>(pixels: &[T]) -> u64 {
+    let mut bytes = Vec::with_capacity(T::SIZE);
+    pixels.iter().fold(FNV_OFFSET, |hash, &v| {
+        bytes.clear();
+        v.write_le(&mut bytes);
+        bytes
+            .iter()
+            .fold(hash, |hash, &b| (hash ^ b as u64).wrapping_mul(FNV_PRIME))
+    })
+}
+
+/// The digest of one block of a band.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockChecksum {
+    pub window: Window,
+    pub digest: u64,
+}
+
+impl<'a, T, A: Access> TypedRasterBand<'a, T, A>
+where
+    T: Copy + GdalType + GdalFrom<f64> + PixelBytes,
+{
+    /// The digest of each natural block of the band, in row-major order,
+    /// reading the band once.
+    pub fn block_checksums(&self) -> Result<Vec<BlockChecksum>> {
+        self.block_windows()
+            .into_iter()
+            .map(|window| self.window_checksum(window))
+            .collect()
+    }
+
+    fn window_checksum(&self, window: Window) -> Result<BlockChecksum> {
+        let buffer: TypedBuffer<T> = self.read(window.offset, window.size, window.size)?.into();
+        Ok(BlockChecksum {
+            window,
+            digest: checksum(&buffer.data),
+        })
+    }
+
+    /// Checks the band against digests from `block_checksums`, returning the
+    /// windows whose pixels no longer match. Each window is read once, so
+    /// this works even if the band has since been rewritten with different
+    /// blocks.
+    pub fn verify(&self, expected: &[BlockChecksum]) -> Result<Vec<Window>> {
+        let mut mismatched = Vec::new();
+        for block in expected {
+            if self.window_checksum(block.window)?.digest != block.digest {
+                mismatched.push(block.window);
+            }
+        }
+        Ok(mismatched)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::checksum::{checksum, BlockChecksum};
+    use crate::typed_rasterband::TypedRasterBand;
+    use gdal::raster::dataset::Dataset;
+    use std::path::Path;
+
+    #[test]
+    fn digest_depends_on_values_and_order() {
+        assert_eq!(checksum::<u8>(&[]), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(checksum(&[97u8]), 0xaf63_dc4c_8601_ec8c);
+        assert_ne!(checksum(&[1u16, 2]), checksum(&[2u16, 1]));
+        assert_ne!(checksum(&[0.0f32]), checksum(&[-0.0f32]));
+    }
+
+    #[test]
+    fn verify_band() {
+        let ds = Dataset::open(Path::new("testdata/test_u8.tif")).unwrap();
+        let band = ds.rasterband(1).unwrap();
+        let typed_band = TypedRasterBand::<u8>::from_rasterband(&band).unwrap();
+        let mut checksums = typed_band.block_checksums().unwrap();
+
+        assert_eq!(checksums.len(), typed_band.block_windows().len());
+        assert!(typed_band.verify(&checksums).unwrap().is_empty());
+
+        checksums[0] = BlockChecksum {
+            digest: checksums[0].digest ^ 1,
+            ..checksums[0]
+        };
+        assert_eq!(
+            typed_band.verify(&checksums).unwrap(),
+            vec![checksums[0].window]
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use crate::window::Window;
+use gdal::raster::rasterband::RasterBand;
+use gdal::raster::types::GdalType;
+
+#[derive(Debug, Clone, Default)]
+pub struct ChipOptions {
+    /// Indices of other bands in the owning dataset to read alongside the
+    /// source band. Each must have the same pixel type.
+    pub extra_bands: Vec<isize>,
+    /// Skip chips where the fraction of nodata pixels in the source band
+    /// exceeds this value. Ignored if the band has no nodata value.
+    pub max_nodata_fraction: Option<f64>,
+}
+
+/// A fixed-size patch of one or more bands.
+#[derive(Debug, Clone)]
+pub struct Chip<T> {
+    pub window: Window,
+    /// The geotransform of the chip, if the dataset is georeferenced.
+    pub geo_transform: Option<[f64; 6]>,
+    /// The source band followed by any extra bands.
+    pub bands: Vec<TypedBuffer<T>>,
+}
+
+pub struct Chips<'a, T: Copy + GdalType> {
+    source: &'a RasterBand<'a>,
+    extra_bands: Vec<RasterBand<'a>>,
+    raster_size: (usize, usize),
+    chip_size: (usize, usize),
+    stride: (usize, usize),
+    geo_transform: Option<[f64; 6]>,
+    no_data: Option<T>,
+    max_nodata_fraction: Option<f64>,
+    next_offset: Option<(usize, usize)>,
+}
+
+impl<'a, T: Copy + GdalType + GdalFrom<f64> + PartialEq, A: Access> TypedRasterBand<'a, T, A> {
+    /// Iterates over fixed-size chips of the band, moving `stride` pixels
+    /// between chips. Chips that would extend past the edge of the raster are
+    /// not produced.
+    pub fn chips(
+        &self,
+        chip_size: (usize, usize),
+        stride: (usize, usize),
+        options: ChipOptions,
+    ) -> Result<Chips<'a, T>> {
+        assert!(
+            chip_size.0 > 0 && chip_size.1 > 0,
+            "chip size must be nonzero"
+        );
+        assert!(stride.0 > 0 && stride.1 > 0, "stride must be nonzero");
+
+        let dataset = self.owning_dataset();
+        let mut extra_bands = Vec::with_capacity(options.extra_bands.len());
+        for &index in &options.extra_bands {
+            let band = dataset.rasterband(index)?;
+            TypedRasterBand::<T>::from_rasterband(&band)?;
+            extra_bands.push(band);
+        }
+
+        let raster_size = dataset.size();
+        let next_offset = if chip_size.0 <= raster_size.0 && chip_size.1 <= raster_size.1 {
+            Some((0, 0))
+        } else {
+            None
+        };
+
+        Ok(Chips {
+            source: self.rasterband(),
+            extra_bands,
+            raster_size,
+            chip_size,
+            stride,
+            geo_transform: dataset.geo_transform().ok(),
+            no_data: self.no_data_value(),
+            max_nodata_fraction: options.max_nodata_fraction,
+            next_offset,
+        })
+    }
+}
+
+impl<'a, T: Copy + GdalType + PartialEq> Chips<'a, T> {
+    fn advance(&mut self) {
+        if let Some((x, y)) = self.next_offset {
+            let (x, y) = if x + self.stride.0 + self.chip_size.0 <= self.raster_size.0 {
+                (x + self.stride.0, y)
+            } else {
+                (0, y + self.stride.1)
+            };
+            self.next_offset = if y + self.chip_size.1 <= self.raster_size.1 {
+                Some((x, y))
+            } else {
+                None
+            };
+        }
+    }
+
+    fn read_chip(&self, window: Window) -> Result<Option<Chip<T>>> {
+        let source: TypedBuffer<T> = self
+            .source
+            .read_as::<T>(window.offset, window.size, window.size)?
+            .into();
+
+        if let (Some(max_fraction), Some(no_data)) = (self.max_nodata_fraction, self.no_data) {
+            let count = source.data.iter().filter(|&&v| v == no_data).count();
+            if count as f64 / source.data.len() as f64 > max_fraction {
+                return Ok(None);
+            }
+        }
+
+        let mut bands = Vec::with_capacity(1 + self.extra_bands.len());
+        bands.push(source);
+        for band in &self.extra_bands {
+            bands.push(
+                band.read_as::<T>(window.offset, window.size, window.size)?
+                    .into(),
+            );
+        }
+
+        Ok(Some(Chip {
+            window,
+            geo_transform: self.geo_transform.map(|gt| window.geo_transform(&gt)),
+            bands,
+        }))
+    }
+}
+
+impl<'a, T: Copy + GdalType + PartialEq> Iterator for Chips<'a, T> {
+    type Item = Result<Chip<T>>;
+
+    fn next(&mut self) -> Option<Result<Chip<T>>> {
+        loop {
+            let (x, y) = self.next_offset?;
+            self.advance();
+
+            let window = Window::new((x as isize, y as isize), self.chip_size);
+            match self.read_chip(window) {
+                Ok(Some(chip)) => return Some(Ok(chip)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chips::ChipOptions;
+    use crate::typed_rasterband::TypedRasterBand;
+    use gdal::raster::dataset::Dataset;
+    use std::path::Path;
+
+    #[test]
+    fn chips_cover_raster() {
+        let path = Path::new("testdata/test_u8.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u8>::from_rasterband(&band).unwrap();
+
+        let chips: Vec<_> = typed_band
+            .chips((100, 100), (100, 100), ChipOptions::default())
+            .unwrap()
+            .map(|c| c.unwrap())
+            .collect();
+
+        assert_eq!(chips.len(), 9);
+        assert_eq!(chips[4].window.offset, (100, 100));
+        assert_eq!(chips[4].bands[0].get(0, 0), 119);
+        let gt = chips[1].geo_transform.unwrap();
+        assert!((gt[0] - 453003.003).abs() < 1e-3);
+    }
+
+    #[test]
+    fn chips_missing_extra_band() {
+        let path = Path::new("testdata/test_u16.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u16>::from_rasterband(&band).unwrap();
+
+        let options = ChipOptions {
+            extra_bands: vec![2],
+            ..Default::default()
+        };
+        assert!(typed_band.chips((64, 64), (64, 64), options).is_err());
+    }
+}
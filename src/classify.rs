@@ -0,0 +1,238 @@
+use crate::buffer::TypedBuffer;
+use crate::colormap::Colormap;
+use crate::errors::{check_cpl_err, Result};
+use crate::source::RasterSource;
+use crate::typed_rasterband::{ReadWrite, TypedRasterBand};
+use gdal_sys::{
+    GDALColorEntry, GDALCreateColorTable, GDALCreateRasterAttributeTable, GDALDestroyColorTable,
+    GDALDestroyRasterAttributeTable, GDALPaletteInterp, GDALRATCreateColumn, GDALRATFieldType,
+    GDALRATFieldUsage, GDALRATSetRowCount, GDALRATSetValueAsDouble, GDALRATSetValueAsInt,
+    GDALRATSetValueAsString, GDALRasterBandH, GDALSetColorEntry, GDALSetDefaultRAT,
+    GDALSetRasterColorTable, GDALSetRasterNoDataValue,
+};
+use std::ffi::CString;
+use std::io;
+
+/// The class ID of pixels that are nodata, NaN or outside every class.
+pub const UNCLASSIFIED: u8 = 0;
+
+/// Discrete classes between breakpoints, for turning continuous data such
+/// as elevation into a classified map.
+///
+/// Class `i` (counting from 1) covers `breaks[i - 1]..breaks[i]`, and the
+/// last class also includes its upper break.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Classification {
+    breaks: Vec<f64>,
+    labels: Vec<String>,
+    colors: Option<Vec<[u8; 4]>>,
+}
+
+/// Classes between consecutive `breaks`, which must be sorted, named by
+/// `labels`, which must have one fewer entry. There can be at most 255
+/// classes.
+pub fn classify_breaks<T: Copy + Into<f64>>(
+    breaks: &[T],
+    labels: &[&str],
+) -> Result<Classification> {
+    let invalid = |msg: &str| Err(io::Error::new(io::ErrorKind::InvalidInput, msg).into());
+    if labels.len() + 1 != breaks.len() {
+        return invalid("classes need one more break than labels");
+    }
+    if labels.len() > 255 {
+        return invalid("there can be at most 255 classes");
+    }
+    let breaks: Vec<f64> = breaks.iter().map(|&b| b.into()).collect();
+    if !breaks.windows(2).all(|w| w[0] <= w[1]) {
+        return invalid("breaks must be sorted");
+    }
+    Ok(Classification {
+        breaks,
+        labels: labels.iter().map(|l| l.to_string()).collect(),
+        colors: None,
+    })
+}
+
+impl Classification {
+    pub fn class_count(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// Colors each class by `colormap` at the middle of its range.
+    pub fn with_colormap(mut self, colormap: &Colormap) -> Classification {
+        let colors = self
+            .breaks
+            .windows(2)
+            .map(|w| colormap.color((w[0] + w[1]) / 2.0))
+            .collect();
+        self.colors = Some(colors);
+        self
+    }
+
+    /// The class ID of `value`, or `UNCLASSIFIED`.
+    pub fn class_of(&self, value: f64) -> u8 {
+        let last = self.breaks[self.breaks.len() - 1];
+        if value.is_nan() || value < self.breaks[0] || value > last {
+            return UNCLASSIFIED;
+        }
+        // The number of breaks at or below the value is its class, except
+        // at the last break, which closes the last class.
+        let i = self.breaks.partition_point(|&b| b <= value);
+        i.min(self.class_count()) as u8
+    }
+
+    /// The class IDs of a single-band source, such as a band or a buffer.
+    pub fn apply<T, S>(&self, source: &S) -> Result<TypedBuffer<u8>>
+    where
+        T: Copy + Into<f64> + PartialEq,
+        S: RasterSource<T>,
+    {
+        let nodata = source.no_data_value();
+        let buffer = source.read_full()?;
+        Ok(buffer.map(|v| {
+            if Some(v) == nodata {
+                UNCLASSIFIED
+            } else {
+                self.class_of(v.into())
+            }
+        }))
+    }
+
+    /// Classifies `source` into `band`, which must be the same size, and
+    /// gives the band `UNCLASSIFIED` as its nodata value, a raster attribute
+    /// table of each class's ID, range and label, and a color table if the
+    /// classes have colors.
+    pub fn write<T, S>(&self, source: &S, band: &TypedRasterBand<u8, ReadWrite>) -> Result<()>
+    where
+        T: Copy + Into<f64> + PartialEq,
+        S: RasterSource<T>,
+    {
+        let classes = self.apply(source)?;
+        band.write((0, 0), classes.size, &classes.into_buffer())?;
+        let c_band = unsafe { band.rasterband()._c_ptr() };
+        check_cpl_err(unsafe { GDALSetRasterNoDataValue(c_band, UNCLASSIFIED as f64) })?;
+        if let Some(colors) = &self.colors {
+            self.write_color_table(c_band, colors)?;
+        }
+        self.write_attribute_table(c_band)?;
+        band.record_history("classify", &[("classes", self.class_count().to_string())])
+    }
+
+    fn write_color_table(&self, c_band: GDALRasterBandH, colors: &[[u8; 4]]) -> Result<()> {
+        unsafe {
+            let table = GDALCreateColorTable(GDALPaletteInterp::GPI_RGB);
+            let entries = Some([0, 0, 0, 0]).into_iter().chain(colors.iter().cloned());
+            for (i, [r, g, b, a]) in entries.enumerate() {
+                let entry = GDALColorEntry {
+                    c1: r as i16,
+                    c2: g as i16,
+                    c3: b as i16,
+                    c4: a as i16,
+                };
+                GDALSetColorEntry(table, i as i32, &entry);
+            }
+            let rv = GDALSetRasterColorTable(c_band, table);
+            GDALDestroyColorTable(table);
+            check_cpl_err(rv)
+        }
+    }
+
+    fn write_attribute_table(&self, c_band: GDALRasterBandH) -> Result<()> {
+        let labels = self
+            .labels
+            .iter()
+            .map(|l| CString::new(l.as_str()))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(io::Error::from)?;
+        let columns = [
+            (
+                "Value",
+                GDALRATFieldType::GFT_Integer,
+                GDALRATFieldUsage::GFU_MinMax,
+            ),
+            (
+                "Min",
+                GDALRATFieldType::GFT_Real,
+                GDALRATFieldUsage::GFU_Min,
+            ),
+            (
+                "Max",
+                GDALRATFieldType::GFT_Real,
+                GDALRATFieldUsage::GFU_Max,
+            ),
+            (
+                "Class",
+                GDALRATFieldType::GFT_String,
+                GDALRATFieldUsage::GFU_Name,
+            ),
+        ];
+        unsafe {
+            let table = GDALCreateRasterAttributeTable();
+            for (name, field_type, usage) in columns.iter() {
+                let c_name = CString::new(*name).unwrap();
+                GDALRATCreateColumn(table, c_name.as_ptr(), *field_type, *usage);
+            }
+            GDALRATSetRowCount(table, self.class_count() as i32);
+            for (row, label) in labels.iter().enumerate() {
+                let r = row as i32;
+                GDALRATSetValueAsInt(table, r, 0, r + 1);
+                GDALRATSetValueAsDouble(table, r, 1, self.breaks[row]);
+                GDALRATSetValueAsDouble(table, r, 2, self.breaks[row + 1]);
+                GDALRATSetValueAsString(table, r, 3, label.as_ptr());
+            }
+            let rv = GDALSetDefaultRAT(c_band, table);
+            GDALDestroyRasterAttributeTable(table);
+            check_cpl_err(rv)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::classify::{classify_breaks, UNCLASSIFIED};
+    use crate::colormap::Colormap;
+
+    #[test]
+    fn classify_values() {
+        let classes = classify_breaks(&[0.0, 10.0, 100.0], &["low", "high"]).unwrap();
+
+        assert_eq!(classes.class_of(-1.0), UNCLASSIFIED);
+        assert_eq!(classes.class_of(0.0), 1);
+        assert_eq!(classes.class_of(9.9), 1);
+        assert_eq!(classes.class_of(10.0), 2);
+        assert_eq!(classes.class_of(100.0), 2);
+        assert_eq!(classes.class_of(100.5), UNCLASSIFIED);
+        assert_eq!(classes.class_of(f64::NAN), UNCLASSIFIED);
+
+        let buffer = TypedBuffer::new((3, 1), vec![5u16, 50, 500]);
+        assert_eq!(classes.apply(&buffer).unwrap().data, vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn colors_from_colormap() {
+        let colormap = Colormap::new(vec![(0.0, [0, 0, 0, 255]), (100.0, [200, 0, 0, 255])]);
+        let classes = classify_breaks(&[0u8, 50, 100], &["a", "b"])
+            .unwrap()
+            .with_colormap(&colormap);
+
+        assert_eq!(
+            classes.colors,
+            Some(vec![[50, 0, 0, 255], [150, 0, 0, 255]])
+        );
+    }
+
+    #[test]
+    fn reject_bad_breaks() {
+        assert!(classify_breaks(&[0.0, 10.0], &["a", "b"]).is_err());
+        assert!(classify_breaks(&[10.0, 0.0], &["a"]).is_err());
+        assert!(classify_breaks(&[0.0, f64::NAN], &["a"]).is_err());
+        let labels = vec!["class"; 256];
+        let breaks: Vec<f64> = (0..257).map(f64::from).collect();
+        assert!(classify_breaks(&breaks, &labels).is_err());
+    }
+}
@@ -0,0 +1,227 @@
+use crate::config::REDACTED;
+use crate::dataset::TypedDataset;
+use crate::errors::Result;
+use crate::typed_rasterband::GdalFrom;
+use gdal::raster::types::GdalType;
+use std::fmt;
+use std::path::Path;
+
+/// Pushes `(key, value)` onto `config` if `value` is set.
+fn push_option(config: &mut Vec<(String, String)>, key: &str, value: &Option<String>) {
+    if let Some(value) = value {
+        config.push((key.to_string(), value.clone()));
+    }
+}
+
+/// A credential as `Debug` shows it: whether it is set, but not its value.
+fn redact(value: &Option<String>) -> Option<&str> {
+    value.as_ref().map(|_| REDACTED)
+}
+
+fn push_flag(config: &mut Vec<(String, String)>, key: &str, set: bool) {
+    if set {
+        config.push((key.to_string(), "YES".to_string()));
+    }
+}
+
+/// Credentials and settings for reading from Amazon S3 or an S3-compatible
+/// store. Unset fields fall back to GDAL's usual environment lookup.
+///
+/// `Debug` shows which credentials are set but not their values, as with
+/// `GcsOptions` and `AzureOptions`.
+#[derive(Clone, Default)]
+pub struct S3Options {
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<String>,
+    pub session_token: Option<String>,
+    pub profile: Option<String>,
+    pub region: Option<String>,
+    /// A custom endpoint, such as `"minio.example.com:9000"`.
+    pub endpoint: Option<String>,
+    /// Read public buckets without signing requests.
+    pub no_sign_request: bool,
+}
+
+impl fmt::Debug for S3Options {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("S3Options")
+            .field("access_key_id", &redact(&self.access_key_id))
+            .field("secret_access_key", &redact(&self.secret_access_key))
+            .field("session_token", &redact(&self.session_token))
+            .field("profile", &self.profile)
+            .field("region", &self.region)
+            .field("endpoint", &self.endpoint)
+            .field("no_sign_request", &self.no_sign_request)
+            .finish()
+    }
+}
+
+impl S3Options {
+    /// The GDAL config options these settings correspond to.
+    pub fn config_options(&self) -> Vec<(String, String)> {
+        let mut config = Vec::new();
+        push_option(&mut config, "AWS_ACCESS_KEY_ID", &self.access_key_id);
+        push_option(
+            &mut config,
+            "AWS_SECRET_ACCESS_KEY",
+            &self.secret_access_key,
+        );
+        push_option(&mut config, "AWS_SESSION_TOKEN", &self.session_token);
+        push_option(&mut config, "AWS_PROFILE", &self.profile);
+        push_option(&mut config, "AWS_REGION", &self.region);
+        push_option(&mut config, "AWS_S3_ENDPOINT", &self.endpoint);
+        push_flag(&mut config, "AWS_NO_SIGN_REQUEST", self.no_sign_request);
+        config
+    }
+}
+
+/// Credentials for reading from Google Cloud Storage.
+#[derive(Clone, Default)]
+pub struct GcsOptions {
+    /// Path to a service account JSON key file.
+    pub application_credentials: Option<String>,
+    /// HMAC access key and secret.
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<String>,
+    pub no_sign_request: bool,
+}
+
+impl fmt::Debug for GcsOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GcsOptions")
+            .field(
+                "application_credentials",
+                &redact(&self.application_credentials),
+            )
+            .field("access_key_id", &redact(&self.access_key_id))
+            .field("secret_access_key", &redact(&self.secret_access_key))
+            .field("no_sign_request", &self.no_sign_request)
+            .finish()
+    }
+}
+
+impl GcsOptions {
+    pub fn config_options(&self) -> Vec<(String, String)> {
+        let mut config = Vec::new();
+        push_option(
+            &mut config,
+            "GOOGLE_APPLICATION_CREDENTIALS",
+            &self.application_credentials,
+        );
+        push_option(&mut config, "GS_ACCESS_KEY_ID", &self.access_key_id);
+        push_option(&mut config, "GS_SECRET_ACCESS_KEY", &self.secret_access_key);
+        push_flag(&mut config, "GS_NO_SIGN_REQUEST", self.no_sign_request);
+        config
+    }
+}
+
+/// Credentials for reading from Azure Blob Storage.
+#[derive(Clone, Default)]
+pub struct AzureOptions {
+    pub storage_account: Option<String>,
+    pub access_key: Option<String>,
+    pub sas_token: Option<String>,
+    pub connection_string: Option<String>,
+    pub no_sign_request: bool,
+}
+
+impl fmt::Debug for AzureOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AzureOptions")
+            .field("storage_account", &self.storage_account)
+            .field("access_key", &redact(&self.access_key))
+            .field("sas_token", &redact(&self.sas_token))
+            .field("connection_string", &redact(&self.connection_string))
+            .field("no_sign_request", &self.no_sign_request)
+            .finish()
+    }
+}
+
+impl AzureOptions {
+    pub fn config_options(&self) -> Vec<(String, String)> {
+        let mut config = Vec::new();
+        push_option(&mut config, "AZURE_STORAGE_ACCOUNT", &self.storage_account);
+        push_option(&mut config, "AZURE_STORAGE_ACCESS_KEY", &self.access_key);
+        push_option(&mut config, "AZURE_STORAGE_SAS_TOKEN", &self.sas_token);
+        push_option(
+            &mut config,
+            "AZURE_STORAGE_CONNECTION_STRING",
+            &self.connection_string,
+        );
+        push_flag(&mut config, "AZURE_NO_SIGN_REQUEST", self.no_sign_request);
+        config
+    }
+}
+
+fn object_path(prefix: &str, bucket: &str, key: &str) -> String {
+    format!("/{}/{}/{}", prefix, bucket, key.trim_start_matches('/'))
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64>> TypedDataset<T> {
+    /// Opens `key` in an S3 bucket through `/vsis3/`. The credentials are
+    /// attached to the dataset rather than set globally.
+    pub fn open_s3(bucket: &str, key: &str, options: &S3Options) -> Result<TypedDataset<T>> {
+        let path = object_path("vsis3", bucket, key);
+        TypedDataset::open_with_config(Path::new(&path), &[], options.config_options())
+    }
+
+    /// Opens `key` in a Google Cloud Storage bucket through `/vsigs/`.
+    pub fn open_gcs(bucket: &str, key: &str, options: &GcsOptions) -> Result<TypedDataset<T>> {
+        let path = object_path("vsigs", bucket, key);
+        TypedDataset::open_with_config(Path::new(&path), &[], options.config_options())
+    }
+
+    /// Opens `blob` in an Azure container through `/vsiaz/`.
+    pub fn open_azure(
+        container: &str,
+        blob: &str,
+        options: &AzureOptions,
+    ) -> Result<TypedDataset<T>> {
+        let path = object_path("vsiaz", container, blob);
+        TypedDataset::open_with_config(Path::new(&path), &[], options.config_options())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cloud::{object_path, AzureOptions, S3Options};
+
+    #[test]
+    fn s3_config_options() {
+        let options = S3Options {
+            region: Some("us-west-2".to_string()),
+            no_sign_request: true,
+            ..S3Options::default()
+        };
+
+        assert_eq!(
+            options.config_options(),
+            vec![
+                ("AWS_REGION".to_string(), "us-west-2".to_string()),
+                ("AWS_NO_SIGN_REQUEST".to_string(), "YES".to_string()),
+            ]
+        );
+        assert_eq!(
+            object_path("vsis3", "bucket", "/cogs/a.tif"),
+            "/vsis3/bucket/cogs/a.tif"
+        );
+    }
+
+    #[test]
+    fn debug_hides_credentials() {
+        let s3 = S3Options {
+            secret_access_key: Some("hunter2".to_string()),
+            region: Some("us-west-2".to_string()),
+            ..S3Options::default()
+        };
+        let azure = AzureOptions {
+            sas_token: Some("sv=2020&sig=abc".to_string()),
+            ..AzureOptions::default()
+        };
+
+        let debug = format!("{:?} {:?}", s3, azure);
+        assert!(debug.contains("us-west-2"));
+        assert!(!debug.contains("hunter2"));
+        assert!(!debug.contains("sig=abc"));
+    }
+}
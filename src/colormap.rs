@@ -0,0 +1,149 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::source::RasterSource;
+
+/// An RGBA image, one `[r, g, b, a]` per pixel.
+pub type RgbaBuffer = TypedBuffer<[u8; 4]>;
+
+/// Viridis, sampled at nine evenly spaced points.
+const VIRIDIS: [[u8; 3]; 9] = [
+    [68, 1, 84],
+    [71, 45, 123],
+    [59, 82, 139],
+    [44, 114, 142],
+    [33, 145, 140],
+    [40, 174, 128],
+    [94, 201, 98],
+    [173, 220, 48],
+    [253, 231, 37],
+];
+
+/// A ramp from sea blue through green lowlands and brown hills to snow.
+const TERRAIN: [(f64, [u8; 3]); 6] = [
+    (0.0, [51, 51, 153]),
+    (0.15, [0, 153, 255]),
+    (0.25, [0, 204, 102]),
+    (0.5, [255, 255, 153]),
+    (0.75, [128, 92, 84]),
+    (1.0, [255, 255, 255]),
+];
+
+/// Maps values to colors by interpolating between breakpoints.
+///
+/// Values below the first breakpoint or above the last take the color of
+/// that breakpoint; nodata and NaN pixels take `nodata_color`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Colormap {
+    stops: Vec<(f64, [u8; 4])>,
+    pub nodata_color: [u8; 4],
+}
+
+impl Colormap {
+    /// A colormap through `stops`, which must be sorted by value.
+    pub fn new(stops: Vec<(f64, [u8; 4])>) -> Colormap {
+        assert!(!stops.is_empty(), "a colormap needs at least one stop");
+        assert!(
+            stops.windows(2).all(|w| w[0].0 <= w[1].0),
+            "colormap stops must be sorted by value"
+        );
+        Colormap {
+            stops,
+            nodata_color: [0, 0, 0, 0],
+        }
+    }
+
+    /// Spreads opaque colors, positioned in [0, 1], over `min..max`.
+    fn scaled(min: f64, max: f64, colors: impl Iterator<Item = (f64, [u8; 3])>) -> Colormap {
+        Colormap::new(
+            colors
+                .map(|(t, [r, g, b])| (min + t * (max - min), [r, g, b, 255]))
+                .collect(),
+        )
+    }
+
+    pub fn viridis(min: f64, max: f64) -> Colormap {
+        let last = (VIRIDIS.len() - 1) as f64;
+        Colormap::scaled(
+            min,
+            max,
+            VIRIDIS
+                .iter()
+                .enumerate()
+                .map(|(i, &c)| (i as f64 / last, c)),
+        )
+    }
+
+    /// The terrain ramp spread over `min..max`.
+    pub fn terrain(min: f64, max: f64) -> Colormap {
+        Colormap::scaled(min, max, TERRAIN.iter().cloned())
+    }
+
+    pub fn with_nodata_color(mut self, color: [u8; 4]) -> Colormap {
+        self.nodata_color = color;
+        self
+    }
+
+    /// The color of `value`.
+    pub fn color(&self, value: f64) -> [u8; 4] {
+        if value.is_nan() {
+            return self.nodata_color;
+        }
+        let i = self.stops.partition_point(|&(v, _)| v <= value);
+        if i == 0 {
+            return self.stops[0].1;
+        }
+        if i == self.stops.len() {
+            return self.stops[i - 1].1;
+        }
+        let ((v0, c0), (v1, c1)) = (self.stops[i - 1], self.stops[i]);
+        let t = (value - v0) / (v1 - v0);
+        let mut color = [0; 4];
+        for (c, (&a, &b)) in color.iter_mut().zip(c0.iter().zip(&c1)) {
+            *c = (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        }
+        color
+    }
+}
+
+/// Renders a single-band source, such as a band or a buffer, through
+/// `colormap`.
+pub fn apply_colormap<T, S>(source: &S, colormap: &Colormap) -> Result<RgbaBuffer>
+where
+    T: Copy + Into<f64> + PartialEq,
+    S: RasterSource<T>,
+{
+    let nodata = source.no_data_value();
+    let buffer = source.read_full()?;
+    Ok(buffer.map(|v| {
+        if Some(v) == nodata {
+            colormap.nodata_color
+        } else {
+            colormap.color(v.into())
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::colormap::{apply_colormap, Colormap};
+
+    #[test]
+    fn interpolate_stops() {
+        let colormap = Colormap::new(vec![(0.0, [0, 0, 0, 255]), (10.0, [200, 100, 0, 255])]);
+
+        assert_eq!(colormap.color(-5.0), [0, 0, 0, 255]);
+        assert_eq!(colormap.color(2.5), [50, 25, 0, 255]);
+        assert_eq!(colormap.color(99.0), [200, 100, 0, 255]);
+        assert_eq!(colormap.color(f64::NAN), [0, 0, 0, 0]);
+        assert_eq!(Colormap::viridis(0.0, 1.0).color(0.5), [33, 145, 140, 255]);
+    }
+
+    #[test]
+    fn colorize_buffer() {
+        let buffer = TypedBuffer::new((2, 1), vec![0.0f32, 1.0]);
+        let rgba = apply_colormap(&buffer, &Colormap::terrain(0.0, 1.0)).unwrap();
+
+        assert_eq!(rgba.data, vec![[51, 51, 153, 255], [255, 255, 255, 255]]);
+    }
+}
@@ -0,0 +1,164 @@
+use crate::bitmask::PixelMask;
+use crate::buffer::TypedBuffer;
+use crate::window::Window;
+
+/// Which neighbours of a pixel count as touching it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Pixels that share an edge.
+    Four,
+    /// Pixels that share an edge or a corner.
+    Eight,
+}
+
+impl Connectivity {
+    pub(crate) fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Connectivity::Four => &[(1, 0), (-1, 0), (0, 1), (0, -1)],
+            Connectivity::Eight => &[
+                (1, 0),
+                (-1, 0),
+                (0, 1),
+                (0, -1),
+                (1, 1),
+                (1, -1),
+                (-1, 1),
+                (-1, -1),
+            ],
+        }
+    }
+}
+
+/// One connected region found by `label_connected`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Component {
+    pub label: u32,
+    pub pixel_count: usize,
+    /// The smallest window holding every pixel of the component.
+    pub bounds: Window,
+}
+
+/// The output of `label_connected`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Labeling {
+    /// The label of each pixel's component, or 0 for background.
+    pub labels: TypedBuffer<u32>,
+    /// The components in the order they were labeled, so that the component
+    /// labeled `n` is at index `n - 1`. Labels are assigned in row-major
+    /// order of each component's first pixel.
+    pub components: Vec<Component>,
+}
+
+/// Labels the components of pixels for which `include` holds, where
+/// neighbouring pixels join a component if `same` holds of their indices.
+fn label<I, S>(size: (usize, usize), connectivity: Connectivity, include: I, same: S) -> Labeling
+where
+    I: Fn(usize) -> bool,
+    S: Fn(usize, usize) -> bool,
+{
+    let (width, height) = size;
+    let mut labels = vec![0u32; width * height];
+    let mut components = Vec::new();
+    let mut stack = Vec::new();
+
+    for start in 0..width * height {
+        if labels[start] != 0 || !include(start) {
+            continue;
+        }
+        let label = components.len() as u32 + 1;
+        let (mut x0, mut y0, mut x1, mut y1) = (width, height, 0, 0);
+        let mut pixel_count = 0;
+        labels[start] = label;
+        stack.push(start);
+        while let Some(i) = stack.pop() {
+            let (x, y) = (i % width, i / width);
+            pixel_count += 1;
+            x0 = x0.min(x);
+            y0 = y0.min(y);
+            x1 = x1.max(x);
+            y1 = y1.max(y);
+            for &(dx, dy) in connectivity.offsets() {
+                let (nx, ny) = (x as isize + dx, y as isize + dy);
+                if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                    continue;
+                }
+                let j = ny as usize * width + nx as usize;
+                if labels[j] == 0 && include(j) && same(i, j) {
+                    labels[j] = label;
+                    stack.push(j);
+                }
+            }
+        }
+        components.push(Component {
+            label,
+            pixel_count,
+            bounds: Window::new((x0 as isize, y0 as isize), (x1 - x0 + 1, y1 - y0 + 1)),
+        });
+    }
+    Labeling {
+        labels: TypedBuffer::new(size, labels),
+        components,
+    }
+}
+
+/// Labels the connected regions of set pixels in `mask`.
+pub fn label_connected<M: PixelMask>(mask: &M, connectivity: Connectivity) -> Labeling {
+    let width = mask.size().0;
+    label(
+        mask.size(),
+        connectivity,
+        |i| mask.is_set(i % width, i / width),
+        |_, _| true,
+    )
+}
+
+impl<T: Copy + PartialEq> TypedBuffer<T> {
+    /// Labels the connected regions of equal value, as when finding the
+    /// individual fields of a classified image. Pixels equal to `background`
+    /// are left unlabeled.
+    pub fn label_connected(&self, connectivity: Connectivity, background: Option<T>) -> Labeling {
+        label(
+            self.size,
+            connectivity,
+            |i| Some(self.data[i]) != background,
+            |i, j| self.data[i] == self.data[j],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::components::{label_connected, Connectivity};
+    use crate::window::Window;
+
+    #[test]
+    fn label_mask() {
+        let mask = TypedBuffer::new(
+            (4, 3),
+            vec![
+                true, false, false, true, //
+                false, true, false, true, //
+                false, false, false, true,
+            ],
+        );
+
+        let four = label_connected(&mask, Connectivity::Four);
+        assert_eq!(four.components.len(), 3);
+        let eight = label_connected(&mask, Connectivity::Eight);
+        assert_eq!(eight.components.len(), 2);
+        assert_eq!(eight.components[0].pixel_count, 2);
+        assert_eq!(eight.components[1].bounds, Window::new((3, 0), (1, 3)));
+        assert_eq!(eight.labels.get(1, 1), 1);
+        assert_eq!(eight.labels.get(2, 1), 0);
+    }
+
+    #[test]
+    fn label_classes() {
+        let classes = TypedBuffer::new((3, 2), vec![1u8, 1, 2, 0, 2, 2]);
+        let labeling = classes.label_connected(Connectivity::Four, Some(0));
+
+        assert_eq!(labeling.labels.data, vec![1, 1, 2, 0, 2, 2]);
+        assert_eq!(labeling.components[1].pixel_count, 3);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::blocks::block_windows;
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::timeseries::RasterTimeSeries;
+use crate::typed_rasterband::GdalFrom;
+use gdal::raster::types::GdalType;
+
+/// How `composite` combines the valid observations of a pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reducer {
+    Mean,
+    Median,
+    Min,
+    Max,
+}
+
+impl Reducer {
+    /// Reduces `values`, which must not be empty. The slice may be reordered.
+    fn reduce(self, values: &mut [f64]) -> f64 {
+        match self {
+            Reducer::Mean => values.iter().sum::<f64>() / values.len() as f64,
+            Reducer::Median => {
+                values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                let mid = values.len() / 2;
+                if values.len() % 2 == 1 {
+                    values[mid]
+                } else {
+                    (values[mid - 1] + values[mid]) / 2.0
+                }
+            }
+            Reducer::Min => values.iter().cloned().fold(f64::INFINITY, f64::min),
+            Reducer::Max => values.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+impl<T> RasterTimeSeries<T>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Into<f64>,
+{
+    /// Combines the stack into one band by reducing each pixel's valid
+    /// observations, e.g. a median composite to suppress clouds.
+    ///
+    /// Observations equal to their band's nodata value, and NaNs, are
+    /// skipped. Pixels with no valid observations are set to the first
+    /// nodata value found among the bands, or zero. The stack is read one
+    /// block at a time.
+    pub fn composite(&self, reducer: Reducer) -> Result<TypedBuffer<T>> {
+        let mut nodata = Vec::with_capacity(self.len());
+        for band in self.bands() {
+            nodata.push(band.no_data_value()?);
+        }
+        let fill = nodata
+            .iter()
+            .find_map(|&v| v)
+            .unwrap_or_else(|| T::from(0.0));
+
+        let mut output = TypedBuffer::filled(self.size(), fill);
+        let block_size = match self.bands().next() {
+            Some(band) => band.with_band(|b| b.block_size())?,
+            None => return Ok(output),
+        };
+
+        let mut values = Vec::with_capacity(self.len());
+        for window in block_windows(self.size(), block_size) {
+            let frames = self.read_frames(window)?;
+            for i in 0..window.pixel_count() {
+                values.clear();
+                for (frame, nodata) in frames.iter().zip(&nodata) {
+                    let value: f64 = frame.data[i].into();
+                    if nodata.map(Into::into) != Some(value) && !value.is_nan() {
+                        values.push(value);
+                    }
+                }
+                if !values.is_empty() {
+                    let x = window.offset.0 as usize + i % window.size.0;
+                    let y = window.offset.1 as usize + i / window.size.0;
+                    output.set(x, y, T::from(reducer.reduce(&mut values)));
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::composite::Reducer;
+    use crate::timeseries::RasterTimeSeries;
+    use std::path::PathBuf;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn reducers() {
+        let values = [4.0, 1.0, 3.0, 2.0];
+        assert_eq!(Reducer::Median.reduce(&mut values.clone()), 2.5);
+        assert_eq!(Reducer::Mean.reduce(&mut values.clone()), 2.5);
+        assert_eq!(Reducer::Max.reduce(&mut values.clone()), 4.0);
+    }
+
+    #[test]
+    fn composite_time_series() {
+        let series = RasterTimeSeries::<u16>::new(vec![
+            (UNIX_EPOCH, PathBuf::from("testdata/test_u16.tif"), 1),
+            (
+                UNIX_EPOCH + Duration::from_secs(1),
+                PathBuf::from("testdata/test_u16_nodata.tif"),
+                1,
+            ),
+        ])
+        .unwrap();
+        let composite = series.composite(Reducer::Max).unwrap();
+
+        assert_eq!(composite.size, (333, 333));
+        assert_eq!(composite.get(100, 100), 6656);
+    }
+}
@@ -0,0 +1,377 @@
+use crate::dataset::TypedDataset;
+use crate::errors::{Error, Result};
+use crate::typed_rasterband::GdalFrom;
+use gdal::raster::dataset::Dataset;
+use gdal::raster::types::GdalType;
+use gdal_sys::{
+    CPLErr, CPLGetConfigOption, CPLGetThreadLocalConfigOption, CPLSetThreadLocalConfigOption,
+    GDALGetCacheMax64, GDALOpenEx, GDALSetCacheMax64, GDAL_OF_RASTER, GDAL_OF_SHARED,
+    GDAL_OF_UPDATE, GDAL_OF_VERBOSE_ERROR,
+};
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::io;
+use std::os::raw::c_char;
+use std::path::Path;
+use std::ptr;
+
+/// Sets the size of GDAL's block cache, in bytes. This is the same setting as
+/// the `GDAL_CACHEMAX` config option, and is shared by every dataset.
+pub fn set_cache_max(bytes: i64) {
+    unsafe { GDALSetCacheMax64(bytes) }
+}
+
+/// The size of GDAL's block cache, in bytes.
+pub fn cache_max() -> i64 {
+    unsafe { GDALGetCacheMax64() }
+}
+
+/// Returns the value of config option `key` as seen by the current thread.
+pub fn config_option(key: &str) -> Option<String> {
+    let key = CString::new(key).ok()?;
+    unsafe {
+        let value = CPLGetConfigOption(key.as_ptr(), ptr::null());
+        if value.is_null() {
+            None
+        } else {
+            Some(CStr::from_ptr(value).to_string_lossy().into_owned())
+        }
+    }
+}
+
+/// Sets GDAL config options for the current thread, restoring their previous
+/// values when dropped.
+pub struct ConfigGuard {
+    previous: Vec<(CString, Option<CString>)>,
+}
+
+impl ConfigGuard {
+    pub fn set<K: AsRef<str>, V: AsRef<str>>(options: &[(K, V)]) -> Result<ConfigGuard> {
+        let mut guard = ConfigGuard {
+            previous: Vec::with_capacity(options.len()),
+        };
+        for (key, value) in options {
+            let key = CString::new(key.as_ref()).map_err(io::Error::from)?;
+            let value = CString::new(value.as_ref()).map_err(io::Error::from)?;
+            unsafe {
+                let old = CPLGetThreadLocalConfigOption(key.as_ptr(), ptr::null());
+                let old = if old.is_null() {
+                    None
+                } else {
+                    Some(CStr::from_ptr(old).to_owned())
+                };
+                CPLSetThreadLocalConfigOption(key.as_ptr(), value.as_ptr());
+                guard.previous.push((key, old));
+            }
+        }
+        Ok(guard)
+    }
+}
+
+impl Drop for ConfigGuard {
+    fn drop(&mut self) {
+        // Restore in reverse so that a key set twice ends up with its
+        // original value.
+        for (key, old) in self.previous.iter().rev() {
+            let old = old.as_ref().map_or(ptr::null(), |v| v.as_ptr());
+            unsafe { CPLSetThreadLocalConfigOption(key.as_ptr(), old) };
+        }
+    }
+}
+
+/// A null-terminated list of `KEY=VALUE` strings, as taken by GDAL.
+pub(crate) struct NameValueList {
+    strings: Vec<CString>,
+    pointers: Vec<*const c_char>,
+}
+
+impl NameValueList {
+    pub(crate) fn new(options: &[(&str, &str)]) -> Result<NameValueList> {
+        let strings: Vec<String> = options
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        NameValueList::from_strings(strings)
+    }
+
+    /// A list of arbitrary strings, such as command-line arguments.
+    pub(crate) fn from_strings(strings: Vec<String>) -> Result<NameValueList> {
+        let strings = strings
+            .into_iter()
+            .map(CString::new)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(io::Error::from)?;
+        let mut pointers: Vec<_> = strings.iter().map(|s| s.as_ptr()).collect();
+        pointers.push(ptr::null());
+        Ok(NameValueList { strings, pointers })
+    }
+
+    pub(crate) fn as_ptr(&self) -> *const *const c_char {
+        if self.strings.is_empty() {
+            ptr::null()
+        } else {
+            self.pointers.as_ptr()
+        }
+    }
+}
+
+/// Whether the config option `key` holds a credential, such as
+/// `AWS_SECRET_ACCESS_KEY` or `AZURE_STORAGE_SAS_TOKEN`, whose value must not
+/// be printed.
+pub(crate) fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_uppercase();
+    [
+        "SECRET",
+        "TOKEN",
+        "KEY",
+        "PASSWORD",
+        "CREDENTIALS",
+        "CONNECTION_STRING",
+    ]
+    .iter()
+    .any(|word| key.contains(word))
+}
+
+/// Formats config options with the values of credentials replaced.
+pub(crate) struct RedactedConfig<'a>(pub(crate) &'a [(String, String)]);
+
+impl fmt::Debug for RedactedConfig<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|(key, value)| {
+                let value = if is_secret_key(key) { REDACTED } else { value };
+                (key, value)
+            }))
+            .finish()
+    }
+}
+
+/// What `Debug` prints in place of a credential.
+pub(crate) const REDACTED: &str = "<redacted>";
+
+/// How to open a dataset: the access mode, which drivers to try, and the
+/// driver open options and config options to use for this file only.
+///
+/// `Debug` hides the values of config options that hold credentials.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct DatasetOpenOptions {
+    /// Open for update instead of read-only.
+    pub update: bool,
+    /// Reuse a dataset already opened with `shared` on this thread for the
+    /// same file and access mode, instead of opening it again.
+    pub shared: bool,
+    /// Only try the drivers with these short names, such as `"GTiff"`. All
+    /// drivers are tried if this is empty.
+    pub allowed_drivers: Vec<String>,
+    /// Driver-specific open options, such as `("NUM_THREADS", "ALL_CPUS")`,
+    /// `("OVERVIEW_LEVEL", "0")` or `("GEOREF_SOURCES", "INTERNAL")`.
+    pub open_options: Vec<(String, String)>,
+    /// Config options attached to the dataset. See `open_with_config`.
+    pub config: Vec<(String, String)>,
+}
+
+impl fmt::Debug for DatasetOpenOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DatasetOpenOptions")
+            .field("update", &self.update)
+            .field("shared", &self.shared)
+            .field("allowed_drivers", &self.allowed_drivers)
+            .field("open_options", &self.open_options)
+            .field("config", &RedactedConfig(&self.config))
+            .finish()
+    }
+}
+
+impl DatasetOpenOptions {
+    fn flags(&self) -> u32 {
+        let mut flags = GDAL_OF_RASTER | GDAL_OF_VERBOSE_ERROR;
+        if self.update {
+            flags |= GDAL_OF_UPDATE;
+        }
+        if self.shared {
+            flags |= GDAL_OF_SHARED;
+        }
+        flags
+    }
+}
+
+/// Opens an untyped dataset as described by `options`, for the typed
+/// wrappers that hold their own handles.
+pub(crate) fn open_dataset(path: &Path, options: &DatasetOpenOptions) -> Result<Dataset> {
+    let filename = CString::new(path.to_string_lossy().as_ref()).map_err(io::Error::from)?;
+    let open_options: Vec<(&str, &str)> = options
+        .open_options
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect();
+    let open_options = NameValueList::new(&open_options)?;
+    let drivers = NameValueList::from_strings(options.allowed_drivers.clone())?;
+    let c_dataset = {
+        let _guard = ConfigGuard::set(&options.config)?;
+        unsafe {
+            GDALOpenEx(
+                filename.as_ptr(),
+                options.flags(),
+                drivers.as_ptr(),
+                open_options.as_ptr(),
+                ptr::null(),
+            )
+        }
+    };
+    if c_dataset.is_null() {
+        return Err(Error::last_cpl_error(CPLErr::CE_Failure));
+    }
+    Ok(unsafe { Dataset::_with_c_ptr(c_dataset) })
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64>> TypedDataset<T> {
+    /// Opens a dataset, passing driver-specific open options such as
+    /// `("NUM_THREADS", "ALL_CPUS")` or `("OVERVIEW_LEVEL", "0")`.
+    pub fn open_with_options(
+        path: &Path,
+        open_options: &[(&str, &str)],
+    ) -> Result<TypedDataset<T>> {
+        TypedDataset::open_with_config(path, open_options, Vec::new())
+    }
+
+    /// Opens a dataset with config options that stay attached to it.
+    ///
+    /// The config options are set on the calling thread while opening and
+    /// again around every read and write through the `TypedDataset`, without
+    /// changing them for the rest of the program.
+    pub fn open_with_config(
+        path: &Path,
+        open_options: &[(&str, &str)],
+        config: Vec<(String, String)>,
+    ) -> Result<TypedDataset<T>> {
+        let options = DatasetOpenOptions {
+            open_options: open_options
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            config,
+            ..Default::default()
+        };
+        TypedDataset::open_with(path, &options)
+    }
+
+    /// Opens a dataset as described by `options`. Every constructor that
+    /// opens a path or URI comes through here.
+    pub fn open_with(path: &Path, options: &DatasetOpenOptions) -> Result<TypedDataset<T>> {
+        let mut dataset = TypedDataset::from_dataset(open_dataset(path, options)?)?;
+        dataset.config = options.config.clone();
+        dataset.open_options = DatasetOpenOptions {
+            config: Vec::new(),
+            ..options.clone()
+        };
+        Ok(dataset)
+    }
+
+    /// The options for opening the dataset again, such as from another
+    /// thread: read-only, with its drivers, open options and config options.
+    /// Pass these to `SharedTypedBand::from_band` or `par_blocks_with` so
+    /// that the new handles read the same pixels.
+    pub fn reopen_options(&self) -> DatasetOpenOptions {
+        DatasetOpenOptions {
+            update: false,
+            config: self.config.clone(),
+            ..self.open_options.clone()
+        }
+    }
+
+    /// The config options attached to the dataset.
+    pub fn config_options(&self) -> &[(String, String)] {
+        &self.config
+    }
+
+    /// Attaches a config option to the dataset, replacing any previous value.
+    pub fn set_config_option(&mut self, key: &str, value: &str) {
+        self.config.retain(|(k, _)| k != key);
+        self.config.push((key.to_string(), value.to_string()));
+    }
+
+    /// Sets the dataset's config options on the current thread until the
+    /// returned guard is dropped.
+    pub(crate) fn config_guard(&self) -> Result<ConfigGuard> {
+        ConfigGuard::set(&self.config)
+    }
+
+    /// Calls `f` with `options` set as config options on the current thread,
+    /// e.g. `&[("GDAL_NUM_THREADS", "ALL_CPUS")]`. The previous values are
+    /// restored afterwards.
+    pub fn with_config<R, F>(&self, options: &[(&str, &str)], f: F) -> Result<R>
+    where
+        F: FnOnce(&TypedDataset<T>) -> R,
+    {
+        let _guard = ConfigGuard::set(options)?;
+        Ok(f(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{config_option, is_secret_key, DatasetOpenOptions};
+    use crate::dataset::TypedDataset;
+    use crate::window::Window;
+    use std::path::Path;
+
+    #[test]
+    fn open_dataset_with_options() {
+        let ds = TypedDataset::<u16>::open_with_options(
+            Path::new("testdata/test_u16.tif"),
+            &[("NUM_THREADS", "2")],
+        )
+        .unwrap();
+        let buffer = ds.read(1, Window::new((100, 100), (2, 1))).unwrap();
+
+        assert_eq!(buffer.data, vec![6656, 6764]);
+    }
+
+    #[test]
+    fn restrict_drivers() {
+        let path = Path::new("testdata/test_u16.tif");
+        let png_only = DatasetOpenOptions {
+            allowed_drivers: vec!["PNG".to_string()],
+            ..Default::default()
+        };
+        assert!(TypedDataset::<u16>::open_with(path, &png_only).is_err());
+
+        let gtiff = DatasetOpenOptions {
+            allowed_drivers: vec!["GTiff".to_string()],
+            open_options: vec![("NUM_THREADS".to_string(), "2".to_string())],
+            ..Default::default()
+        };
+        let ds = TypedDataset::<u16>::open_with(path, &gtiff).unwrap();
+        assert_eq!(ds.size(), (333, 333));
+    }
+
+    #[test]
+    fn scoped_config_options() {
+        let ds = TypedDataset::<u8>::open(Path::new("testdata/test_u8.tif")).unwrap();
+        let value = ds
+            .with_config(&[("GDAL_NUM_THREADS", "ALL_CPUS")], |_| {
+                config_option("GDAL_NUM_THREADS")
+            })
+            .unwrap();
+
+        assert_eq!(value.as_deref(), Some("ALL_CPUS"));
+        assert_eq!(config_option("GDAL_NUM_THREADS"), None);
+    }
+
+    #[test]
+    fn debug_hides_credentials() {
+        let options = DatasetOpenOptions {
+            config: vec![
+                ("AWS_REGION".to_string(), "us-west-2".to_string()),
+                ("AWS_SECRET_ACCESS_KEY".to_string(), "hunter2".to_string()),
+            ],
+            ..DatasetOpenOptions::default()
+        };
+        let debug = format!("{:?}", options);
+
+        assert!(debug.contains("us-west-2"));
+        assert!(!debug.contains("hunter2"));
+        assert!(is_secret_key("AZURE_STORAGE_SAS_TOKEN"));
+        assert!(!is_secret_key("GDAL_NUM_THREADS"));
+    }
+}
@@ -0,0 +1,143 @@
+use crate::config::NameValueList;
+use crate::errors::{check_cpl_err, Error, Result};
+use crate::request::{call_progress, ProgressFn};
+use crate::typed_rasterband::{Access, GdalFrom, ReadWrite, TypedRasterBand};
+use gdal::raster::types::GdalType;
+use gdal_sys::{
+    GDALRasterBandCopyWholeRaster, GDALSetRasterNoDataValue, GDALSetRasterOffset,
+    GDALSetRasterScale,
+};
+use std::os::raw::c_void;
+use std::ptr;
+
+/// Options for `TypedRasterBand::copy_to`.
+#[derive(Default)]
+pub struct CopyOptions<'p> {
+    /// Hints that the target is compressed, so GDAL writes it in whole
+    /// blocks.
+    pub compressed: bool,
+    /// Skips regions of the source that are sparse holes in the file.
+    pub skip_holes: bool,
+    /// Called with the fraction copied so far. Returning `false` cancels the
+    /// copy.
+    pub progress: Option<&'p mut ProgressFn<'p>>,
+}
+
+impl<'a, T: Copy + GdalType + GdalFrom<f64> + Into<f64>, A: Access> TypedRasterBand<'a, T, A> {
+    /// Copies every pixel of the band into `target`, which must be the same
+    /// size, along with its nodata value, scale and offset.
+    ///
+    /// GDAL streams the copy in chunks that suit both bands' block layouts.
+    pub fn copy_to(
+        &self,
+        target: &TypedRasterBand<T, ReadWrite>,
+        options: CopyOptions,
+    ) -> Result<()> {
+        if self.size() != target.size() {
+            return Err(Error::Alignment(format!(
+                "band sizes {:?} and {:?} differ",
+                self.size(),
+                target.size()
+            )));
+        }
+        let mut gdal_options = Vec::new();
+        if options.compressed {
+            gdal_options.push(("COMPRESSED", "YES"));
+        }
+        if options.skip_holes {
+            gdal_options.push(("SKIP_HOLES", "YES"));
+        }
+        let gdal_options = NameValueList::new(&gdal_options)?;
+
+        let (c_source, c_target) =
+            unsafe { (self.rasterband()._c_ptr(), target.rasterband()._c_ptr()) };
+        let mut progress = options.progress;
+        let (pfn_progress, progress_data) = match progress {
+            Some(ref mut f) => (
+                Some(call_progress as unsafe extern "C" fn(_, _, _) -> _),
+                f as *mut &mut ProgressFn<'_> as *mut c_void,
+            ),
+            None => (None, ptr::null_mut()),
+        };
+        check_cpl_err(unsafe {
+            GDALRasterBandCopyWholeRaster(
+                c_source,
+                c_target,
+                gdal_options.as_ptr(),
+                pfn_progress,
+                progress_data,
+            )
+        })?;
+
+        if let Some(nodata) = self.no_data_value() {
+            check_cpl_err(unsafe { GDALSetRasterNoDataValue(c_target, nodata.into()) })?;
+        }
+        if let Some(scale) = self.scale() {
+            check_cpl_err(unsafe { GDALSetRasterScale(c_target, scale) })?;
+        }
+        if let Some(offset) = self.offset() {
+            check_cpl_err(unsafe { GDALSetRasterOffset(c_target, offset) })?;
+        }
+        target.record_history("copy", &[("source_band", self.band_index().to_string())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::copy::CopyOptions;
+    use crate::create::DatasetBuilder;
+    use crate::dataset::TypedDataset;
+    use crate::errors::Error;
+    use crate::window::Window;
+    use std::path::Path;
+
+    #[test]
+    fn copy_band_with_nodata() {
+        let source = TypedDataset::<u16>::open(Path::new("testdata/test_u16_nodata.tif")).unwrap();
+        let target = DatasetBuilder::<u16>::new("MEM", "", (333, 333))
+            .create()
+            .unwrap();
+
+        let mut last = 0.0;
+        let mut progress = |complete| {
+            last = complete;
+            true
+        };
+        source
+            .with_band(1, |band| {
+                target.with_band_mut(1, |out| {
+                    let options = CopyOptions {
+                        progress: Some(&mut progress),
+                        ..CopyOptions::default()
+                    };
+                    band.copy_to(out, options)
+                })
+            })
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(last, 1.0);
+        let buffer = target.read(1, Window::new((100, 100), (2, 1))).unwrap();
+        assert_eq!(buffer.data, vec![6656, 6764]);
+        target
+            .with_band(1, |band| assert_eq!(band.no_data_value(), Some(42)))
+            .unwrap();
+    }
+
+    #[test]
+    fn copy_to_smaller_band() {
+        let source = TypedDataset::<u16>::open(Path::new("testdata/test_u16.tif")).unwrap();
+        let target = DatasetBuilder::<u16>::new("MEM", "", (10, 10))
+            .create()
+            .unwrap();
+
+        let result = source
+            .with_band(1, |band| {
+                target.with_band_mut(1, |out| band.copy_to(out, CopyOptions::default()))
+            })
+            .unwrap()
+            .unwrap();
+        assert!(matches!(result, Err(Error::Alignment(_))));
+    }
+}
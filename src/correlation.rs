@@ -0,0 +1,130 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use gdal::raster::types::GdalType;
+
+/// How two bands vary together, from `TypedRasterBand::correlate`.
+///
+/// Statistics that are undefined, such as the correlation of a constant
+/// band, are NaN.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Correlation {
+    /// The number of pixel pairs used.
+    pub count: usize,
+    /// Pearson's correlation coefficient.
+    pub r: f64,
+    /// The sample covariance.
+    pub covariance: f64,
+    /// The least-squares fit of the other band's values as
+    /// `slope * value + intercept`.
+    pub slope: f64,
+    pub intercept: f64,
+}
+
+/// Accumulates co-moments one pair at a time, as Welford's algorithm does
+/// for the variance, so that large bands don't lose precision.
+#[derive(Debug, Default)]
+struct CoMoments {
+    count: usize,
+    mean_x: f64,
+    mean_y: f64,
+    sum_xx: f64,
+    sum_yy: f64,
+    sum_xy: f64,
+}
+
+impl CoMoments {
+    fn add(&mut self, x: f64, y: f64) {
+        self.count += 1;
+        let n = self.count as f64;
+        let dx = x - self.mean_x;
+        self.mean_x += dx / n;
+        let dy = y - self.mean_y;
+        self.mean_y += dy / n;
+        self.sum_xx += dx * (x - self.mean_x);
+        self.sum_yy += dy * (y - self.mean_y);
+        self.sum_xy += dx * (y - self.mean_y);
+    }
+
+    fn finish(&self) -> Correlation {
+        let slope = self.sum_xy / self.sum_xx;
+        Correlation {
+            count: self.count,
+            r: self.sum_xy / (self.sum_xx * self.sum_yy).sqrt(),
+            covariance: self.sum_xy / (self.count as f64 - 1.0),
+            slope,
+            intercept: self.mean_y - slope * self.mean_x,
+        }
+    }
+}
+
+impl<'a, T, A: Access> TypedRasterBand<'a, T, A>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Into<f64>,
+{
+    /// Correlates the band with `other` one block at a time, after checking
+    /// that the bands share a grid.
+    ///
+    /// A pixel is used only if it's valid in both bands: pairs where either
+    /// value is nodata or NaN are skipped.
+    pub fn correlate<U, B>(&self, other: &TypedRasterBand<U, B>) -> Result<Correlation>
+    where
+        U: Copy + GdalType + GdalFrom<f64> + Into<f64>,
+        B: Access,
+    {
+        self.check_same_grid(other)?;
+
+        let nodata_x = self.no_data_value().map(Into::into);
+        let nodata_y = other.no_data_value().map(Into::into);
+        let valid = |v: f64, nodata: Option<f64>| !v.is_nan() && Some(v) != nodata;
+        let mut moments = CoMoments::default();
+        for window in self.block_windows() {
+            let xs: TypedBuffer<T> = self.read(window.offset, window.size, window.size)?.into();
+            let ys: TypedBuffer<U> = other.read(window.offset, window.size, window.size)?.into();
+            for (&x, &y) in xs.data.iter().zip(&ys.data) {
+                let (x, y): (f64, f64) = (x.into(), y.into());
+                if valid(x, nodata_x) && valid(y, nodata_y) {
+                    moments.add(x, y);
+                }
+            }
+        }
+        Ok(moments.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::correlation::CoMoments;
+    use crate::typed_rasterband::TypedRasterBand;
+    use gdal::raster::dataset::Dataset;
+    use std::path::Path;
+
+    #[test]
+    fn fit_line() {
+        let mut moments = CoMoments::default();
+        for &(x, y) in &[(1.0, 5.0), (2.0, 7.0), (3.0, 9.0), (4.0, 11.0)] {
+            moments.add(x, y);
+        }
+        let c = moments.finish();
+
+        assert_eq!(c.count, 4);
+        assert!((c.r - 1.0).abs() < 1e-12);
+        assert!((c.slope - 2.0).abs() < 1e-12);
+        assert!((c.intercept - 3.0).abs() < 1e-12);
+        assert!((c.covariance - 10.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn correlate_with_nodata_copy() {
+        let ds = Dataset::open(Path::new("testdata/test_u16.tif")).unwrap();
+        let ds_nodata = Dataset::open(Path::new("testdata/test_u16_nodata.tif")).unwrap();
+        let (band, band_nodata) = (ds.rasterband(1).unwrap(), ds_nodata.rasterband(1).unwrap());
+        let a = TypedRasterBand::<u16>::from_rasterband(&band).unwrap();
+        let b = TypedRasterBand::<u16>::from_rasterband(&band_nodata).unwrap();
+
+        let c = a.correlate(&b).unwrap();
+        assert!(c.count > 0 && c.count <= 333 * 333);
+        assert!((c.r - 1.0).abs() < 1e-9);
+        assert!((c.slope - 1.0).abs() < 1e-9);
+    }
+}
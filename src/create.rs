@@ -0,0 +1,164 @@
+use crate::config::NameValueList;
+use crate::dataset::TypedDataset;
+use crate::errors::{check_cpl_err, Error, Result};
+use crate::typed_rasterband::GdalFrom;
+use gdal::raster::dataset::Dataset;
+use gdal::raster::driver::Driver;
+use gdal::raster::types::GdalType;
+use gdal_sys::{CPLErr, GDALCreate, GDALFillRaster, GDALSetRasterNoDataValue};
+use std::ffi::CString;
+use std::io;
+use std::marker::PhantomData;
+
+/// Builds a new dataset whose bands have pixel type `T`.
+///
+/// If a nodata value is given but no fill, the bands are filled with the
+/// nodata value, so that pixels nothing is written to don't read as zeros.
+pub struct DatasetBuilder<T> {
+    driver: String,
+    pub(crate) path: String,
+    size: (usize, usize),
+    bands: usize,
+    fill: Option<T>,
+    nodata: Option<T>,
+    geo_transform: Option<[f64; 6]>,
+    projection: Option<String>,
+    creation_options: Vec<(String, String)>,
+    pixel_type: PhantomData<T>,
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64> + Into<f64>> DatasetBuilder<T> {
+    /// Starts building a single-band dataset of `size` pixels at `path`
+    /// with the named driver, such as `"GTiff"` or `"MEM"`.
+    pub fn new(driver: &str, path: &str, size: (usize, usize)) -> DatasetBuilder<T> {
+        DatasetBuilder {
+            driver: driver.to_string(),
+            path: path.to_string(),
+            size,
+            bands: 1,
+            fill: None,
+            nodata: None,
+            geo_transform: None,
+            projection: None,
+            creation_options: Vec::new(),
+            pixel_type: PhantomData,
+        }
+    }
+
+    pub fn bands(mut self, bands: usize) -> Self {
+        self.bands = bands;
+        self
+    }
+
+    /// The value every pixel starts with.
+    pub fn fill(mut self, value: T) -> Self {
+        self.fill = Some(value);
+        self
+    }
+
+    pub fn nodata(mut self, value: T) -> Self {
+        self.nodata = Some(value);
+        self
+    }
+
+    pub fn geo_transform(mut self, geo_transform: [f64; 6]) -> Self {
+        self.geo_transform = Some(geo_transform);
+        self
+    }
+
+    pub fn projection(mut self, wkt: &str) -> Self {
+        self.projection = Some(wkt.to_string());
+        self
+    }
+
+    /// Adds a driver-specific creation option, such as
+    /// `("COMPRESS", "DEFLATE")`.
+    pub fn creation_option(mut self, key: &str, value: &str) -> Self {
+        self.creation_options
+            .push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn create(self) -> Result<TypedDataset<T>> {
+        let driver = Driver::get(&self.driver)?;
+        let c_path = CString::new(self.path.as_str()).map_err(io::Error::from)?;
+        let options: Vec<(&str, &str)> = self
+            .creation_options
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        let options = NameValueList::new(&options)?;
+
+        let c_dataset = unsafe {
+            GDALCreate(
+                driver._c_ptr(),
+                c_path.as_ptr(),
+                self.size.0 as i32,
+                self.size.1 as i32,
+                self.bands as i32,
+                T::gdal_type(),
+                options.as_ptr() as *mut *mut _,
+            )
+        };
+        if c_dataset.is_null() {
+            return Err(Error::last_cpl_error(CPLErr::CE_Failure));
+        }
+        let dataset = unsafe { Dataset::_with_c_ptr(c_dataset) };
+
+        if let Some(geo_transform) = self.geo_transform {
+            dataset.set_geo_transform(&geo_transform)?;
+        }
+        if let Some(projection) = &self.projection {
+            dataset.set_projection(projection)?;
+        }
+        for index in 1..=self.bands as isize {
+            let band = dataset.rasterband(index)?;
+            let c_band = unsafe { band._c_ptr() };
+            if let Some(nodata) = self.nodata {
+                check_cpl_err(unsafe { GDALSetRasterNoDataValue(c_band, nodata.into()) })?;
+            }
+            if let Some(fill) = self.fill.or(self.nodata) {
+                check_cpl_err(unsafe { GDALFillRaster(c_band, fill.into(), 0.0) })?;
+            }
+        }
+        TypedDataset::from_dataset(dataset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::create::DatasetBuilder;
+    use crate::window::Window;
+
+    #[test]
+    fn create_filled_with_nodata() {
+        let ds = DatasetBuilder::<i16>::new("MEM", "", (8, 4))
+            .bands(2)
+            .nodata(-9999)
+            .create()
+            .unwrap();
+
+        assert_eq!(ds.band_count(), 2);
+        let buffer = ds.read(2, Window::new((0, 0), (8, 4))).unwrap();
+        assert!(buffer.data.iter().all(|&v| v == -9999));
+        ds.with_band(2, |band| assert_eq!(band.no_data_value(), Some(-9999)))
+            .unwrap();
+    }
+
+    #[test]
+    fn create_with_fill() {
+        let ds = DatasetBuilder::<f32>::new("MEM", "", (3, 3))
+            .fill(1.5)
+            .nodata(f32::NAN)
+            .geo_transform([100.0, 10.0, 0.0, 200.0, 0.0, -10.0])
+            .create()
+            .unwrap();
+
+        let buffer = ds.read(1, Window::new((0, 0), (3, 3))).unwrap();
+        assert_eq!(buffer.data, vec![1.5; 9]);
+        assert_eq!(
+            ds.geo_transform(),
+            Some([100.0, 10.0, 0.0, 200.0, 0.0, -10.0])
+        );
+    }
+}
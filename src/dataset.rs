@@ -0,0 +1,207 @@
+use crate::buffer::TypedBuffer;
+use crate::config::DatasetOpenOptions;
+use crate::error_handler::with_error_context;
+use crate::errors::{Error, Result};
+use crate::io_stats::IoStats;
+use crate::trace::{buffer_bytes, instrument};
+use crate::typed_rasterband::{GdalFrom, ReadWrite, TypeError, TypedRasterBand};
+use crate::vsi::MemFile;
+use crate::window::Window;
+use gdal::raster::dataset::Dataset;
+use gdal::raster::types::GdalType;
+use gdal_sys::{GDALAccess, GDALGetAccess};
+use std::marker::PhantomData;
+use std::mem;
+use std::os::raw::c_int;
+use std::path::Path;
+use std::sync::Arc;
+
+/// A dataset whose bands all have pixel type `T`.
+pub struct TypedDataset<T: Copy + GdalType> {
+    dataset: Dataset,
+    // Declared after `dataset` so that the dataset closes first.
+    pub(crate) backing: Option<MemFile>,
+    pub(crate) config: Vec<(String, String)>,
+    pub(crate) io_stats: Option<Arc<IoStats>>,
+    /// How the dataset was opened, less its config options.
+    pub(crate) open_options: DatasetOpenOptions,
+    pixel_type: PhantomData<T>,
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64>> TypedDataset<T> {
+    pub fn open(path: &Path) -> Result<TypedDataset<T>> {
+        TypedDataset::open_with(path, &DatasetOpenOptions::default())
+    }
+
+    /// Opens a dataset for update, so that it can be written to with `write`
+    /// and `with_band_mut`. Datasets opened with `open` are read-only.
+    pub fn open_rw(path: &Path) -> Result<TypedDataset<T>> {
+        let options = DatasetOpenOptions {
+            update: true,
+            ..Default::default()
+        };
+        TypedDataset::open_with(path, &options)
+    }
+
+    /// Wraps a dataset, checking that every band has pixel type `T`.
+    pub fn from_dataset(dataset: Dataset) -> Result<TypedDataset<T>> {
+        for index in 1..=dataset.count() {
+            if dataset.band_type(index)? != T::gdal_type() {
+                return Err(TypeError {}.into());
+            }
+        }
+        Ok(TypedDataset {
+            dataset,
+            backing: None,
+            config: Vec::new(),
+            io_stats: None,
+            open_options: DatasetOpenOptions::default(),
+            pixel_type: PhantomData,
+        })
+    }
+
+    pub fn dataset(&self) -> &Dataset {
+        &self.dataset
+    }
+
+    pub(crate) fn dataset_mut(&mut self) -> &mut Dataset {
+        &mut self.dataset
+    }
+
+    pub fn into_dataset(self) -> Dataset {
+        self.dataset
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        self.dataset.size()
+    }
+
+    pub fn band_count(&self) -> isize {
+        self.dataset.count()
+    }
+
+    pub fn geo_transform(&self) -> Option<[f64; 6]> {
+        self.dataset.geo_transform().ok()
+    }
+
+    pub fn projection(&self) -> String {
+        self.dataset.projection()
+    }
+
+    /// Whether the dataset was opened for update.
+    pub fn is_writable(&self) -> bool {
+        unsafe { GDALGetAccess(self.dataset._c_ptr()) == GDALAccess::GA_Update as c_int }
+    }
+
+    /// Calls `f` with a typed view of band `index`.
+    pub fn with_band<R, F>(&self, index: isize, f: F) -> Result<R>
+    where
+        F: FnOnce(&TypedRasterBand<T>) -> R,
+    {
+        let _guard = self.config_guard()?;
+        let band = self.dataset.rasterband(index)?;
+        let typed_band = TypedRasterBand::from_rasterband(&band)?;
+        Ok(f(&typed_band))
+    }
+
+    /// Calls `f` with a writable typed view of band `index`, failing with
+    /// `Error::ReadOnly` if the dataset wasn't opened for update.
+    pub fn with_band_mut<R, F>(&self, index: isize, f: F) -> Result<R>
+    where
+        F: FnOnce(&TypedRasterBand<T, ReadWrite>) -> R,
+    {
+        let _guard = self.config_guard()?;
+        let band = self.dataset.rasterband(index)?;
+        let typed_band = TypedRasterBand::from_writable_rasterband(&band)?;
+        Ok(f(&typed_band))
+    }
+
+    pub fn read(&self, index: isize, window: Window) -> Result<TypedBuffer<T>> {
+        let _guard = self.config_guard()?;
+        let buffer = with_error_context("read", Some(window), || {
+            instrument("read", window, buffer_bytes::<T>(window.size), || {
+                let buffer = self.dataset.read_raster_as::<T>(
+                    index,
+                    window.offset,
+                    window.size,
+                    window.size,
+                )?;
+                Ok(buffer.into())
+            })
+        })?;
+        if let Some(stats) = &self.io_stats {
+            let block_size = self.dataset.rasterband(index)?.block_size();
+            stats.record_read(window, window.size, block_size, mem::size_of::<T>());
+        }
+        Ok(buffer)
+    }
+
+    /// Writes `buffer` to `window` of band `index`, failing with
+    /// `Error::ReadOnly` before touching GDAL if the dataset wasn't opened
+    /// for update.
+    pub fn write(&self, index: isize, window: Window, buffer: &TypedBuffer<T>) -> Result<()> {
+        if !self.is_writable() {
+            return Err(Error::ReadOnly);
+        }
+        with_error_context("write", Some(window), || {
+            self.with_band_mut(index, |band| {
+                band.write_slice(window, &buffer.data, buffer.size)
+            })??;
+            let (x, y) = window.offset;
+            let (width, height) = window.size;
+            self.record_history(
+                index,
+                "write",
+                &[("window", format!("{},{},{},{}", x, y, width, height))],
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::dataset::TypedDataset;
+    use crate::errors::Error;
+    use crate::window::Window;
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn typed_dataset_read() {
+        let ds = TypedDataset::<u16>::open(Path::new("testdata/test_u16.tif")).unwrap();
+        let buffer = ds.read(1, Window::new((100, 100), (2, 1))).unwrap();
+
+        assert_eq!(ds.band_count(), 1);
+        assert_eq!(buffer.data, vec![6656, 6764]);
+    }
+
+    #[test]
+    fn write_to_read_only_dataset() {
+        let ds = TypedDataset::<u16>::open(Path::new("testdata/test_u16.tif")).unwrap();
+        let buffer = TypedBuffer::new((1, 1), vec![0]);
+        let result = ds.write(1, Window::new((0, 0), (1, 1)), &buffer);
+
+        assert!(!ds.is_writable());
+        assert!(matches!(result, Err(Error::ReadOnly)));
+    }
+
+    #[test]
+    fn write_to_read_write_dataset() {
+        let path = env::temp_dir().join("gdal_typed_rasterband_open_rw.tif");
+        fs::copy("testdata/test_u16.tif", &path).unwrap();
+        let ds = TypedDataset::<u16>::open_rw(&path).unwrap();
+        let window = Window::new((0, 0), (1, 1));
+        ds.write(1, window, &TypedBuffer::new((1, 1), vec![7]))
+            .unwrap();
+
+        assert!(ds.is_writable());
+        assert_eq!(ds.read(1, window).unwrap().data, vec![7]);
+    }
+
+    #[test]
+    fn typed_dataset_incorrect_type() {
+        assert!(TypedDataset::<u8>::open(Path::new("testdata/test_u16.tif")).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+
+type ReadFn<'f, T> = dyn Fn(Window) -> Result<TypedBuffer<T>> + 'f;
+
+/// A band whose pixels are computed on demand by a closure, such as an
+/// index derived from several real bands.
+///
+/// ```ignore
+/// let ndvi = DerivedBand::new(red.size(), |window| {
+///     let red = red.view(window).read_all()?;
+///     let nir = nir.view(window).read_all()?;
+///     let data = red.data.iter().zip(&nir.data);
+///     let data = data.map(|(&r, &n)| (n - r) / (n + r)).collect();
+///     Ok(TypedBuffer::new(window.size, data))
+/// });
+/// ```
+pub struct DerivedBand<'f, T> {
+    size: (usize, usize),
+    no_data: Option<T>,
+    geo_transform: Option<[f64; 6]>,
+    read_fn: Box<ReadFn<'f, T>>,
+}
+
+impl<'f, T: Copy> DerivedBand<'f, T> {
+    /// A band of `size` pixels that reads each window by calling `read_fn`,
+    /// which must return a buffer the size of the window.
+    pub fn new<F>(size: (usize, usize), read_fn: F) -> DerivedBand<'f, T>
+    where
+        F: Fn(Window) -> Result<TypedBuffer<T>> + 'f,
+    {
+        DerivedBand {
+            size,
+            no_data: None,
+            geo_transform: None,
+            read_fn: Box::new(read_fn),
+        }
+    }
+
+    /// A band that applies `f` to each pixel of `band`, keeping its
+    /// geotransform.
+    pub fn from_band<'a, U, A, F>(band: &'f TypedRasterBand<'a, U, A>, f: F) -> DerivedBand<'f, T>
+    where
+        U: Copy + GdalType + GdalFrom<f64>,
+        A: Access,
+        F: Fn(U) -> T + 'f,
+    {
+        let geo_transform = band.owning_dataset().geo_transform().ok();
+        DerivedBand::new(band.size(), move |window: Window| {
+            let buffer: TypedBuffer<U> = band.read(window.offset, window.size, window.size)?.into();
+            Ok(buffer.map(&f))
+        })
+        .with_geo_transform(geo_transform)
+    }
+
+    pub fn with_no_data(mut self, no_data: Option<T>) -> DerivedBand<'f, T> {
+        self.no_data = no_data;
+        self
+    }
+
+    pub fn with_geo_transform(mut self, geo_transform: Option<[f64; 6]>) -> DerivedBand<'f, T> {
+        self.geo_transform = geo_transform;
+        self
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    pub fn no_data_value(&self) -> Option<T> {
+        self.no_data
+    }
+
+    pub fn geo_transform(&self) -> Option<[f64; 6]> {
+        self.geo_transform
+    }
+
+    /// Computes `window`, clipped to the band, so the buffer may be smaller
+    /// than requested.
+    pub fn read(&self, window: Window) -> Result<TypedBuffer<T>> {
+        let window = match window.intersection(&Window::full(self.size)) {
+            Some(window) => window,
+            None => return Ok(TypedBuffer::new((0, 0), Vec::new())),
+        };
+        let buffer = (self.read_fn)(window)?;
+        assert_eq!(
+            buffer.size, window.size,
+            "derived band returned a buffer of the wrong size"
+        );
+        Ok(buffer)
+    }
+
+    /// Computes the whole band.
+    pub fn read_all(&self) -> Result<TypedBuffer<T>> {
+        self.read(Window::full(self.size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::derived::DerivedBand;
+    use crate::window::Window;
+
+    #[test]
+    fn derived_band_from_closure() {
+        let band = DerivedBand::new((10, 10), |window: Window| {
+            let data = (0..window.size.1)
+                .flat_map(|y| {
+                    (0..window.size.0).map(move |x| {
+                        (x as isize + window.offset.0) * (y as isize + window.offset.1)
+                    })
+                })
+                .map(|v| v as u32)
+                .collect();
+            Ok(TypedBuffer::new(window.size, data))
+        });
+
+        let buffer = band.read(Window::new((8, 2), (4, 2))).unwrap();
+        assert_eq!(buffer.size, (2, 2));
+        assert_eq!(buffer.data, vec![16, 18, 24, 27]);
+        assert_eq!(band.read_all().unwrap().get(9, 9), 81);
+    }
+}
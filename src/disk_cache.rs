@@ -0,0 +1,170 @@
+use crate::blocks::block_window;
+use crate::buffer::TypedBuffer;
+use crate::checksum::checksum;
+use crate::errors::Result;
+use crate::npy::NpyPixel;
+use crate::shared::SharedTypedBand;
+use crate::typed_rasterband::GdalFrom;
+use gdal::raster::types::GdalType;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_TEMP_FILE: AtomicUsize = AtomicUsize::new(0);
+
+/// A cache of decoded blocks on disk, so that repeated runs against remote
+/// datasets don't download and decode the same blocks again.
+///
+/// Each block is stored as a `.npy` file named by a digest of the dataset's
+/// path or URI, its open options, the band, the block and the pixel type,
+/// so that, say, an `OVERVIEW_LEVEL` doesn't share entries with the full
+/// resolution band. Entries are written to a temporary file unique to the
+/// process and call and renamed into place, so threads and processes can
+/// share a cache directory. Entries never expire: clear the cache if a source
+/// changes.
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    /// Uses `dir` for the cache, creating it if needed.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Result<DiskCache> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(DiskCache { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The file holding block `block` of band `band_index` of `uri` opened
+    /// with `open_options`.
+    fn entry_path<T: NpyPixel>(
+        &self,
+        uri: &Path,
+        open_options: &[(String, String)],
+        band_index: isize,
+        block: (usize, usize),
+    ) -> PathBuf {
+        let mut key = format!(
+            "{}\n{}\n{}\n{}\n{}",
+            uri.to_string_lossy(),
+            band_index,
+            block.0,
+            block.1,
+            T::DESCR
+        );
+        for (name, value) in open_options {
+            key.push_str(&format!("\n{}={}", name, value));
+        }
+        self.dir
+            .join(format!("{:016x}.npy", checksum(key.as_bytes())))
+    }
+
+    /// Returns the natural block at column `block.0` and row `block.1` of
+    /// `band`, reading it and storing it on a miss. Entries that can't be
+    /// loaded, such as ones truncated by a crash, count as misses.
+    pub fn get_or_read<T>(
+        &self,
+        band: &SharedTypedBand<T>,
+        block: (usize, usize),
+    ) -> Result<TypedBuffer<T>>
+    where
+        T: Copy + GdalType + GdalFrom<f64> + NpyPixel,
+    {
+        let path = self.entry_path::<T>(
+            band.path(),
+            &band.options().open_options,
+            band.band_index(),
+            block,
+        );
+        if let Ok(buffer) = TypedBuffer::load_npy(&path) {
+            return Ok(buffer);
+        }
+
+        let window = band.with_band(|b| block_window(b.size(), b.block_size(), block))?;
+        let buffer = band.read(window, window.size)?;
+        let mut temp = path.clone().into_os_string();
+        let n = NEXT_TEMP_FILE.fetch_add(1, Ordering::SeqCst);
+        temp.push(format!(".{}_{}.tmp", process::id(), n));
+        if let Err(e) = buffer
+            .save_npy(&temp)
+            .and_then(|_| fs::rename(&temp, &path).map_err(Into::into))
+        {
+            let _ = fs::remove_file(&temp);
+            return Err(e);
+        }
+        Ok(buffer)
+    }
+
+    /// Removes every entry.
+    pub fn clear(&self) -> Result<()> {
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "npy") {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::disk_cache::DiskCache;
+    use crate::shared::SharedTypedBand;
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+    use std::thread;
+
+    #[test]
+    fn entries_differ_by_key() {
+        let cache = DiskCache::new(env::temp_dir().join("gdal_typed_rasterband_keys")).unwrap();
+        let uri = Path::new("/vsicurl/https://example.com/dem.tif");
+        let entry = cache.entry_path::<u16>(uri, &[], 1, (0, 0));
+
+        assert_eq!(entry, cache.entry_path::<u16>(uri, &[], 1, (0, 0)));
+        assert_ne!(entry, cache.entry_path::<u16>(uri, &[], 1, (1, 0)));
+        assert_ne!(entry, cache.entry_path::<u16>(uri, &[], 2, (0, 0)));
+        assert_ne!(entry, cache.entry_path::<f32>(uri, &[], 1, (0, 0)));
+        let overview = [("OVERVIEW_LEVEL".to_string(), "0".to_string())];
+        assert_ne!(entry, cache.entry_path::<u16>(uri, &overview, 1, (0, 0)));
+    }
+
+    #[test]
+    fn read_through_disk_cache() {
+        let cache =
+            DiskCache::new(env::temp_dir().join("gdal_typed_rasterband_disk_cache")).unwrap();
+        cache.clear().unwrap();
+        let band = SharedTypedBand::<u8>::new("testdata/test_u8.tif", 1).unwrap();
+
+        let first = cache.get_or_read(&band, (0, 0)).unwrap();
+        assert!(cache.entry_path::<u8>(band.path(), &[], 1, (0, 0)).exists());
+        assert_eq!(cache.get_or_read(&band, (0, 0)).unwrap(), first);
+        assert_eq!(first.get(0, 1), 139);
+    }
+
+    #[test]
+    fn concurrent_misses() {
+        let dir = env::temp_dir().join("gdal_typed_rasterband_concurrent_cache");
+        let cache = DiskCache::new(&dir).unwrap();
+        cache.clear().unwrap();
+        let band = SharedTypedBand::<u8>::new("testdata/test_u8.tif", 1).unwrap();
+
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| cache.get_or_read(&band, (0, 1)).unwrap());
+            }
+        });
+        let leftovers = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "tmp"))
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+}
@@ -0,0 +1,187 @@
+use crate::bitmask::PixelMask;
+use crate::buffer::TypedBuffer;
+
+/// How `distance_transform` measures distance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DistanceMethod {
+    /// The exact Euclidean distance, by Felzenszwalb and Huttenlocher's
+    /// algorithm.
+    #[default]
+    Exact,
+    /// Steps between neighbouring pixels, including diagonal ones. Faster,
+    /// but overestimates distances off the eight compass directions by up
+    /// to about 8%.
+    Chamfer,
+}
+
+/// The squared distance transform of one line of samples `spacing` apart,
+/// where `f` holds each sample's squared distance so far.
+fn squared_distance_1d(f: &[f64], spacing: f64, out: &mut [f64]) {
+    // The lower envelope of parabolas rooted at the finite samples, with
+    // each parabola's region starting at the matching entry of `starts`.
+    let mut roots: Vec<usize> = Vec::new();
+    let mut starts: Vec<f64> = Vec::new();
+    for q in 0..f.len() {
+        if f[q].is_infinite() {
+            continue;
+        }
+        let pq = q as f64 * spacing;
+        let mut start = f64::NEG_INFINITY;
+        while let Some(&r) = roots.last() {
+            let pr = r as f64 * spacing;
+            start = ((f[q] + pq * pq) - (f[r] + pr * pr)) / (2.0 * (pq - pr));
+            if start <= starts[starts.len() - 1] {
+                roots.pop();
+                starts.pop();
+                start = f64::NEG_INFINITY;
+            } else {
+                break;
+            }
+        }
+        roots.push(q);
+        starts.push(start);
+    }
+
+    if roots.is_empty() {
+        out.iter_mut().for_each(|d| *d = f64::INFINITY);
+        return;
+    }
+    let mut k = 0;
+    for (p, d) in out.iter_mut().enumerate() {
+        let pp = p as f64 * spacing;
+        while k + 1 < roots.len() && starts[k + 1] < pp {
+            k += 1;
+        }
+        let pr = roots[k] as f64 * spacing;
+        *d = (pp - pr) * (pp - pr) + f[roots[k]];
+    }
+}
+
+fn exact_distances<M: PixelMask>(mask: &M, pixel_size: (f64, f64)) -> Vec<f64> {
+    let (width, height) = mask.size();
+    let mut grid = vec![0.0; width * height];
+    let mut line = vec![0.0; height];
+    let mut out = vec![0.0; height.max(width)];
+    for x in 0..width {
+        for (y, v) in line.iter_mut().enumerate() {
+            *v = if mask.is_set(x, y) {
+                0.0
+            } else {
+                f64::INFINITY
+            };
+        }
+        squared_distance_1d(&line, pixel_size.1, &mut out[..height]);
+        for y in 0..height {
+            grid[y * width + x] = out[y];
+        }
+    }
+    for row in grid.chunks_mut(width.max(1)) {
+        squared_distance_1d(row, pixel_size.0, &mut out[..width]);
+        row.copy_from_slice(&out[..width]);
+    }
+    grid.iter().map(|d| d.sqrt()).collect()
+}
+
+fn chamfer_distances<M: PixelMask>(mask: &M, pixel_size: (f64, f64)) -> Vec<f64> {
+    let (width, height) = mask.size();
+    let (w, h) = (width as isize, height as isize);
+    let diagonal = pixel_size.0.hypot(pixel_size.1);
+    let mut grid: Vec<f64> = (0..width * height)
+        .map(|i| {
+            if mask.is_set(i % width, i / width) {
+                0.0
+            } else {
+                f64::INFINITY
+            }
+        })
+        .collect();
+
+    // The neighbours already visited in a forward raster scan; the backward
+    // scan uses their mirror images.
+    let forward = [
+        (-1, 0, pixel_size.0),
+        (-1, -1, diagonal),
+        (0, -1, pixel_size.1),
+        (1, -1, diagonal),
+    ];
+    let relax = |x: isize, y: isize, sign: isize, grid: &mut [f64]| {
+        let i = (y * w + x) as usize;
+        for &(dx, dy, cost) in &forward {
+            let (nx, ny) = (x + sign * dx, y + sign * dy);
+            if nx >= 0 && ny >= 0 && nx < w && ny < h {
+                let d = grid[(ny * w + nx) as usize] + cost;
+                if d < grid[i] {
+                    grid[i] = d;
+                }
+            }
+        }
+    };
+    for y in 0..h {
+        for x in 0..w {
+            relax(x, y, 1, &mut grid);
+        }
+    }
+    for y in (0..h).rev() {
+        for x in (0..w).rev() {
+            relax(x, y, -1, &mut grid);
+        }
+    }
+    grid
+}
+
+/// The distance from each pixel to the nearest set pixel of `mask`, in the
+/// units of `pixel_size`, which is the width and height of a pixel (for
+/// example `(gt[1].abs(), gt[5].abs())` for map units).
+///
+/// Set pixels are 0, and every pixel is infinite if none are set.
+pub fn distance_transform<M: PixelMask>(
+    mask: &M,
+    pixel_size: (f64, f64),
+    method: DistanceMethod,
+) -> TypedBuffer<f32> {
+    let distances = match method {
+        DistanceMethod::Exact => exact_distances(mask, pixel_size),
+        DistanceMethod::Chamfer => chamfer_distances(mask, pixel_size),
+    };
+    TypedBuffer::new(
+        mask.size(),
+        distances.into_iter().map(|d| d as f32).collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::distance::{distance_transform, DistanceMethod};
+
+    fn centre_mask() -> TypedBuffer<bool> {
+        let mut mask = TypedBuffer::filled((5, 5), false);
+        mask.data[12] = true;
+        mask
+    }
+
+    #[test]
+    fn exact_distances() {
+        let mask = centre_mask();
+        let d = distance_transform(&mask, (1.0, 1.0), DistanceMethod::Exact);
+        assert_eq!(d.get(2, 2), 0.0);
+        assert!((d.get(4, 4) - 8f32.sqrt()).abs() < 1e-6);
+        assert!((d.get(4, 3) - 5f32.sqrt()).abs() < 1e-6);
+
+        let wide = distance_transform(&mask, (2.0, 1.0), DistanceMethod::Exact);
+        assert!((wide.get(4, 2) - 4.0).abs() < 1e-6);
+        assert!((wide.get(2, 4) - 2.0).abs() < 1e-6);
+
+        let empty = TypedBuffer::filled((3, 3), false);
+        let none = distance_transform(&empty, (1.0, 1.0), DistanceMethod::Exact);
+        assert!(none.data.iter().all(|d| d.is_infinite()));
+    }
+
+    #[test]
+    fn chamfer_distances() {
+        let d = distance_transform(&centre_mask(), (1.0, 1.0), DistanceMethod::Chamfer);
+        assert!((d.get(4, 4) - 8f32.sqrt()).abs() < 1e-6);
+        assert!((d.get(4, 3) - (1.0 + 2f32.sqrt())).abs() < 1e-6);
+        assert_eq!(d.get(0, 2), 2.0);
+    }
+}
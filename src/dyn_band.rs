@@ -0,0 +1,87 @@
+use crate::any::AnyTypedBand;
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::typed_rasterband::{Access, TypeError, TypedRasterBand};
+use crate::visitor::VisitablePixel;
+use crate::window::Window;
+use gdal_sys::GDALDataType;
+use std::any::Any;
+
+/// An object-safe view of a typed band, for code that can't be generic over
+/// the pixel type.
+pub trait DynTypedBand {
+    fn pixel_type(&self) -> GDALDataType::Type;
+
+    fn size(&self) -> (usize, usize);
+
+    /// Reads `window`, converting the pixels to `f64`.
+    fn read_f64(&self, window: Window) -> Result<TypedBuffer<f64>>;
+
+    /// Reads `window` into `out`, which must be a `TypedBuffer` of the
+    /// band's pixel type; otherwise a `TypeError` is returned.
+    fn read_into_any(&self, window: Window, out: &mut dyn Any) -> Result<()>;
+}
+
+impl<'a, T: VisitablePixel, A: Access> DynTypedBand for TypedRasterBand<'a, T, A> {
+    fn pixel_type(&self) -> GDALDataType::Type {
+        T::gdal_type()
+    }
+
+    fn size(&self) -> (usize, usize) {
+        TypedRasterBand::size(self)
+    }
+
+    fn read_f64(&self, window: Window) -> Result<TypedBuffer<f64>> {
+        let buffer: TypedBuffer<T> = self.read(window.offset, window.size, window.size)?.into();
+        Ok(buffer.map(Into::into))
+    }
+
+    fn read_into_any(&self, window: Window, out: &mut dyn Any) -> Result<()> {
+        let out = out.downcast_mut::<TypedBuffer<T>>().ok_or(TypeError {})?;
+        *out = self.read(window.offset, window.size, window.size)?.into();
+        Ok(())
+    }
+}
+
+impl<'a> AnyTypedBand<'a> {
+    pub fn as_dyn(&self) -> &dyn DynTypedBand {
+        match self {
+            AnyTypedBand::U8(b) => b,
+            AnyTypedBand::U16(b) => b,
+            AnyTypedBand::U32(b) => b,
+            AnyTypedBand::I16(b) => b,
+            AnyTypedBand::I32(b) => b,
+            AnyTypedBand::F32(b) => b,
+            AnyTypedBand::F64(b) => b,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::any::AnyTypedBand;
+    use crate::buffer::TypedBuffer;
+    use crate::window::Window;
+    use gdal::raster::dataset::Dataset;
+    use gdal_sys::GDALDataType;
+    use std::path::Path;
+
+    #[test]
+    fn dyn_band_reads() {
+        let ds = Dataset::open(Path::new("testdata/test_u16.tif")).unwrap();
+        let band = ds.rasterband(1).unwrap();
+        let any_band = AnyTypedBand::from_rasterband(&band).unwrap();
+        let band = any_band.as_dyn();
+        let window = Window::new((100, 100), (2, 1));
+
+        assert_eq!(band.pixel_type(), GDALDataType::GDT_UInt16);
+        assert_eq!(band.read_f64(window).unwrap().data, vec![6656.0, 6764.0]);
+
+        let mut out = TypedBuffer::<u16>::filled((0, 0), 0);
+        band.read_into_any(window, &mut out).unwrap();
+        assert_eq!(out.data, vec![6656, 6764]);
+
+        let mut wrong = TypedBuffer::<u8>::filled((0, 0), 0);
+        assert!(band.read_into_any(window, &mut wrong).is_err());
+    }
+}
@@ -0,0 +1,107 @@
+use crate::buffer::TypedBuffer;
+use crate::colormap::RgbaBuffer;
+#[cfg(any(feature = "image", feature = "webp"))]
+use crate::errors::Result;
+#[cfg(feature = "image")]
+use image::{png::PNGEncoder, ColorType};
+
+impl RgbaBuffer {
+    /// The pixels as interleaved RGBA bytes.
+    pub fn to_rgba_bytes(&self) -> Vec<u8> {
+        self.data.iter().flat_map(|p| p.iter().cloned()).collect()
+    }
+}
+
+#[cfg(feature = "image")]
+fn encode_png(data: &[u8], size: (usize, usize), color: ColorType) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    PNGEncoder::new(&mut bytes).encode(data, size.0 as u32, size.1 as u32, color)?;
+    Ok(bytes)
+}
+
+#[cfg(feature = "webp")]
+fn encode_webp(encoder: webp::Encoder, quality: Option<f32>) -> Vec<u8> {
+    match quality {
+        Some(quality) => encoder.encode(quality).to_vec(),
+        None => encoder.encode_lossless().to_vec(),
+    }
+}
+
+impl TypedBuffer<u8> {
+    /// Encodes the buffer as a greyscale PNG in memory.
+    #[cfg(feature = "image")]
+    pub fn encode_png(&self) -> Result<Vec<u8>> {
+        encode_png(&self.data, self.size, ColorType::Gray(8))
+    }
+
+    /// Encodes the buffer as a greyscale WebP in memory, lossy with
+    /// `quality` between 0 and 100 or lossless if it's `None`.
+    #[cfg(feature = "webp")]
+    pub fn encode_webp(&self, quality: Option<f32>) -> Result<Vec<u8>> {
+        // WebP has no greyscale mode.
+        let rgb: Vec<u8> = self
+            .data
+            .iter()
+            .flat_map(|&v| std::iter::repeat_n(v, 3))
+            .collect();
+        let (width, height) = (self.size.0 as u32, self.size.1 as u32);
+        Ok(encode_webp(
+            webp::Encoder::from_rgb(&rgb, width, height),
+            quality,
+        ))
+    }
+}
+
+impl RgbaBuffer {
+    /// Encodes the buffer as an RGBA PNG in memory.
+    #[cfg(feature = "image")]
+    pub fn encode_png(&self) -> Result<Vec<u8>> {
+        encode_png(&self.to_rgba_bytes(), self.size, ColorType::RGBA(8))
+    }
+
+    /// Encodes the buffer as an RGBA WebP in memory, lossy with `quality`
+    /// between 0 and 100 or lossless if it's `None`.
+    #[cfg(feature = "webp")]
+    pub fn encode_webp(&self, quality: Option<f32>) -> Result<Vec<u8>> {
+        let rgba = self.to_rgba_bytes();
+        let (width, height) = (self.size.0 as u32, self.size.1 as u32);
+        Ok(encode_webp(
+            webp::Encoder::from_rgba(&rgba, width, height),
+            quality,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+
+    #[test]
+    fn rgba_bytes() {
+        let buffer = TypedBuffer::new((2, 1), vec![[1, 2, 3, 4], [5, 6, 7, 8]]);
+        assert_eq!(buffer.to_rgba_bytes(), vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn encode_png_tiles() {
+        const SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+        let gray = TypedBuffer::filled((4, 4), 100u8).encode_png().unwrap();
+        assert_eq!(gray[..8], SIGNATURE);
+        let rgba = TypedBuffer::filled((4, 4), [0, 0, 255, 255])
+            .encode_png()
+            .unwrap();
+        assert_eq!(rgba[..8], SIGNATURE);
+    }
+
+    #[cfg(feature = "webp")]
+    #[test]
+    fn encode_webp_tile() {
+        let bytes = TypedBuffer::filled((4, 4), 100u8)
+            .encode_webp(Some(80.0))
+            .unwrap();
+        assert_eq!(&bytes[..4], b"RIFF");
+        assert_eq!(&bytes[8..12], b"WEBP");
+    }
+}
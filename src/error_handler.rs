@@ -0,0 +1,115 @@
+use crate::errors::{Error, Result};
+use crate::window::Window;
+use gdal_sys::{CPLErr, CPLGetErrorHandlerUserData, CPLPopErrorHandler, CPLPushErrorHandlerEx};
+use std::cell::RefCell;
+use std::ffi::CStr;
+use std::marker::PhantomData;
+use std::os::raw::{c_char, c_int, c_void};
+
+/// A message GDAL reported through `CPLError`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CplMessage {
+    pub class: CPLErr::Type,
+    pub number: i32,
+    pub msg: String,
+}
+
+unsafe extern "C" fn record_message(class: CPLErr::Type, number: c_int, msg: *const c_char) {
+    if class == CPLErr::CE_Debug {
+        return;
+    }
+    let messages = &*(CPLGetErrorHandlerUserData() as *const RefCell<Vec<CplMessage>>);
+    let msg = CStr::from_ptr(msg).to_string_lossy().into_owned();
+    messages
+        .borrow_mut()
+        .push(CplMessage { class, number, msg });
+}
+
+/// Collects the warnings and errors GDAL reports on this thread while the
+/// capture is alive, instead of letting GDAL print them.
+///
+/// Captures nest; only the innermost one sees messages.
+pub struct ErrorCapture {
+    messages: Box<RefCell<Vec<CplMessage>>>,
+    // GDAL error handlers are per thread, so the capture must be dropped on
+    // the thread that created it.
+    thread: PhantomData<*const ()>,
+}
+
+impl ErrorCapture {
+    pub fn new() -> ErrorCapture {
+        let messages = Box::new(RefCell::new(Vec::new()));
+        unsafe {
+            CPLPushErrorHandlerEx(
+                Some(record_message),
+                &*messages as *const RefCell<Vec<CplMessage>> as *mut c_void,
+            );
+        }
+        ErrorCapture {
+            messages,
+            thread: PhantomData,
+        }
+    }
+
+    /// Removes and returns the messages captured so far.
+    pub fn take(&self) -> Vec<CplMessage> {
+        self.messages.borrow_mut().drain(..).collect()
+    }
+}
+
+impl Default for ErrorCapture {
+    fn default() -> ErrorCapture {
+        ErrorCapture::new()
+    }
+}
+
+impl Drop for ErrorCapture {
+    fn drop(&mut self) {
+        unsafe { CPLPopErrorHandler() }
+    }
+}
+
+/// Runs `f` with GDAL's messages captured. If it fails, the error is wrapped
+/// in `Error::Context` along with `operation`, `window` and whatever GDAL
+/// reported along the way.
+pub fn with_error_context<R, F>(operation: &str, window: Option<Window>, f: F) -> Result<R>
+where
+    F: FnOnce() -> Result<R>,
+{
+    let capture = ErrorCapture::new();
+    f().map_err(|source| Error::Context {
+        operation: operation.to_string(),
+        window,
+        messages: capture.take(),
+        source: Box::new(source),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dataset::TypedDataset;
+    use crate::errors::Error;
+    use crate::window::Window;
+    use gdal_sys::CPLErr;
+    use std::path::Path;
+
+    #[test]
+    fn read_error_has_context() {
+        let ds = TypedDataset::<u8>::open(Path::new("testdata/test_u8.tif")).unwrap();
+        let window = Window::new((330, 0), (10, 10));
+
+        match ds.read(1, window) {
+            Err(Error::Context {
+                operation,
+                window: w,
+                messages,
+                ..
+            }) => {
+                assert_eq!(operation, "read");
+                assert_eq!(w, Some(window));
+                assert!(messages.iter().any(|m| m.class == CPLErr::CE_Failure));
+            }
+            _ => panic!("expected an error with context"),
+        }
+    }
+}
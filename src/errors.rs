@@ -0,0 +1,184 @@
+use crate::error_handler::CplMessage;
+use crate::typed_rasterband::TypeError;
+use crate::window::Window;
+#[cfg(feature = "arrow")]
+use arrow::error::ArrowError;
+use gdal::errors::Error as GdalError;
+use gdal_sys::{CPLErr, CPLGetLastErrorMsg, CPLGetLastErrorNo};
+#[cfg(feature = "parquet-export")]
+use parquet::errors::ParquetError;
+use std::error;
+use std::ffi::CStr;
+use std::fmt;
+use std::io;
+#[cfg(feature = "tiff")]
+use tiff::TiffError;
+
+#[derive(Debug)]
+pub enum Error {
+    Gdal(GdalError),
+    Type(TypeError),
+    Io(io::Error),
+    #[cfg(feature = "arrow")]
+    Arrow(ArrowError),
+    #[cfg(feature = "parquet-export")]
+    Parquet(ParquetError),
+    #[cfg(feature = "tiff")]
+    Tiff(TiffError),
+    Cpl {
+        class: CPLErr::Type,
+        number: i32,
+        msg: String,
+    },
+    /// Rasters that must share a grid don't.
+    Alignment(String),
+    /// A window, buffer or point doesn't fit the raster it's used with.
+    Bounds(String),
+    /// A geotransform can't be used, such as one that can't be inverted.
+    GeoTransform(String),
+    /// A band was opened for writing but its dataset is read-only.
+    ReadOnly,
+    /// A `calc` expression couldn't be parsed or refers to an unbound band.
+    Expression(String),
+    /// Integer arithmetic under `OverflowPolicy::Error` overflowed the pixel
+    /// type at this column and row.
+    Overflow {
+        col: usize,
+        row: usize,
+    },
+    /// An operation failed, along with what GDAL reported while it ran.
+    Context {
+        operation: String,
+        window: Option<Window>,
+        messages: Vec<CplMessage>,
+        source: Box<Error>,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Builds an error from the last error GDAL reported on this thread.
+    pub(crate) fn last_cpl_error(class: CPLErr::Type) -> Error {
+        let (number, msg) = unsafe {
+            let msg = CStr::from_ptr(CPLGetLastErrorMsg())
+                .to_string_lossy()
+                .into_owned();
+            (CPLGetLastErrorNo(), msg)
+        };
+        Error::Cpl { class, number, msg }
+    }
+}
+
+/// Converts the return value of a GDAL function into a `Result`.
+pub(crate) fn check_cpl_err(rv: CPLErr::Type) -> Result<()> {
+    if rv == CPLErr::CE_None {
+        Ok(())
+    } else {
+        Err(Error::last_cpl_error(rv))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Gdal(e) => write!(f, "GDAL error: {}", e),
+            Error::Type(e) => write!(f, "{}", e),
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            #[cfg(feature = "arrow")]
+            Error::Arrow(e) => write!(f, "Arrow error: {}", e),
+            #[cfg(feature = "parquet-export")]
+            Error::Parquet(e) => write!(f, "Parquet error: {}", e),
+            #[cfg(feature = "tiff")]
+            Error::Tiff(e) => write!(f, "TIFF error: {}", e),
+            Error::Cpl { number, msg, .. } => write!(f, "CPL error {}: {}", number, msg),
+            Error::Alignment(msg) => write!(f, "rasters are not aligned: {}", msg),
+            Error::Bounds(msg) => write!(f, "out of bounds: {}", msg),
+            Error::GeoTransform(msg) => write!(f, "unusable geotransform: {}", msg),
+            Error::ReadOnly => write!(
+                f,
+                "dataset was opened read-only; open it with `open_rw` to write to it"
+            ),
+            Error::Expression(msg) => write!(f, "invalid expression: {}", msg),
+            Error::Overflow { col, row } => {
+                write!(f, "arithmetic overflowed at pixel ({}, {})", col, row)
+            }
+            Error::Context {
+                operation,
+                window,
+                messages,
+                source,
+            } => {
+                write!(f, "{} failed", operation)?;
+                if let Some(window) = window {
+                    write!(
+                        f,
+                        " for window at {:?} of size {:?}",
+                        window.offset, window.size
+                    )?;
+                }
+                write!(f, ": {}", source)?;
+                for message in messages {
+                    write!(f, "; CPL error {}: {}", message.number, message.msg)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Type(e) => Some(e),
+            Error::Io(e) => Some(e),
+            Error::Context { source, .. } => Some(source.as_ref()),
+            #[cfg(feature = "arrow")]
+            Error::Arrow(e) => Some(e),
+            #[cfg(feature = "parquet-export")]
+            Error::Parquet(e) => Some(e),
+            #[cfg(feature = "tiff")]
+            Error::Tiff(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<GdalError> for Error {
+    fn from(e: GdalError) -> Error {
+        Error::Gdal(e)
+    }
+}
+
+impl From<TypeError> for Error {
+    fn from(e: TypeError) -> Error {
+        Error::Type(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl From<ArrowError> for Error {
+    fn from(e: ArrowError) -> Error {
+        Error::Arrow(e)
+    }
+}
+
+#[cfg(feature = "parquet-export")]
+impl From<ParquetError> for Error {
+    fn from(e: ParquetError) -> Error {
+        Error::Parquet(e)
+    }
+}
+
+#[cfg(feature = "tiff")]
+impl From<TiffError> for Error {
+    fn from(e: TiffError) -> Error {
+        Error::Tiff(e)
+    }
+}
@@ -0,0 +1,310 @@
+use crate::bitmask::PixelMask;
+use crate::dataset::TypedDataset;
+use crate::errors::Result;
+use crate::geo;
+use crate::transform::{self, GeoTransform};
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+use geo_types::{Coordinate, LineString, MultiPolygon, Polygon};
+use std::collections::HashMap;
+
+type Point = (f64, f64);
+
+/// Traces the outlines of the set pixels of `mask` as rings of pixel
+/// corners. Outer rings run clockwise as seen on screen and holes
+/// counter-clockwise, dropping corners where the outline runs straight.
+fn trace_rings<M: PixelMask>(mask: &M) -> Vec<Vec<(i64, i64)>> {
+    let (width, height) = mask.size();
+    let set = |x: i64, y: i64| {
+        x >= 0
+            && y >= 0
+            && x < width as i64
+            && y < height as i64
+            && mask.is_set(x as usize, y as usize)
+    };
+
+    // Each boundary edge, keyed by its start, with the set pixel on its right.
+    let mut edges: HashMap<(i64, i64), Vec<(i64, i64)>> = HashMap::new();
+    for y in 0..height as i64 {
+        for x in 0..width as i64 {
+            if !set(x, y) {
+                continue;
+            }
+            let sides = [
+                ((0, -1), (x, y), (x + 1, y)),
+                ((1, 0), (x + 1, y), (x + 1, y + 1)),
+                ((0, 1), (x + 1, y + 1), (x, y + 1)),
+                ((-1, 0), (x, y + 1), (x, y)),
+            ];
+            for &((dx, dy), start, end) in &sides {
+                if !set(x + dx, y + dy) {
+                    edges.entry(start).or_default().push(end);
+                }
+            }
+        }
+    }
+
+    let mut starts: Vec<(i64, i64)> = edges.keys().cloned().collect();
+    starts.sort_by_key(|&(x, y)| (y, x));
+    let mut rings = Vec::new();
+    for start in starts {
+        while let Some(next) = edges.get_mut(&start).and_then(|e| e.pop()) {
+            let mut ring = vec![start];
+            let mut current = next;
+            while current != start {
+                ring.push(current);
+                current = edges
+                    .get_mut(&current)
+                    .and_then(|e| e.pop())
+                    .expect("pixel outlines always close");
+            }
+            ring.push(start);
+            rings.push(drop_straight_corners(ring));
+        }
+    }
+    rings
+}
+
+fn drop_straight_corners(ring: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+    let n = ring.len() - 1;
+    let mut corners: Vec<(i64, i64)> = (0..n)
+        .filter(|&i| {
+            let prev = ring[(i + n - 1) % n];
+            let (p, next) = (ring[i], ring[i + 1]);
+            (p.0 - prev.0) * (next.1 - p.1) != (p.1 - prev.1) * (next.0 - p.0)
+        })
+        .map(|i| ring[i])
+        .collect();
+    corners.push(corners[0]);
+    corners
+}
+
+/// Twice the signed area of a closed ring.
+fn signed_area(ring: &[(i64, i64)]) -> i64 {
+    ring.windows(2)
+        .map(|w| w[0].0 * w[1].1 - w[1].0 * w[0].1)
+        .sum()
+}
+
+fn contains(ring: &[(i64, i64)], point: Point) -> bool {
+    let mut inside = false;
+    for w in ring.windows(2) {
+        let ((x0, y0), (x1, y1)) = (
+            (w[0].0 as f64, w[0].1 as f64),
+            (w[1].0 as f64, w[1].1 as f64),
+        );
+        if (y0 > point.1) != (y1 > point.1) && point.0 < x0 + (point.1 - y0) / (y1 - y0) * (x1 - x0)
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// The distance from `p` to the segment from `a` to `b`.
+fn segment_distance(p: Point, a: Point, b: Point) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length2 = dx * dx + dy * dy;
+    let t = if length2 > 0.0 {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length2).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    ((p.0 - a.0 - t * dx).powi(2) + (p.1 - a.1 - t * dy).powi(2)).sqrt()
+}
+
+/// Douglas-Peucker simplification of an open line, keeping its ends.
+fn simplify_line(points: &[Point], tolerance: f64, out: &mut Vec<Point>) {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let farthest = (1..points.len() - 1)
+        .map(|i| (i, segment_distance(points[i], first, last)))
+        .fold((0, 0.0), |best, d| if d.1 > best.1 { d } else { best });
+    if farthest.1 > tolerance {
+        simplify_line(&points[..=farthest.0], tolerance, out);
+        out.pop();
+        simplify_line(&points[farthest.0..], tolerance, out);
+    } else {
+        out.extend_from_slice(&[first, last]);
+    }
+}
+
+/// Simplifies a closed ring, or returns `None` if it collapses.
+fn simplify_ring(ring: Vec<Point>, tolerance: f64) -> Option<Vec<Point>> {
+    if tolerance <= 0.0 {
+        return Some(ring);
+    }
+    // Split at the vertex farthest from the start so that both halves are
+    // open lines.
+    let start = ring[0];
+    let far = (1..ring.len() - 1)
+        .max_by(|&a, &b| {
+            let da = (ring[a].0 - start.0).hypot(ring[a].1 - start.1);
+            let db = (ring[b].0 - start.0).hypot(ring[b].1 - start.1);
+            da.partial_cmp(&db).unwrap()
+        })
+        .unwrap_or(0);
+    let mut out = Vec::new();
+    simplify_line(&ring[..=far], tolerance, &mut out);
+    out.pop();
+    simplify_line(&ring[far..], tolerance, &mut out);
+    if out.len() < 4 {
+        None
+    } else {
+        Some(out)
+    }
+}
+
+fn line_string(ring: Vec<Point>) -> LineString<f64> {
+    LineString(ring.into_iter().map(|(x, y)| Coordinate { x, y }).collect())
+}
+
+/// The outlines of the set pixels of `mask` as polygons in map coordinates,
+/// simplified so that no outline moves more than `simplify_tolerance` map
+/// units. Outlines that collapse when simplified are dropped.
+pub fn mask_outline<M: PixelMask>(
+    mask: &M,
+    gt: &GeoTransform,
+    simplify_tolerance: f64,
+) -> MultiPolygon<f64> {
+    let rings = trace_rings(mask);
+    let (exteriors, holes): (Vec<_>, Vec<_>) =
+        rings.into_iter().partition(|ring| signed_area(ring) > 0);
+
+    let mut interiors: Vec<Vec<Vec<(i64, i64)>>> = vec![Vec::new(); exteriors.len()];
+    for hole in holes {
+        // The set pixel to the right of the start of the hole's first edge
+        // lies inside the smallest exterior that contains the hole.
+        let (a, b) = (hole[0], hole[1]);
+        let (dx, dy) = ((b.0 - a.0).signum() as f64, (b.1 - a.1).signum() as f64);
+        let pixel = (
+            a.0 as f64 + dx / 2.0 - dy / 2.0,
+            a.1 as f64 + dy / 2.0 + dx / 2.0,
+        );
+        let owner = exteriors
+            .iter()
+            .enumerate()
+            .filter(|(_, ring)| contains(ring, pixel))
+            .min_by_key(|(_, ring)| signed_area(ring))
+            .map(|(i, _)| i);
+        if let Some(i) = owner {
+            interiors[i].push(hole);
+        }
+    }
+
+    let to_map = |ring: Vec<(i64, i64)>| -> Option<LineString<f64>> {
+        let ring = ring
+            .into_iter()
+            .map(|(x, y)| transform::apply(gt, x as f64, y as f64))
+            .collect();
+        simplify_ring(ring, simplify_tolerance).map(line_string)
+    };
+    let polygons = exteriors
+        .into_iter()
+        .zip(interiors)
+        .filter_map(|(exterior, holes)| {
+            let exterior = to_map(exterior)?;
+            Some(Polygon::new(
+                exterior,
+                holes.into_iter().filter_map(&to_map).collect(),
+            ))
+        })
+        .collect();
+    MultiPolygon(polygons)
+}
+
+impl<'a, T: Copy + GdalType + GdalFrom<f64>, A: Access> TypedRasterBand<'a, T, A> {
+    /// The outline of the band's valid pixels, according to its GDAL mask,
+    /// in map coordinates. See `mask_outline`.
+    pub fn valid_data_footprint(&self, simplify_tolerance: f64) -> Result<MultiPolygon<f64>> {
+        let gt = self.owning_dataset().geo_transform()?;
+        let mask = self.read_mask(Window::full(self.size()))?;
+        Ok(mask_outline(&mask, &gt, simplify_tolerance))
+    }
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64>> TypedDataset<T> {
+    /// The extent of the dataset as a polygon in map coordinates.
+    pub fn footprint(&self) -> Result<Polygon<f64>> {
+        Ok(geo::footprint(self.dataset())?)
+    }
+}
+
+fn ring_json(ring: &LineString<f64>) -> String {
+    let coords: Vec<String> = ring
+        .0
+        .iter()
+        .map(|c| format!("[{},{}]", c.x, c.y))
+        .collect();
+    format!("[{}]", coords.join(","))
+}
+
+fn polygon_rings_json(polygon: &Polygon<f64>) -> String {
+    let rings: Vec<String> = std::iter::once(polygon.exterior())
+        .chain(polygon.interiors())
+        .map(ring_json)
+        .collect();
+    format!("[{}]", rings.join(","))
+}
+
+/// The polygon as a GeoJSON geometry.
+pub fn polygon_to_geojson(polygon: &Polygon<f64>) -> String {
+    format!(
+        "{{\"type\":\"Polygon\",\"coordinates\":{}}}",
+        polygon_rings_json(polygon)
+    )
+}
+
+/// The polygons as a GeoJSON geometry.
+pub fn multi_polygon_to_geojson(polygons: &MultiPolygon<f64>) -> String {
+    let polygons: Vec<String> = polygons.0.iter().map(polygon_rings_json).collect();
+    format!(
+        "{{\"type\":\"MultiPolygon\",\"coordinates\":[{}]}}",
+        polygons.join(",")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::footprint::{mask_outline, multi_polygon_to_geojson};
+
+    #[test]
+    fn outline_with_hole() {
+        // A ring of set pixels around an unset centre, and a lone pixel.
+        let mask = TypedBuffer::new(
+            (5, 3),
+            vec![
+                true, true, true, false, false, //
+                true, false, true, false, true, //
+                true, true, true, false, false,
+            ],
+        );
+        let gt = [0.0, 1.0, 0.0, 0.0, 0.0, -1.0];
+        let outline = mask_outline(&mask, &gt, 0.0);
+
+        assert_eq!(outline.0.len(), 2);
+        let square = &outline.0[0];
+        assert_eq!(square.exterior().0.len(), 5);
+        assert_eq!(square.interiors().len(), 1);
+        assert_eq!(outline.0[1].interiors().len(), 0);
+        assert_eq!(
+            multi_polygon_to_geojson(&outline)
+                .split("\"coordinates\"")
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn simplify_staircase() {
+        // A diagonal staircase collapses to a triangle when simplified.
+        let mask = TypedBuffer::new((4, 4), (0..16).map(|i| i % 4 <= i / 4).collect());
+        let gt = [0.0, 1.0, 0.0, 0.0, 0.0, 1.0];
+
+        let exact = mask_outline(&mask, &gt, 0.0);
+        assert_eq!(exact.0[0].exterior().0.len(), 11);
+        let simplified = mask_outline(&mask, &gt, 1.0);
+        assert_eq!(simplified.0[0].exterior().0.len(), 4);
+    }
+}
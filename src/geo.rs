@@ -0,0 +1,109 @@
+use crate::transform::{self, GeoTransform};
+use crate::window::Window;
+use gdal::errors::Result as GdalResult;
+use gdal::raster::dataset::Dataset;
+use geo_types::{Coordinate, LineString, Polygon, Rect};
+
+impl Window {
+    /// The smallest window containing a rectangle in map coordinates. Returns
+    /// `None` if the geotransform can't be inverted. The window may extend
+    /// beyond the raster; intersect it with `Window::full` to clip it.
+    pub fn from_rect(rect: &Rect<f64>, gt: &GeoTransform) -> Option<Window> {
+        let inv = transform::invert(gt)?;
+        let corners = [
+            (rect.min.x, rect.min.y),
+            (rect.min.x, rect.max.y),
+            (rect.max.x, rect.min.y),
+            (rect.max.x, rect.max.y),
+        ];
+
+        let (mut x0, mut y0) = (f64::INFINITY, f64::INFINITY);
+        let (mut x1, mut y1) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for &(x, y) in &corners {
+            let (px, py) = transform::apply(&inv, x, y);
+            x0 = x0.min(px);
+            y0 = y0.min(py);
+            x1 = x1.max(px);
+            y1 = y1.max(py);
+        }
+
+        let (x0, y0) = (x0.floor(), y0.floor());
+        let (x1, y1) = (x1.ceil(), y1.ceil());
+        Some(Window::new(
+            (x0 as isize, y0 as isize),
+            ((x1 - x0) as usize, (y1 - y0) as usize),
+        ))
+    }
+
+    /// The smallest window containing the bounding box of a polygon in map
+    /// coordinates.
+    pub fn from_polygon(polygon: &Polygon<f64>, gt: &GeoTransform) -> Option<Window> {
+        let coords = &polygon.exterior().0;
+        let first = coords.first()?;
+        let (min, max) = coords.iter().fold((*first, *first), |(min, max), c| {
+            (
+                Coordinate {
+                    x: min.x.min(c.x),
+                    y: min.y.min(c.y),
+                },
+                Coordinate {
+                    x: max.x.max(c.x),
+                    y: max.y.max(c.y),
+                },
+            )
+        });
+        Window::from_rect(&Rect { min, max }, gt)
+    }
+
+    /// The outline of the window in map coordinates.
+    pub fn to_polygon(&self, gt: &GeoTransform) -> Polygon<f64> {
+        let (x0, y0) = (self.offset.0 as f64, self.offset.1 as f64);
+        let (x1, y1) = (x0 + self.size.0 as f64, y0 + self.size.1 as f64);
+        let ring: Vec<(f64, f64)> = [(x0, y0), (x1, y0), (x1, y1), (x0, y1), (x0, y0)]
+            .iter()
+            .map(|&(px, py)| transform::apply(gt, px, py))
+            .collect();
+        Polygon::new(LineString::from(ring), vec![])
+    }
+}
+
+/// The extent of a dataset as a polygon in map coordinates.
+pub fn footprint(dataset: &Dataset) -> GdalResult<Polygon<f64>> {
+    let gt = dataset.geo_transform()?;
+    Ok(Window::full(dataset.size()).to_polygon(&gt))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::window::Window;
+    use geo_types::{Coordinate, Rect};
+
+    #[test]
+    fn window_from_rect() {
+        let gt = [450000.0, 30.0, 0.0, 5410000.0, 0.0, -30.0];
+        let rect = Rect {
+            min: Coordinate {
+                x: 450100.0,
+                y: 5409000.0,
+            },
+            max: Coordinate {
+                x: 450400.0,
+                y: 5409500.0,
+            },
+        };
+
+        let window = Window::from_rect(&rect, &gt).unwrap();
+        assert_eq!(window, Window::new((3, 16), (11, 18)));
+    }
+
+    #[test]
+    fn window_polygon_corners() {
+        let gt = [450000.0, 30.0, 0.0, 5410000.0, 0.0, -30.0];
+        let polygon = Window::new((1, 2), (3, 4)).to_polygon(&gt);
+
+        let coords = &polygon.exterior().0;
+        assert_eq!(coords.len(), 5);
+        assert_eq!((coords[0].x, coords[0].y), (450030.0, 5409940.0));
+        assert_eq!((coords[2].x, coords[2].y), (450120.0, 5409820.0));
+    }
+}
@@ -0,0 +1,175 @@
+use crate::errors::{Error, Result};
+use crate::transform::{self, GeoTransform};
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use gdal::raster::types::GdalType;
+
+/// A rectangle in map coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+/// How `Grid::snap` rounds a bounding box to whole pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SnapStrategy {
+    /// The smallest set of pixels covering the box.
+    #[default]
+    Expand,
+    /// The largest set of pixels inside the box.
+    Shrink,
+    /// The pixel edges nearest the box's edges.
+    Nearest,
+}
+
+// Pixel edges closer than this to the box's edges, in pixels, count as on
+// them, so that rounding error doesn't add or lose a row.
+const SNAP_EPSILON: f64 = 1e-9;
+
+/// A raster grid: a CRS, a geotransform and a size in pixels. This is what
+/// rasters must share to be combined pixel by pixel, so it's the natural
+/// target for warping several of them to match, with
+/// `TypedDataset::warp_to_grid` or `warp::mosaic`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid {
+    /// The CRS as WKT, if the grid has one.
+    pub crs: Option<String>,
+    pub transform: GeoTransform,
+    pub size: (usize, usize),
+}
+
+impl Grid {
+    pub fn new(crs: Option<String>, transform: GeoTransform, size: (usize, usize)) -> Grid {
+        Grid {
+            crs,
+            transform,
+            size,
+        }
+    }
+
+    /// The grid of `band`'s dataset.
+    pub fn from_reference<T, A>(band: &TypedRasterBand<T, A>) -> Result<Grid>
+    where
+        T: Copy + GdalType + GdalFrom<f64>,
+        A: Access,
+    {
+        let dataset = band.owning_dataset();
+        let crs = dataset.projection();
+        Ok(Grid {
+            crs: if crs.is_empty() { None } else { Some(crs) },
+            transform: dataset.geo_transform()?,
+            size: band.size(),
+        })
+    }
+
+    /// Whether the grid's rows and columns run along the map axes.
+    pub fn is_north_up(&self) -> bool {
+        self.transform[2] == 0.0 && self.transform[4] == 0.0
+    }
+
+    /// The bounding box of the grid's corners.
+    pub fn bounds(&self) -> BoundingBox {
+        let (w, h) = (self.size.0 as f64, self.size.1 as f64);
+        let corners = [(0.0, 0.0), (w, 0.0), (0.0, h), (w, h)]
+            .iter()
+            .map(|&(col, row)| transform::apply(&self.transform, col, row))
+            .collect::<Vec<_>>();
+        BoundingBox {
+            min_x: corners.iter().map(|c| c.0).fold(f64::INFINITY, f64::min),
+            min_y: corners.iter().map(|c| c.1).fold(f64::INFINITY, f64::min),
+            max_x: corners
+                .iter()
+                .map(|c| c.0)
+                .fold(f64::NEG_INFINITY, f64::max),
+            max_y: corners
+                .iter()
+                .map(|c| c.1)
+                .fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+
+    /// The grid with this grid's CRS, resolution and pixel edges that covers
+    /// `bbox`, rounded to whole pixels by `strategy`. The result may extend
+    /// past this grid.
+    ///
+    /// Fails unless the grid is north-up.
+    pub fn snap(&self, bbox: &BoundingBox, strategy: SnapStrategy) -> Result<Grid> {
+        if !self.is_north_up() || transform::invert(&self.transform).is_none() {
+            return Err(Error::GeoTransform(
+                "only north-up grids can be snapped to".to_string(),
+            ));
+        }
+        let gt = &self.transform;
+        // Pixel coordinates of the box's edges, in increasing order.
+        let span = |a: f64, b: f64| if a <= b { (a, b) } else { (b, a) };
+        let (col0, col1) = span((bbox.min_x - gt[0]) / gt[1], (bbox.max_x - gt[0]) / gt[1]);
+        let (row0, row1) = span((bbox.max_y - gt[3]) / gt[5], (bbox.min_y - gt[3]) / gt[5]);
+
+        let round = |lo: f64, hi: f64| match strategy {
+            SnapStrategy::Expand => ((lo + SNAP_EPSILON).floor(), (hi - SNAP_EPSILON).ceil()),
+            SnapStrategy::Shrink => ((lo - SNAP_EPSILON).ceil(), (hi + SNAP_EPSILON).floor()),
+            SnapStrategy::Nearest => (lo.round(), hi.round()),
+        };
+        let (col0, col1) = round(col0, col1);
+        let (row0, row1) = round(row0, row1);
+        let (x0, y0) = transform::apply(gt, col0, row0);
+        Ok(Grid {
+            crs: self.crs.clone(),
+            transform: [x0, gt[1], 0.0, y0, 0.0, gt[5]],
+            size: (
+                (col1 - col0).max(0.0) as usize,
+                (row1 - row0).max(0.0) as usize,
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::Error;
+    use crate::grid::{BoundingBox, Grid, SnapStrategy};
+
+    fn grid() -> Grid {
+        Grid::new(None, [1000.0, 30.0, 0.0, 5000.0, 0.0, -30.0], (100, 100))
+    }
+
+    #[test]
+    fn grid_bounds() {
+        assert_eq!(
+            grid().bounds(),
+            BoundingBox {
+                min_x: 1000.0,
+                min_y: 2000.0,
+                max_x: 4000.0,
+                max_y: 5000.0,
+            }
+        );
+    }
+
+    #[test]
+    fn snap_to_grid() {
+        let bbox = BoundingBox {
+            min_x: 1045.0,
+            min_y: 4000.0,
+            max_x: 1100.0,
+            max_y: 4950.0,
+        };
+
+        let expanded = grid().snap(&bbox, SnapStrategy::Expand).unwrap();
+        assert_eq!(expanded.transform, [1030.0, 30.0, 0.0, 4970.0, 0.0, -30.0]);
+        assert_eq!(expanded.size, (3, 33));
+
+        let shrunk = grid().snap(&bbox, SnapStrategy::Shrink).unwrap();
+        assert_eq!(shrunk.transform, [1060.0, 30.0, 0.0, 4940.0, 0.0, -30.0]);
+        assert_eq!(shrunk.size, (1, 31));
+
+        let mut rotated = grid();
+        rotated.transform[2] = 1.0;
+        assert!(matches!(
+            rotated.snap(&bbox, SnapStrategy::Expand),
+            Err(Error::GeoTransform(_))
+        ));
+    }
+}
@@ -0,0 +1,303 @@
+use crate::dataset::TypedDataset;
+use crate::errors::Result;
+use crate::typed_rasterband::{Access, GdalFrom, ReadWrite, TypedRasterBand};
+use gdal::metadata::Metadata;
+use gdal::raster::dataset::Dataset;
+use gdal::raster::rasterband::RasterBand;
+use gdal::raster::types::GdalType;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The metadata domain that processing history is kept in.
+pub const HISTORY_DOMAIN: &str = "PROCESSING_HISTORY";
+
+/// The dataset metadata item, in `HISTORY_DOMAIN`, that turns recording on.
+const ENABLED_ITEM: &str = "ENABLED";
+
+/// The band metadata item, in `HISTORY_DOMAIN`, holding the entry count, so
+/// that appending doesn't read every earlier entry.
+const COUNT_ITEM: &str = "ENTRY_COUNT";
+
+/// One operation that wrote to a band.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub operation: String,
+    pub parameters: Vec<(String, String)>,
+    /// When the operation ran, in RFC 3339 form.
+    pub timestamp: String,
+    /// The version of this crate that ran it.
+    pub version: String,
+}
+
+/// Formats seconds since the Unix epoch as an RFC 3339 UTC timestamp.
+fn rfc3339(secs: u64) -> String {
+    let (days, rem) = ((secs / 86400) as i64, secs % 86400);
+    // Converts days since the epoch to a civil date, after Howard Hinnant.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem / 60 % 60,
+        rem % 60
+    )
+}
+
+impl HistoryEntry {
+    /// An entry for `operation` running now.
+    ///
+    /// Parameter names and values may not contain `;`, and names may not
+    /// contain `=`.
+    pub fn new(operation: &str, parameters: &[(&str, String)]) -> HistoryEntry {
+        for (name, value) in parameters {
+            assert!(
+                !name.contains([';', '=']) && !value.contains(';'),
+                "history parameter {:?} contains a separator",
+                name
+            );
+        }
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        HistoryEntry {
+            operation: operation.to_string(),
+            parameters: parameters
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect(),
+            timestamp: rfc3339(secs),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+
+    fn parameters_item(&self) -> String {
+        let pairs: Vec<String> = self
+            .parameters
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        pairs.join(";")
+    }
+}
+
+fn parse_parameters(item: &str) -> Vec<(String, String)> {
+    item.split(';')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.find('=') {
+            Some(i) => (pair[..i].to_string(), pair[i + 1..].to_string()),
+            None => (pair.to_string(), String::new()),
+        })
+        .collect()
+}
+
+/// Reads the history recorded on `band`, oldest first.
+pub fn read_history(band: &RasterBand) -> Vec<HistoryEntry> {
+    let mut entries = Vec::new();
+    for n in 1.. {
+        let item =
+            |field: &str| band.metadata_item(&format!("ENTRY_{}_{}", n, field), HISTORY_DOMAIN);
+        let operation = match item("OPERATION") {
+            Some(operation) => operation,
+            None => break,
+        };
+        entries.push(HistoryEntry {
+            operation,
+            parameters: parse_parameters(&item("PARAMETERS").unwrap_or_default()),
+            timestamp: item("TIMESTAMP").unwrap_or_default(),
+            version: item("VERSION").unwrap_or_default(),
+        });
+    }
+    entries
+}
+
+/// Appends `entry` to the history recorded on `band`.
+pub fn append_history(band: &mut RasterBand, entry: &HistoryEntry) -> Result<()> {
+    let count = match band.metadata_item(COUNT_ITEM, HISTORY_DOMAIN) {
+        Some(count) => count.parse().unwrap_or(0),
+        // Histories recorded before the count was kept.
+        None => read_history(band).len(),
+    };
+    let n = count + 1;
+    let fields = [
+        ("OPERATION", entry.operation.clone()),
+        ("PARAMETERS", entry.parameters_item()),
+        ("TIMESTAMP", entry.timestamp.clone()),
+        ("VERSION", entry.version.clone()),
+    ];
+    for (field, value) in &fields {
+        band.set_metadata_item(&format!("ENTRY_{}_{}", n, field), value, HISTORY_DOMAIN)?;
+    }
+    band.set_metadata_item(COUNT_ITEM, &n.to_string(), HISTORY_DOMAIN)?;
+    Ok(())
+}
+
+/// Whether history is enabled on `dataset`. See `TypedDataset::enable_history`.
+pub fn history_enabled(dataset: &Dataset) -> bool {
+    dataset
+        .metadata_item(ENABLED_ITEM, HISTORY_DOMAIN)
+        .as_deref()
+        == Some("YES")
+}
+
+impl<'a, T: Copy + GdalType + GdalFrom<f64>, A: Access> TypedRasterBand<'a, T, A> {
+    /// The history recorded on the band. See `TypedDataset::enable_history`.
+    pub fn history(&self) -> Vec<HistoryEntry> {
+        read_history(self.rasterband())
+    }
+}
+
+impl<'a, T: Copy + GdalType + GdalFrom<f64>> TypedRasterBand<'a, T, ReadWrite> {
+    /// Records `operation` on the band, if its dataset has history enabled.
+    /// Crate operations that write a band call this once each when they
+    /// finish.
+    pub fn record_history(&self, operation: &str, parameters: &[(&str, String)]) -> Result<()> {
+        let dataset = self.owning_dataset();
+        if !history_enabled(dataset) {
+            return Ok(());
+        }
+        let mut band = dataset.rasterband(self.band_index())?;
+        append_history(&mut band, &HistoryEntry::new(operation, parameters))
+    }
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64>> TypedDataset<T> {
+    /// Starts recording each operation that writes a band of the dataset,
+    /// such as `write`, `copy_to`, `calc` or a flushed `BandWriter`, in the
+    /// `PROCESSING_HISTORY` metadata domain of the band written to.
+    ///
+    /// The setting is kept in the dataset's metadata, so it applies to
+    /// every handle to the dataset and stays on when it is reopened.
+    pub fn enable_history(&mut self) -> Result<()> {
+        self.dataset_mut()
+            .set_metadata_item(ENABLED_ITEM, "YES", HISTORY_DOMAIN)?;
+        Ok(())
+    }
+
+    /// Whether history is enabled. See `enable_history`.
+    pub fn history_enabled(&self) -> bool {
+        history_enabled(self.dataset())
+    }
+
+    /// Records `operation` on band `index`, if history is enabled.
+    pub fn record_history(
+        &self,
+        index: isize,
+        operation: &str,
+        parameters: &[(&str, String)],
+    ) -> Result<()> {
+        if !self.history_enabled() {
+            return Ok(());
+        }
+        self.with_band_mut(index, |band| band.record_history(operation, parameters))?
+    }
+
+    /// Enables history on `output`, a dataset made from this one, if it is
+    /// enabled here, and records `operation` on each of its bands.
+    pub(crate) fn record_derived<U>(
+        &self,
+        output: &mut TypedDataset<U>,
+        operation: &str,
+        parameters: &[(&str, String)],
+    ) -> Result<()>
+    where
+        U: Copy + GdalType + GdalFrom<f64>,
+    {
+        if !self.history_enabled() {
+            return Ok(());
+        }
+        output.enable_history()?;
+        // Outputs such as COGs come back read-only, where GDAL keeps the
+        // metadata in a sidecar instead.
+        let entry = HistoryEntry::new(operation, parameters);
+        for index in 1..=output.band_count() {
+            append_history(&mut output.dataset().rasterband(index)?, &entry)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::copy::CopyOptions;
+    use crate::create::DatasetBuilder;
+    use crate::history::{parse_parameters, rfc3339, HistoryEntry};
+    use crate::window::Window;
+
+    #[test]
+    fn format_timestamps() {
+        assert_eq!(rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(rfc3339(951_782_400 + 3723), "2000-02-29T01:02:03Z");
+
+        let entry = HistoryEntry::new("fill", &[("value", "7".to_string())]);
+        assert_eq!(parse_parameters(&entry.parameters_item()), entry.parameters);
+    }
+
+    #[test]
+    fn record_writes() {
+        let mut ds = DatasetBuilder::<u8>::new("MEM", "", (4, 4))
+            .create()
+            .unwrap();
+        ds.write(
+            1,
+            Window::new((0, 0), (1, 1)),
+            &TypedBuffer::filled((1, 1), 1),
+        )
+        .unwrap();
+        ds.enable_history().unwrap();
+        ds.write(
+            1,
+            Window::new((1, 2), (3, 2)),
+            &TypedBuffer::filled((3, 2), 2),
+        )
+        .unwrap();
+        ds.record_history(1, "smooth", &[("radius", "2".to_string())])
+            .unwrap();
+
+        let history = ds.with_band(1, |band| band.history()).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].operation, "write");
+        assert_eq!(
+            history[0].parameters,
+            vec![("window".to_string(), "1,2,3,2".to_string())]
+        );
+        assert_eq!(history[1].operation, "smooth");
+        assert_eq!(history[1].version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn record_one_entry_per_operation() {
+        let mut ds = DatasetBuilder::<u8>::new("MEM", "", (64, 64))
+            .create()
+            .unwrap();
+        ds.enable_history().unwrap();
+        let source = DatasetBuilder::<u8>::new("MEM", "", (64, 64))
+            .create()
+            .unwrap();
+
+        ds.with_band_mut(1, |band| {
+            let mut writer = band.writer();
+            writer.write(Window::full((64, 64)), &TypedBuffer::filled((64, 64), 3));
+            assert!(writer.dirty_blocks() > 1);
+            writer.flush().unwrap();
+            source
+                .with_band(1, |source| source.copy_to(band, CopyOptions::default()))
+                .unwrap()
+                .unwrap();
+        })
+        .unwrap();
+
+        let history = ds.with_band(1, |band| band.history()).unwrap();
+        let operations: Vec<&str> = history.iter().map(|e| e.operation.as_str()).collect();
+        assert_eq!(operations, vec!["write", "copy"]);
+    }
+}
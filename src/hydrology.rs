@@ -0,0 +1,276 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Flow direction codes, as ESRI and most hydrology tools use them.
+pub const EAST: u8 = 1;
+pub const SOUTHEAST: u8 = 2;
+pub const SOUTH: u8 = 4;
+pub const SOUTHWEST: u8 = 8;
+pub const WEST: u8 = 16;
+pub const NORTHWEST: u8 = 32;
+pub const NORTH: u8 = 64;
+pub const NORTHEAST: u8 = 128;
+/// A pixel with no lower neighbour, such as a pit or a flat.
+pub const NO_FLOW: u8 = 0;
+pub const NO_DATA: u8 = 255;
+
+const NEIGHBOURS: [(isize, isize, u8); 8] = [
+    (1, 0, EAST),
+    (1, 1, SOUTHEAST),
+    (0, 1, SOUTH),
+    (-1, 1, SOUTHWEST),
+    (-1, 0, WEST),
+    (-1, -1, NORTHWEST),
+    (0, -1, NORTH),
+    (1, -1, NORTHEAST),
+];
+
+/// How `flow_direction` routes water out of each pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowRouting {
+    /// All flow goes to the neighbour with the steepest drop.
+    D8,
+}
+
+/// A pixel waiting in the priority flood, ordered so that the lowest pops
+/// first from a `BinaryHeap`.
+struct Pending<T> {
+    z: f64,
+    index: usize,
+    value: T,
+}
+
+impl<T> PartialEq for Pending<T> {
+    fn eq(&self, other: &Pending<T>) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Pending<T> {}
+
+impl<T> PartialOrd for Pending<T> {
+    fn partial_cmp(&self, other: &Pending<T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Pending<T> {
+    fn cmp(&self, other: &Pending<T>) -> Ordering {
+        other
+            .z
+            .total_cmp(&self.z)
+            .then(other.index.cmp(&self.index))
+    }
+}
+
+/// Raises every pit of `dem` to the level at which it would spill, so that
+/// water can flow from every pixel to the edge or to nodata. Uses the
+/// priority-flood algorithm of Barnes et al., which needs the whole DEM in
+/// memory.
+///
+/// Nodata pixels are left alone and act as outlets, like the edges.
+pub fn fill_sinks<T: Copy + Into<f64> + PartialEq>(
+    dem: &TypedBuffer<T>,
+    nodata: Option<T>,
+) -> TypedBuffer<T> {
+    let (width, height) = dem.size;
+    let mut filled = dem.clone();
+    let mut done = vec![false; width * height];
+    let mut queue = BinaryHeap::new();
+    let is_nodata = |v: T| Some(v) == nodata;
+
+    for y in 0..height {
+        for x in 0..width {
+            let i = y * width + x;
+            let value = dem.data[i];
+            if is_nodata(value) {
+                done[i] = true;
+                continue;
+            }
+            let outlet = NEIGHBOURS.iter().any(|&(dx, dy, _)| {
+                let (nx, ny) = (x as isize + dx, y as isize + dy);
+                nx < 0
+                    || ny < 0
+                    || nx >= width as isize
+                    || ny >= height as isize
+                    || is_nodata(dem.get(nx as usize, ny as usize))
+            });
+            if outlet {
+                done[i] = true;
+                queue.push(Pending {
+                    z: value.into(),
+                    index: i,
+                    value,
+                });
+            }
+        }
+    }
+
+    while let Some(cell) = queue.pop() {
+        let (x, y) = (cell.index % width, cell.index / width);
+        for &(dx, dy, _) in &NEIGHBOURS {
+            let (nx, ny) = (x as isize + dx, y as isize + dy);
+            if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                continue;
+            }
+            let j = ny as usize * width + nx as usize;
+            if done[j] {
+                continue;
+            }
+            done[j] = true;
+            let original = filled.data[j];
+            let (z, value) = if original.into() < cell.z {
+                (cell.z, cell.value)
+            } else {
+                (original.into(), original)
+            };
+            filled.data[j] = value;
+            queue.push(Pending { z, index: j, value });
+        }
+    }
+    filled
+}
+
+/// The D8 flow direction of each pixel of `dem`: the neighbour with the
+/// steepest drop, allowing for `cell_size`, the width and height of a
+/// pixel in elevation units. Only neighbours inside the buffer count.
+///
+/// Ties go to the first direction clockwise from east. Nodata pixels are
+/// `NO_DATA`, and pixels with no lower neighbour are `NO_FLOW`.
+pub fn flow_direction<T: Copy + Into<f64> + PartialEq>(
+    dem: &TypedBuffer<T>,
+    cell_size: (f64, f64),
+    nodata: Option<T>,
+) -> TypedBuffer<u8> {
+    let (width, height) = dem.size;
+    let diagonal = cell_size.0.hypot(cell_size.1);
+    let mut directions = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let centre = dem.get(x, y);
+            if Some(centre) == nodata {
+                directions.push(NO_DATA);
+                continue;
+            }
+            let z: f64 = centre.into();
+            let mut best = (0.0, NO_FLOW);
+            for &(dx, dy, code) in &NEIGHBOURS {
+                let (nx, ny) = (x as isize + dx, y as isize + dy);
+                if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                    continue;
+                }
+                let neighbour = dem.get(nx as usize, ny as usize);
+                if Some(neighbour) == nodata {
+                    continue;
+                }
+                let distance = match (dx, dy) {
+                    (0, _) => cell_size.1,
+                    (_, 0) => cell_size.0,
+                    _ => diagonal,
+                };
+                let slope = (z - neighbour.into()) / distance;
+                if slope > best.0 {
+                    best = (slope, code);
+                }
+            }
+            directions.push(best.1);
+        }
+    }
+    TypedBuffer::new(dem.size, directions)
+}
+
+impl<'a, T, A: Access> TypedRasterBand<'a, T, A>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Into<f64> + PartialEq,
+{
+    /// Fills the band's sinks. Unlike `flow_direction`, this isn't
+    /// blockwise: a sink can spill anywhere in the band, so the priority
+    /// flood reads the whole band into memory. See `fill_sinks`.
+    pub fn fill_sinks(&self) -> Result<TypedBuffer<T>> {
+        let dem: TypedBuffer<T> = self.read_band()?.into();
+        Ok(fill_sinks(&dem, self.no_data_value()))
+    }
+
+    /// The flow direction of each pixel of the band, read one block at a
+    /// time with a one-pixel margin. The cell size comes from the
+    /// geotransform. See `flow_direction`.
+    pub fn flow_direction(&self, routing: FlowRouting) -> Result<TypedBuffer<u8>> {
+        let FlowRouting::D8 = routing;
+        let gt = self.owning_dataset().geo_transform()?;
+        let cell_size = (gt[1].abs(), gt[5].abs());
+        let nodata = self.no_data_value();
+        let full = Window::full(self.size());
+        let mut directions = TypedBuffer::filled(self.size(), NO_DATA);
+
+        for block in self.block_windows() {
+            let padded = Window::new(
+                (block.offset.0 - 1, block.offset.1 - 1),
+                (block.size.0 + 2, block.size.1 + 2),
+            )
+            .intersection(&full)
+            .expect("a padded block overlaps its band");
+            let dem: TypedBuffer<T> = self.read(padded.offset, padded.size, padded.size)?.into();
+            let local = flow_direction(&dem, cell_size, nodata);
+            let (ox, oy) = (
+                (block.offset.0 - padded.offset.0) as usize,
+                (block.offset.1 - padded.offset.1) as usize,
+            );
+            for y in 0..block.size.1 {
+                for x in 0..block.size.0 {
+                    directions.set(
+                        block.offset.0 as usize + x,
+                        block.offset.1 as usize + y,
+                        local.get(ox + x, oy + y),
+                    );
+                }
+            }
+        }
+        Ok(directions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::hydrology::{fill_sinks, flow_direction, EAST, NO_DATA, NO_FLOW, SOUTH};
+
+    #[test]
+    fn fill_pit() {
+        let dem = TypedBuffer::new(
+            (4, 3),
+            vec![
+                5u16, 5, 5, 5, //
+                5, 1, 2, 4, //
+                5, 5, 5, 5,
+            ],
+        );
+        let filled = fill_sinks(&dem, None);
+
+        assert_eq!(filled.get(1, 1), 4);
+        assert_eq!(filled.get(2, 1), 4);
+        assert_eq!(filled.get(3, 1), 4);
+        assert_eq!(filled.get(0, 0), 5);
+    }
+
+    #[test]
+    fn d8_directions() {
+        let dem = TypedBuffer::new(
+            (3, 2),
+            vec![
+                9.0f32, 8.0, 1.0, //
+                5.0, 4.0, -1.0,
+            ],
+        );
+        let directions = flow_direction(&dem, (1.0, 1.0), Some(-1.0));
+
+        assert_eq!(directions.get(1, 0), EAST);
+        assert_eq!(directions.get(0, 0), SOUTH);
+        assert_eq!(directions.get(2, 0), NO_FLOW);
+        assert_eq!(directions.get(2, 1), NO_DATA);
+    }
+}
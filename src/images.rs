@@ -0,0 +1,170 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::sidecar::Georeference;
+use crate::typed_rasterband::TypedRasterBand;
+use crate::window::Window;
+use gdal::raster::dataset::Dataset;
+use image::jpeg::JPEGEncoder;
+use image::{ColorType, GrayImage, ImageBuffer, ImageFormat, Luma, RgbImage};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+pub type Gray16Image = ImageBuffer<Luma<u16>, Vec<u16>>;
+
+impl TypedBuffer<u8> {
+    pub fn to_gray_image(&self) -> GrayImage {
+        let (width, height) = self.size;
+        ImageBuffer::from_raw(width as u32, height as u32, self.data.clone())
+            .expect("buffer size doesn't match length of data")
+    }
+
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let (width, height) = self.size;
+        image::save_buffer_with_format(
+            path,
+            &self.data,
+            width as u32,
+            height as u32,
+            ColorType::Gray(8),
+            ImageFormat::PNG,
+        )?;
+        Ok(())
+    }
+
+    /// Saves the buffer as a greyscale JPEG with `quality` between 1 and 100.
+    pub fn save_jpeg<P: AsRef<Path>>(&self, path: P, quality: u8) -> Result<()> {
+        let (width, height) = self.size;
+        let mut writer = BufWriter::new(File::create(path)?);
+        JPEGEncoder::new_with_quality(&mut writer, quality).encode(
+            &self.data,
+            width as u32,
+            height as u32,
+            ColorType::Gray(8),
+        )?;
+        Ok(())
+    }
+
+    /// Saves the buffer as a PNG along with world file and projection
+    /// sidecars.
+    pub fn save_png_georeferenced<P: AsRef<Path>>(
+        &self,
+        path: P,
+        georef: &Georeference,
+    ) -> Result<()> {
+        self.save_png(&path)?;
+        georef.write_sidecars(path)
+    }
+
+    /// Saves the buffer as a JPEG along with world file and projection
+    /// sidecars.
+    pub fn save_jpeg_georeferenced<P: AsRef<Path>>(
+        &self,
+        path: P,
+        quality: u8,
+        georef: &Georeference,
+    ) -> Result<()> {
+        self.save_jpeg(&path, quality)?;
+        georef.write_sidecars(path)
+    }
+}
+
+impl TypedBuffer<u16> {
+    pub fn to_gray16_image(&self) -> Gray16Image {
+        let (width, height) = self.size;
+        ImageBuffer::from_raw(width as u32, height as u32, self.data.clone())
+            .expect("buffer size doesn't match length of data")
+    }
+
+    /// Saves the buffer as a 16-bit greyscale PNG.
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let (width, height) = self.size;
+        let bytes: Vec<u8> = self
+            .data
+            .iter()
+            .flat_map(|v| v.to_be_bytes().to_vec())
+            .collect();
+        image::save_buffer_with_format(
+            path,
+            &bytes,
+            width as u32,
+            height as u32,
+            ColorType::Gray(16),
+            ImageFormat::PNG,
+        )?;
+        Ok(())
+    }
+}
+
+impl From<GrayImage> for TypedBuffer<u8> {
+    fn from(img: GrayImage) -> TypedBuffer<u8> {
+        let (width, height) = img.dimensions();
+        TypedBuffer::new((width as usize, height as usize), img.into_raw())
+    }
+}
+
+impl From<Gray16Image> for TypedBuffer<u16> {
+    fn from(img: Gray16Image) -> TypedBuffer<u16> {
+        let (width, height) = img.dimensions();
+        TypedBuffer::new((width as usize, height as usize), img.into_raw())
+    }
+}
+
+/// Interleaves three byte buffers of equal size into an RGB image.
+pub fn rgb_image(
+    red: &TypedBuffer<u8>,
+    green: &TypedBuffer<u8>,
+    blue: &TypedBuffer<u8>,
+) -> RgbImage {
+    assert!(
+        red.size == green.size && red.size == blue.size,
+        "RGB buffers must have the same size"
+    );
+    let (width, height) = red.size;
+    let mut data = Vec::with_capacity(3 * red.data.len());
+    for i in 0..red.data.len() {
+        data.extend_from_slice(&[red.data[i], green.data[i], blue.data[i]]);
+    }
+    ImageBuffer::from_raw(width as u32, height as u32, data)
+        .expect("buffer size doesn't match length of data")
+}
+
+/// Reads three byte bands of a dataset as the red, green, and blue channels
+/// of an image.
+pub fn read_rgb_image(dataset: &Dataset, bands: [isize; 3], window: Window) -> Result<RgbImage> {
+    let mut channels = Vec::with_capacity(3);
+    for &index in &bands {
+        let band = dataset.rasterband(index)?;
+        let typed_band = TypedRasterBand::<u8>::from_rasterband(&band)?;
+        let buffer: TypedBuffer<u8> = typed_band
+            .read(window.offset, window.size, window.size)?
+            .into();
+        channels.push(buffer);
+    }
+    Ok(rgb_image(&channels[0], &channels[1], &channels[2]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+
+    #[test]
+    fn gray_image_round_trip() {
+        let buffer = TypedBuffer::new((2, 3), vec![0u8, 50, 100, 150, 200, 250]);
+        let img = buffer.to_gray_image();
+
+        assert_eq!(img.dimensions(), (2, 3));
+        assert_eq!(img.get_pixel(1, 2).data, [250]);
+        assert_eq!(TypedBuffer::from(img), buffer);
+    }
+
+    #[test]
+    fn rgb_from_buffers() {
+        let r = TypedBuffer::filled((2, 2), 10u8);
+        let g = TypedBuffer::filled((2, 2), 20u8);
+        let b = TypedBuffer::filled((2, 2), 30u8);
+
+        let img = super::rgb_image(&r, &g, &b);
+        assert_eq!(img.get_pixel(1, 1).data, [10, 20, 30]);
+    }
+}
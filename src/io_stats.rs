@@ -0,0 +1,184 @@
+use crate::dataset::TypedDataset;
+use crate::typed_rasterband::GdalFrom;
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Counters for the I/O done through a dataset, and through any
+/// `BlockCache` given the same counters.
+///
+/// The counters can be shared between threads.
+#[derive(Debug, Default)]
+pub struct IoStats {
+    reads: AtomicU64,
+    blocks_read: AtomicU64,
+    pixels_decoded: AtomicU64,
+    bytes_requested: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+}
+
+/// A point-in-time copy of `IoStats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct IoStatsSnapshot {
+    /// The number of read calls.
+    pub reads: u64,
+    /// The number of natural blocks the reads touched, counting a block
+    /// once per read.
+    pub blocks_read: u64,
+    /// The number of pixels in those blocks. This is an upper bound on what
+    /// GDAL decoded, since its own block cache may already hold some.
+    pub pixels_decoded: u64,
+    /// The number of bytes of pixel data returned to callers.
+    pub bytes_requested: u64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+}
+
+impl IoStatsSnapshot {
+    /// The fraction of cache lookups that hit, or `None` if the cache was
+    /// never used.
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        let lookups = self.cache_hits + self.cache_misses;
+        if lookups == 0 {
+            None
+        } else {
+            Some(self.cache_hits as f64 / lookups as f64)
+        }
+    }
+}
+
+/// The number of natural blocks of size `block_size` that `window` touches.
+pub(crate) fn blocks_touched(window: Window, block_size: (usize, usize)) -> usize {
+    if window.size.0 == 0 || window.size.1 == 0 {
+        return 0;
+    }
+    let (x0, y0) = (window.offset.0 as usize, window.offset.1 as usize);
+    let columns = (x0 + window.size.0 - 1) / block_size.0 - x0 / block_size.0 + 1;
+    let rows = (y0 + window.size.1 - 1) / block_size.1 - y0 / block_size.1 + 1;
+    columns * rows
+}
+
+impl IoStats {
+    pub fn new() -> IoStats {
+        IoStats::default()
+    }
+
+    pub fn snapshot(&self) -> IoStatsSnapshot {
+        IoStatsSnapshot {
+            reads: self.reads.load(Ordering::Relaxed),
+            blocks_read: self.blocks_read.load(Ordering::Relaxed),
+            pixels_decoded: self.pixels_decoded.load(Ordering::Relaxed),
+            bytes_requested: self.bytes_requested.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            cache_misses: self.cache_misses.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn reset(&self) {
+        for counter in &[
+            &self.reads,
+            &self.blocks_read,
+            &self.pixels_decoded,
+            &self.bytes_requested,
+            &self.cache_hits,
+            &self.cache_misses,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Records a read of `window`, resampled to `size`, from a band with
+    /// natural blocks of `block_size` and pixels of `pixel_bytes` bytes.
+    pub(crate) fn record_read(
+        &self,
+        window: Window,
+        size: (usize, usize),
+        block_size: (usize, usize),
+        pixel_bytes: usize,
+    ) {
+        let blocks = blocks_touched(window, block_size) as u64;
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        self.blocks_read.fetch_add(blocks, Ordering::Relaxed);
+        self.pixels_decoded.fetch_add(
+            blocks * (block_size.0 * block_size.1) as u64,
+            Ordering::Relaxed,
+        );
+        self.bytes_requested
+            .fetch_add((size.0 * size.1 * pixel_bytes) as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_cache_lookup(&self, hit: bool) {
+        let counter = if hit {
+            &self.cache_hits
+        } else {
+            &self.cache_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64>> TypedDataset<T> {
+    /// Starts counting the I/O done through this dataset, returning the
+    /// counters so they can be shared with a `BlockCache`. Calling it again
+    /// returns the same counters.
+    pub fn enable_io_stats(&mut self) -> Arc<IoStats> {
+        self.io_stats
+            .get_or_insert_with(|| Arc::new(IoStats::new()))
+            .clone()
+    }
+
+    /// The I/O counted so far, if `enable_io_stats` has been called.
+    pub fn io_stats(&self) -> Option<IoStatsSnapshot> {
+        self.io_stats.as_ref().map(|stats| stats.snapshot())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dataset::TypedDataset;
+    use crate::io_stats::{blocks_touched, IoStats};
+    use crate::window::Window;
+    use std::path::Path;
+
+    #[test]
+    fn count_blocks_touched() {
+        assert_eq!(blocks_touched(Window::new((0, 0), (333, 24)), (333, 24)), 1);
+        assert_eq!(blocks_touched(Window::new((0, 20), (10, 10)), (333, 24)), 2);
+        assert_eq!(
+            blocks_touched(Window::new((250, 250), (10, 10)), (256, 256)),
+            4
+        );
+        assert_eq!(blocks_touched(Window::new((5, 5), (0, 10)), (256, 256)), 0);
+    }
+
+    #[test]
+    fn cache_hit_rate() {
+        let stats = IoStats::new();
+        assert_eq!(stats.snapshot().cache_hit_rate(), None);
+
+        stats.record_cache_lookup(true);
+        stats.record_cache_lookup(true);
+        stats.record_cache_lookup(false);
+        stats.record_cache_lookup(true);
+        assert_eq!(stats.snapshot().cache_hit_rate(), Some(0.75));
+
+        stats.reset();
+        assert_eq!(stats.snapshot().cache_hits, 0);
+    }
+
+    #[test]
+    fn count_dataset_reads() {
+        let mut ds = TypedDataset::<u16>::open(Path::new("testdata/test_u16.tif")).unwrap();
+        assert_eq!(ds.io_stats(), None);
+
+        ds.enable_io_stats();
+        ds.read(1, Window::new((0, 10), (4, 4))).unwrap();
+        let stats = ds.io_stats().unwrap();
+        assert_eq!(stats.reads, 1);
+        assert_eq!(stats.blocks_read, 2);
+        assert_eq!(stats.pixels_decoded, 2 * 333 * 12);
+        assert_eq!(stats.bytes_requested, 32);
+    }
+}
@@ -1,9 +1,117 @@
+pub mod aligned;
+pub mod any;
+pub mod area;
+#[cfg(feature = "ndarray")]
+pub mod array;
+#[cfg(feature = "arrow")]
+pub mod arrow_export;
+#[cfg(feature = "tokio")]
+pub mod async_read;
+pub mod atomic;
+pub mod bitmask;
+pub mod blocks;
+pub mod buffer;
+pub mod cache;
+pub mod calc;
+pub mod change;
+pub mod checksum;
+pub mod chips;
+pub mod classify;
+pub mod cloud;
+pub mod colormap;
+pub mod components;
+pub mod composite;
+pub mod config;
+pub mod copy;
+pub mod correlation;
+pub mod create;
+pub mod dataset;
+pub mod derived;
+pub mod disk_cache;
+pub mod distance;
+pub mod dyn_band;
+pub mod encode;
+pub mod error_handler;
+pub mod errors;
+#[cfg(feature = "geo-types")]
+pub mod footprint;
+#[cfg(feature = "geo-types")]
+pub mod geo;
+pub mod grid;
+pub mod history;
+pub mod hydrology;
+#[cfg(feature = "image")]
+pub mod images;
+pub mod io_stats;
+pub mod majority;
+#[cfg(feature = "nalgebra")]
+pub mod matrix;
+pub mod morphology;
+pub mod normalize;
+pub mod npy;
+pub mod overflow;
+pub mod pad;
+pub mod pansharpen;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+#[cfg(feature = "parquet-export")]
+pub mod parquet_export;
+pub mod pipeline;
+pub mod pixel;
+pub mod planner;
+pub mod prefetch;
+pub mod raw;
+pub mod reduce;
+pub mod region;
+pub mod relief;
+pub mod remote;
+pub mod request;
+pub mod resample;
+pub mod retry;
+pub mod rle;
+mod rng;
+pub mod sampling;
+pub mod shared;
+pub mod sidecar;
+pub mod simd;
+pub mod sink;
+pub mod smoothing;
+pub mod source;
+mod srs;
+#[cfg(feature = "stac")]
+pub mod stac;
+pub mod statistics;
+#[cfg(all(feature = "tokio", feature = "futures"))]
+pub mod stream;
+pub mod stretch;
+pub mod subdatasets;
+pub mod testing;
+#[cfg(feature = "tiff")]
+pub mod tiff_fallback;
+pub mod timeseries;
+mod trace;
+pub mod transform;
+pub mod translate;
+pub mod validate;
+pub mod view;
+pub mod viewshed;
+pub mod visitor;
+pub mod vsi;
+pub mod warp;
+pub mod window;
+pub mod writer;
+pub mod xyz;
+pub mod zarr;
+
 pub mod typed_rasterband {
+    use crate::errors::{Error, Result};
+    use crate::trace::{buffer_bytes, instrument};
+    use crate::window::Window;
     use gdal::errors::Result as GdalResult;
     use gdal::raster::dataset::{Buffer, Dataset};
     use gdal::raster::rasterband::RasterBand;
     use gdal::raster::types::GdalType;
-    use gdal_sys::GDALDataType;
+    use gdal_sys::{GDALAccess, GDALDataType, GDALGetBandNumber, GDALGetRasterAccess};
     use std::error;
     use std::fmt;
     use std::marker::PhantomData;
@@ -67,15 +175,41 @@ pub mod typed_rasterband {
         }
     }
 
-    pub struct TypedRasterBand<'a, T: Copy + GdalType> {
+    mod sealed {
+        pub trait Sealed {}
+    }
+
+    /// Marks whether a `TypedRasterBand` may be written to.
+    pub trait Access: sealed::Sealed {}
+
+    /// A band that can only be read.
+    #[derive(Debug, Clone, Copy)]
+    pub struct ReadOnly;
+
+    /// A band that belongs to a dataset opened for update.
+    #[derive(Debug, Clone, Copy)]
+    pub struct ReadWrite;
+
+    impl sealed::Sealed for ReadOnly {}
+    impl sealed::Sealed for ReadWrite {}
+    impl Access for ReadOnly {}
+    impl Access for ReadWrite {}
+
+    /// A band with pixel type `T`.
+    ///
+    /// `A` records whether the band may be written to, so that writing to a
+    /// band of a read-only dataset is a compile error instead of a GDAL
+    /// error at runtime.
+    pub struct TypedRasterBand<'a, T: Copy + GdalType, A: Access = ReadOnly> {
         rasterband: &'a RasterBand<'a>,
         pixel_type: PhantomData<&'a T>,
+        access: PhantomData<A>,
     }
 
     impl<'a, T: Copy + GdalType + GdalFrom<f64>> TypedRasterBand<'a, T> {
         pub fn from_rasterband(
             rasterband: &'a RasterBand,
-        ) -> Result<TypedRasterBand<'a, T>, TypeError> {
+        ) -> std::result::Result<TypedRasterBand<'a, T>, TypeError> {
             let pixel_type = PhantomData::<&'a T>;
 
             let bt = rasterband.band_type();
@@ -84,11 +218,57 @@ pub mod typed_rasterband {
                 Ok(TypedRasterBand {
                     rasterband,
                     pixel_type,
+                    access: PhantomData,
                 })
             } else {
                 Err(TypeError {})
             }
         }
+    }
+
+    impl<'a, T: Copy + GdalType + GdalFrom<f64>> TypedRasterBand<'a, T, ReadWrite> {
+        /// Wraps a band that can be written to, failing if its dataset was
+        /// opened read-only.
+        pub fn from_writable_rasterband(
+            rasterband: &'a RasterBand,
+        ) -> Result<TypedRasterBand<'a, T, ReadWrite>> {
+            let band = TypedRasterBand::<T>::from_rasterband(rasterband)?;
+            if unsafe { GDALGetRasterAccess(rasterband._c_ptr()) } != GDALAccess::GA_Update {
+                return Err(Error::ReadOnly);
+            }
+            Ok(TypedRasterBand {
+                rasterband: band.rasterband,
+                pixel_type: PhantomData,
+                access: PhantomData,
+            })
+        }
+
+        /// A read-only view of the same band.
+        pub fn as_read_only(&self) -> TypedRasterBand<'a, T> {
+            TypedRasterBand {
+                rasterband: self.rasterband,
+                pixel_type: PhantomData,
+                access: PhantomData,
+            }
+        }
+
+        pub fn write(
+            &self,
+            window: (isize, isize),
+            window_size: (usize, usize),
+            buffer: &Buffer<T>,
+        ) -> GdalResult<()> {
+            let span_window = Window::new(window, window_size);
+            instrument("write", span_window, buffer_bytes::<T>(window_size), || {
+                self.rasterband.write(window, window_size, buffer)
+            })
+        }
+    }
+
+    impl<'a, T: Copy + GdalType + GdalFrom<f64>, A: Access> TypedRasterBand<'a, T, A> {
+        pub fn rasterband(&self) -> &'a RasterBand<'a> {
+            self.rasterband
+        }
 
         pub fn owning_dataset(&self) -> &'a Dataset {
             self.rasterband.owning_dataset()
@@ -100,26 +280,37 @@ pub mod typed_rasterband {
             window_size: (usize, usize),
             size: (usize, usize),
         ) -> GdalResult<Buffer<T>> {
-            self.rasterband.read_as(window, window_size, size)
+            let span_window = Window::new(window, window_size);
+            instrument("read", span_window, buffer_bytes::<T>(size), || {
+                self.rasterband.read_as(window, window_size, size)
+            })
         }
 
+        /// Reads the whole band through RasterIO and GDAL's block cache.
+        /// See `read_band_fast` for a path that skips both.
         pub fn read_band(&self) -> GdalResult<Buffer<T>> {
-            self.rasterband.read_band_as()
-        }
-
-        pub fn write(
-            &self,
-            window: (isize, isize),
-            window_size: (usize, usize),
-            buffer: &Buffer<T>,
-        ) -> GdalResult<()> {
-            self.rasterband.write(window, window_size, buffer)
+            let full = Window::new((0, 0), self.size());
+            instrument("read", full, buffer_bytes::<T>(full.size), || {
+                self.rasterband.read_band_as()
+            })
         }
 
         pub fn band_type(&self) -> GDALDataType::Type {
             self.rasterband.band_type()
         }
 
+        pub fn band_index(&self) -> isize {
+            unsafe { GDALGetBandNumber(self.rasterband._c_ptr()) as isize }
+        }
+
+        pub fn size(&self) -> (usize, usize) {
+            self.rasterband.size()
+        }
+
+        pub fn block_size(&self) -> (usize, usize) {
+            self.rasterband.block_size()
+        }
+
         pub fn no_data_value(&self) -> Option<T> {
             let no_data_f64 = self.rasterband.no_data_value();
             no_data_f64.map({ |f| T::from(f) })
@@ -0,0 +1,132 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use gdal::raster::types::GdalType;
+
+/// Which class `majority_filter` picks when several are equally common.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TieBreak {
+    /// Keeps the pixel's own class if it's among the most common, and
+    /// otherwise takes the smallest.
+    #[default]
+    KeepCentre,
+    Smallest,
+    Largest,
+}
+
+/// The most common class among `counts`, according to `ties`.
+fn majority<T: Copy + Ord>(counts: &[(T, usize)], centre: T, ties: TieBreak) -> T {
+    let most = counts.iter().map(|&(_, n)| n).max().unwrap_or(0);
+    let tied = counts.iter().filter(|&&(_, n)| n == most).map(|&(c, _)| c);
+    match ties {
+        TieBreak::KeepCentre if counts.iter().any(|&(c, n)| c == centre && n == most) => centre,
+        TieBreak::KeepCentre | TieBreak::Smallest => tied.min().unwrap_or(centre),
+        TieBreak::Largest => tied.max().unwrap_or(centre),
+    }
+}
+
+impl<T: Copy + Ord> TypedBuffer<T> {
+    /// Replaces each pixel with the most common class in the
+    /// `window_size` by `window_size` square around it, repeating
+    /// `iterations` times.
+    ///
+    /// Nodata pixels are left alone and aren't counted, and neighbourhoods
+    /// are cut off at the edges of the buffer.
+    pub fn majority_filter(
+        &self,
+        window_size: usize,
+        iterations: usize,
+        ties: TieBreak,
+        nodata: Option<T>,
+    ) -> TypedBuffer<T> {
+        assert!(window_size % 2 == 1, "window size must be odd");
+        let radius = (window_size / 2) as isize;
+        let (width, height) = self.size;
+        let mut current = self.clone();
+        let mut counts: Vec<(T, usize)> = Vec::new();
+
+        for _ in 0..iterations {
+            let mut next = current.clone();
+            for y in 0..height {
+                for x in 0..width {
+                    let centre = current.get(x, y);
+                    if Some(centre) == nodata {
+                        continue;
+                    }
+                    counts.clear();
+                    for dy in -radius..=radius {
+                        for dx in -radius..=radius {
+                            let (nx, ny) = (x as isize + dx, y as isize + dy);
+                            if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                                continue;
+                            }
+                            let class = current.get(nx as usize, ny as usize);
+                            if Some(class) == nodata {
+                                continue;
+                            }
+                            match counts.iter_mut().find(|(c, _)| *c == class) {
+                                Some((_, n)) => *n += 1,
+                                None => counts.push((class, 1)),
+                            }
+                        }
+                    }
+                    next.data[y * width + x] = majority(&counts, centre, ties);
+                }
+            }
+            current = next;
+        }
+        current
+    }
+}
+
+impl<'a, T, A: Access> TypedRasterBand<'a, T, A>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Ord,
+{
+    /// Reads the band and smooths it with `TypedBuffer::majority_filter`,
+    /// ignoring the band's nodata pixels.
+    pub fn majority_filter(
+        &self,
+        window_size: usize,
+        iterations: usize,
+        ties: TieBreak,
+    ) -> Result<TypedBuffer<T>> {
+        let buffer: TypedBuffer<T> = self.read_band()?.into();
+        Ok(buffer.majority_filter(window_size, iterations, ties, self.no_data_value()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::majority::TieBreak;
+
+    #[test]
+    fn remove_speckle() {
+        let classes = TypedBuffer::new(
+            (4, 3),
+            vec![
+                1u8, 1, 2, 2, //
+                1, 3, 2, 2, //
+                1, 1, 2, 0,
+            ],
+        );
+        let smoothed = classes.majority_filter(3, 1, TieBreak::KeepCentre, Some(0));
+
+        assert_eq!(smoothed.get(1, 1), 1);
+        assert_eq!(smoothed.get(3, 2), 0);
+        assert_eq!(smoothed.get(3, 0), 2);
+    }
+
+    #[test]
+    fn break_ties() {
+        let classes = TypedBuffer::new((2, 1), vec![4u16, 7]);
+
+        let keep = classes.majority_filter(3, 1, TieBreak::KeepCentre, None);
+        assert_eq!(keep.data, vec![4, 7]);
+        let smallest = classes.majority_filter(3, 1, TieBreak::Smallest, None);
+        assert_eq!(smallest.data, vec![4, 4]);
+        let largest = classes.majority_filter(3, 1, TieBreak::Largest, None);
+        assert_eq!(largest.data, vec![7, 7]);
+    }
+}
@@ -0,0 +1,36 @@
+use crate::buffer::TypedBuffer;
+use nalgebra::{DMatrix, Scalar};
+
+impl<T: Copy + Scalar> TypedBuffer<T> {
+    /// Copies the buffer into a matrix with one row per raster row.
+    pub fn to_dmatrix(&self) -> DMatrix<T> {
+        let (width, height) = self.size;
+        DMatrix::from_row_slice(height, width, &self.data)
+    }
+
+    pub fn from_dmatrix(matrix: &DMatrix<T>) -> TypedBuffer<T> {
+        let (rows, cols) = matrix.shape();
+        let mut data = Vec::with_capacity(rows * cols);
+        for r in 0..rows {
+            for c in 0..cols {
+                data.push(matrix[(r, c)]);
+            }
+        }
+        TypedBuffer::new((cols, rows), data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+
+    #[test]
+    fn buffer_dmatrix_round_trip() {
+        let buffer = TypedBuffer::new((3, 2), vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let matrix = buffer.to_dmatrix();
+
+        assert_eq!(matrix.shape(), (2, 3));
+        assert_eq!(matrix[(1, 0)], 4.0);
+        assert_eq!(TypedBuffer::from_dmatrix(&matrix), buffer);
+    }
+}
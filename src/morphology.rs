@@ -0,0 +1,157 @@
+use crate::bitmask::{BitMaskBuffer, PixelMask};
+use crate::buffer::TypedBuffer;
+
+/// The neighbourhood a morphological operation looks at around each pixel.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StructuringElement {
+    /// A square reaching `radius` pixels from the centre on every side.
+    Square(usize),
+    /// The pixels within `radius` pixels of the centre.
+    Disk(usize),
+    /// The set pixels of a mask with odd width and height, centred on the
+    /// middle pixel.
+    Custom(TypedBuffer<bool>),
+}
+
+impl StructuringElement {
+    /// The offsets from the centre that the element covers.
+    fn offsets(&self) -> Vec<(isize, isize)> {
+        match self {
+            StructuringElement::Square(radius) | StructuringElement::Disk(radius) => {
+                let r = *radius as isize;
+                let disk = matches!(self, StructuringElement::Disk(_));
+                let mut offsets = Vec::new();
+                for dy in -r..=r {
+                    for dx in -r..=r {
+                        if !disk || dx * dx + dy * dy <= r * r {
+                            offsets.push((dx, dy));
+                        }
+                    }
+                }
+                offsets
+            }
+            StructuringElement::Custom(element) => {
+                let (width, height) = element.size;
+                assert!(
+                    width % 2 == 1 && height % 2 == 1,
+                    "structuring element must have odd dimensions"
+                );
+                let (cx, cy) = ((width / 2) as isize, (height / 2) as isize);
+                element
+                    .pixels()
+                    .filter(|&(_, _, set)| set)
+                    .map(|(x, y, _)| (x as isize - cx, y as isize - cy))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Sets each pixel for which `test` holds, given how many of the
+/// element's neighbours of the pixel that lie inside the mask are set, and
+/// how many lie inside the mask at all.
+fn apply<M, F>(mask: &M, element: &StructuringElement, test: F) -> BitMaskBuffer
+where
+    M: PixelMask,
+    F: Fn(usize, usize) -> bool,
+{
+    let (width, height) = mask.size();
+    let offsets = element.offsets();
+    let mut out = BitMaskBuffer::filled((width, height), false);
+    for y in 0..height {
+        for x in 0..width {
+            let (mut set, mut inside) = (0, 0);
+            for &(dx, dy) in &offsets {
+                let (nx, ny) = (x as isize + dx, y as isize + dy);
+                if nx >= 0 && ny >= 0 && nx < width as isize && ny < height as isize {
+                    inside += 1;
+                    if mask.is_set(nx as usize, ny as usize) {
+                        set += 1;
+                    }
+                }
+            }
+            if test(set, inside) {
+                out.set(x, y, true);
+            }
+        }
+    }
+    out
+}
+
+/// Grows the set region of `mask` by `element`, as when buffering a cloud
+/// mask. Pixels past the edge count as unset.
+pub fn dilate<M: PixelMask>(mask: &M, element: &StructuringElement) -> BitMaskBuffer {
+    // Reflect the element, so that dilation and erosion are duals for
+    // asymmetric elements too.
+    let reflected = match element {
+        StructuringElement::Custom(e) => {
+            let mut data = e.data.clone();
+            data.reverse();
+            StructuringElement::Custom(TypedBuffer::new(e.size, data))
+        }
+        element => element.clone(),
+    };
+    apply(mask, &reflected, |set, _| set > 0)
+}
+
+/// Shrinks the set region of `mask` by `element`. Pixels past the edge are
+/// ignored, so regions touching the edge don't shrink away from it.
+pub fn erode<M: PixelMask>(mask: &M, element: &StructuringElement) -> BitMaskBuffer {
+    apply(mask, element, |set, inside| set == inside)
+}
+
+/// Erodes and then dilates `mask`, removing specks smaller than `element`.
+pub fn open<M: PixelMask>(mask: &M, element: &StructuringElement) -> BitMaskBuffer {
+    dilate(&erode(mask, element), element)
+}
+
+/// Dilates and then erodes `mask`, filling holes smaller than `element`.
+pub fn close<M: PixelMask>(mask: &M, element: &StructuringElement) -> BitMaskBuffer {
+    erode(&dilate(mask, element), element)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bitmask::PixelMask;
+    use crate::buffer::TypedBuffer;
+    use crate::morphology::{close, dilate, erode, open, StructuringElement};
+
+    fn one_pixel(size: (usize, usize), at: (usize, usize)) -> TypedBuffer<bool> {
+        let mut mask = TypedBuffer::filled(size, false);
+        mask.data[at.1 * size.0 + at.0] = true;
+        mask
+    }
+
+    #[test]
+    fn dilate_and_erode() {
+        let mask = one_pixel((5, 5), (2, 2));
+
+        let square = dilate(&mask, &StructuringElement::Square(1));
+        assert_eq!(square.count_set(), 9);
+        let disk = dilate(&mask, &StructuringElement::Disk(1));
+        assert_eq!(disk.count_set(), 5);
+        assert!(!disk.is_set(1, 1));
+        assert_eq!(
+            erode(&square, &StructuringElement::Square(1)).count_set(),
+            1
+        );
+    }
+
+    #[test]
+    fn open_and_close() {
+        // A speck beside a solid block with a hole in it.
+        let mut mask = TypedBuffer::filled((8, 5), false);
+        for y in 0..5 {
+            for x in 3..8 {
+                mask.data[y * 8 + x] = (x, y) != (5, 2);
+            }
+        }
+        mask.data[2 * 8] = true;
+
+        let opened = open(&mask, &StructuringElement::Square(1));
+        assert!(!opened.is_set(0, 2));
+        assert!(opened.is_set(6, 1));
+        let closed = close(&mask, &StructuringElement::Square(1));
+        assert!(closed.is_set(5, 2));
+    }
+}
@@ -0,0 +1,59 @@
+use crate::buffer::TypedBuffer;
+use crate::statistics::Statistics;
+
+impl<T: Copy + Into<f64>> TypedBuffer<T> {
+    /// Standardizes pixels to `(v - mean) / std_dev`.
+    pub fn normalize(&self, mean: f64, std_dev: f64) -> TypedBuffer<f32> {
+        self.map(|v| ((v.into() - mean) / std_dev) as f32)
+    }
+
+    /// Standardizes pixels using previously computed band statistics, so that
+    /// every chip of a band is scaled consistently.
+    pub fn normalize_with(&self, stats: &Statistics) -> TypedBuffer<f32> {
+        self.normalize(stats.mean, stats.std_dev)
+    }
+
+    /// Rescales pixels to the range [0, 1] using the buffer's own extremes.
+    pub fn min_max_scale(&self) -> TypedBuffer<f32> {
+        let (min, max) = self
+            .data
+            .iter()
+            .map(|&v| v.into())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                (lo.min(v), hi.max(v))
+            });
+        self.scale_range(min, max)
+    }
+
+    /// Rescales pixels to the range [0, 1] using the extremes from band
+    /// statistics.
+    pub fn min_max_scale_with(&self, stats: &Statistics) -> TypedBuffer<f32> {
+        self.scale_range(stats.min, stats.max)
+    }
+
+    fn scale_range(&self, min: f64, max: f64) -> TypedBuffer<f32> {
+        let range = max - min;
+        if range > 0.0 {
+            self.map(|v| ((v.into() - min) / range) as f32)
+        } else {
+            self.map(|_| 0.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+
+    #[test]
+    fn normalize_buffer() {
+        let buffer = TypedBuffer::new((2, 2), vec![2u16, 4, 6, 8]);
+
+        assert_eq!(buffer.normalize(5.0, 2.0).data, vec![-1.5, -0.5, 0.5, 1.5]);
+        assert_eq!(buffer.min_max_scale().data[1], 1.0 / 3.0);
+        assert_eq!(
+            TypedBuffer::filled((2, 1), 7u8).min_max_scale().data,
+            vec![0.0, 0.0]
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::pixel::PixelBytes;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// Pixel types with a NumPy dtype descriptor.
+pub trait NpyPixel: PixelBytes {
+    const DESCR: &'static str;
+}
+
+impl NpyPixel for u8 {
+    const DESCR: &'static str = "|u1";
+}
+impl NpyPixel for u16 {
+    const DESCR: &'static str = "<u2";
+}
+impl NpyPixel for u32 {
+    const DESCR: &'static str = "<u4";
+}
+impl NpyPixel for i16 {
+    const DESCR: &'static str = "<i2";
+}
+impl NpyPixel for i32 {
+    const DESCR: &'static str = "<i4";
+}
+impl NpyPixel for f32 {
+    const DESCR: &'static str = "<f4";
+}
+impl NpyPixel for f64 {
+    const DESCR: &'static str = "<f8";
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+impl<T: NpyPixel> TypedBuffer<T> {
+    /// Writes the buffer in NumPy `.npy` format as a C-ordered array of shape
+    /// `(height, width)`.
+    pub fn write_npy<W: Write>(&self, mut writer: W) -> Result<()> {
+        let (width, height) = self.size;
+        let mut header = format!(
+            "{{'descr': '{}', 'fortran_order': False, 'shape': ({}, {}), }}",
+            T::DESCR,
+            height,
+            width
+        );
+        // Magic, version, and header length take 10 bytes; pad the header with
+        // spaces and a newline so that the data is 64-byte aligned.
+        let padding = 64 - (10 + header.len() + 1) % 64;
+        header.push_str(&" ".repeat(padding % 64));
+        header.push('\n');
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[1, 0])?;
+        writer.write_all(&(header.len() as u16).to_le_bytes())?;
+        writer.write_all(header.as_bytes())?;
+
+        let mut bytes = Vec::with_capacity(self.data.len() * T::SIZE);
+        for &v in &self.data {
+            v.write_le(&mut bytes);
+        }
+        writer.write_all(&bytes)?;
+        Ok(())
+    }
+
+    pub fn save_npy<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_npy(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads a two-dimensional, C-ordered `.npy` array whose dtype matches
+    /// `T`.
+    pub fn read_npy<R: Read>(mut reader: R) -> Result<TypedBuffer<T>> {
+        let mut preamble = [0u8; 8];
+        reader.read_exact(&mut preamble)?;
+        if &preamble[..6] != MAGIC {
+            return Err(invalid_data("not an npy file").into());
+        }
+
+        let header_len = match preamble[6] {
+            1 => {
+                let mut len = [0u8; 2];
+                reader.read_exact(&mut len)?;
+                u16::from_le_bytes(len) as usize
+            }
+            2 | 3 => {
+                let mut len = [0u8; 4];
+                reader.read_exact(&mut len)?;
+                u32::from_le_bytes(len) as usize
+            }
+            _ => return Err(invalid_data("unsupported npy version").into()),
+        };
+        let mut header = vec![0u8; header_len];
+        reader.read_exact(&mut header)?;
+        let header = String::from_utf8_lossy(&header);
+
+        if !header.contains(&format!("'descr': '{}'", T::DESCR)) {
+            return Err(invalid_data("npy dtype doesn't match buffer type").into());
+        }
+        if header.contains("'fortran_order': True") {
+            return Err(invalid_data("Fortran-ordered npy arrays aren't supported").into());
+        }
+        let shape = parse_shape(&header).ok_or_else(|| invalid_data("invalid npy shape"))?;
+        let (height, width) = match shape.as_slice() {
+            &[height, width] => (height, width),
+            _ => return Err(invalid_data("npy array isn't two-dimensional").into()),
+        };
+
+        let mut bytes = vec![0u8; width * height * T::SIZE];
+        reader.read_exact(&mut bytes)?;
+        let data = bytes.chunks(T::SIZE).map(T::read_le).collect();
+        Ok(TypedBuffer::new((width, height), data))
+    }
+
+    pub fn load_npy<P: AsRef<Path>>(path: P) -> Result<TypedBuffer<T>> {
+        TypedBuffer::read_npy(BufReader::new(File::open(path)?))
+    }
+}
+
+fn parse_shape(header: &str) -> Option<Vec<usize>> {
+    let start = header.find("'shape':")? + "'shape':".len();
+    let open = start + header[start..].find('(')? + 1;
+    let close = open + header[open..].find(')')?;
+    header[open..close]
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+
+    #[test]
+    fn npy_round_trip() {
+        let buffer = TypedBuffer::new((3, 2), vec![1.5f32, -2.0, 3.25, 0.0, 8.0, 9.5]);
+        let mut bytes = Vec::new();
+        buffer.write_npy(&mut bytes).unwrap();
+
+        assert_eq!(&bytes[..6], b"\x93NUMPY");
+        assert_eq!((bytes.len() - 6 * 4) % 64, 0);
+        assert_eq!(TypedBuffer::read_npy(bytes.as_slice()).unwrap(), buffer);
+    }
+
+    #[test]
+    fn npy_type_mismatch() {
+        let buffer = TypedBuffer::new((2, 1), vec![1u16, 2]);
+        let mut bytes = Vec::new();
+        buffer.write_npy(&mut bytes).unwrap();
+
+        assert!(TypedBuffer::<u8>::read_npy(bytes.as_slice()).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::{Error, Result};
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use gdal::raster::types::GdalType;
+use std::convert::TryFrom;
+
+/// What integer band math does when a result doesn't fit the pixel type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Wrap around, as `u16` arithmetic does in release builds.
+    Wrap,
+    /// Clamp to the type's minimum or maximum.
+    #[default]
+    Saturate,
+    /// Return every result as an `i64`. Only products of two 32-bit pixels
+    /// can exceed it, and those fail with `Error::Overflow`.
+    Promote,
+    /// Fail with `Error::Overflow` at the first pixel that overflows.
+    Error,
+}
+
+/// An arithmetic operation on two integer pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntOp {
+    Add,
+    Sub,
+    Mul,
+}
+
+impl IntOp {
+    /// The exact result. Products of `u32` pixels can exceed an `i64`, so
+    /// this works in `i128`.
+    fn apply(self, a: i128, b: i128) -> i128 {
+        match self {
+            IntOp::Add => a + b,
+            IntOp::Sub => a - b,
+            IntOp::Mul => a * b,
+        }
+    }
+}
+
+/// Integer pixel types, whose values all fit in an `i64`.
+pub trait IntegerPixel: Copy + PartialEq + Into<i64> {
+    const MIN: i64;
+    const MAX: i64;
+
+    /// The low bits of `value`, as an `as` cast keeps them.
+    fn wrapping_from(value: i128) -> Self;
+}
+
+macro_rules! integer_pixel {
+    ($($t:ty),*) => {
+        $(impl IntegerPixel for $t {
+            const MIN: i64 = <$t>::MIN as i64;
+            const MAX: i64 = <$t>::MAX as i64;
+
+            fn wrapping_from(value: i128) -> $t {
+                value as $t
+            }
+        })*
+    };
+}
+
+integer_pixel!(u8, u16, u32, i16, i32);
+
+/// The result of `checked_zip`: pixels of the input type, or `i64`s under
+/// `OverflowPolicy::Promote`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntOutput<T> {
+    Native(TypedBuffer<T>),
+    Promoted(TypedBuffer<i64>),
+}
+
+impl<T: IntegerPixel> IntOutput<T> {
+    pub fn into_i64(self) -> TypedBuffer<i64> {
+        match self {
+            IntOutput::Native(buffer) => buffer.map(Into::into),
+            IntOutput::Promoted(buffer) => buffer,
+        }
+    }
+
+    /// The results as `f64`s, exactly for any result up to 2^53.
+    pub fn into_f64(self) -> TypedBuffer<f64> {
+        self.into_i64().map(|v| v as f64)
+    }
+}
+
+/// Combines `a` and `b` pixel by pixel, setting pixels for which `skip`
+/// holds to `fill`.
+fn zip_checked<T: IntegerPixel>(
+    a: &TypedBuffer<T>,
+    b: &TypedBuffer<T>,
+    op: IntOp,
+    policy: OverflowPolicy,
+    skip: impl Fn(T, T) -> bool,
+    fill: T,
+) -> Result<IntOutput<T>> {
+    if a.size != b.size {
+        return Err(Error::Alignment(format!(
+            "buffer sizes {:?} and {:?} differ",
+            a.size, b.size
+        )));
+    }
+    let exact = a.data.iter().zip(&b.data).map(|(&x, &y)| {
+        if skip(x, y) {
+            None
+        } else {
+            Some(op.apply(x.into().into(), y.into().into()))
+        }
+    });
+    let overflow = |i: usize| Error::Overflow {
+        col: i % a.size.0,
+        row: i / a.size.0,
+    };
+    if policy == OverflowPolicy::Promote {
+        let mut data = Vec::with_capacity(a.data.len());
+        for (i, v) in exact.enumerate() {
+            data.push(match v {
+                None => fill.into(),
+                Some(v) => i64::try_from(v).map_err(|_| overflow(i))?,
+            });
+        }
+        return Ok(IntOutput::Promoted(TypedBuffer::new(a.size, data)));
+    }
+
+    let mut data = Vec::with_capacity(a.data.len());
+    for (i, v) in exact.enumerate() {
+        let v = match v {
+            None => fill,
+            Some(v) if (T::MIN as i128..=T::MAX as i128).contains(&v) => T::wrapping_from(v),
+            Some(v) => match policy {
+                OverflowPolicy::Wrap => T::wrapping_from(v),
+                OverflowPolicy::Error => return Err(overflow(i)),
+                _ => T::wrapping_from(v.clamp(T::MIN as i128, T::MAX as i128)),
+            },
+        };
+        data.push(v);
+    }
+    Ok(IntOutput::Native(TypedBuffer::new(a.size, data)))
+}
+
+impl<T: IntegerPixel> TypedBuffer<T> {
+    /// Computes `self op other` for every pixel, handling results that
+    /// don't fit `T` according to `policy`. The buffers must be the same
+    /// size.
+    pub fn checked_zip(
+        &self,
+        other: &TypedBuffer<T>,
+        op: IntOp,
+        policy: OverflowPolicy,
+    ) -> Result<IntOutput<T>> {
+        zip_checked(self, other, op, policy, |_, _| false, T::wrapping_from(0))
+    }
+}
+
+impl<'a, T, A: Access> TypedRasterBand<'a, T, A>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Into<f64> + IntegerPixel,
+{
+    /// Computes `self op other` for every pixel, after checking that the
+    /// bands share a grid, handling results that don't fit `T` according to
+    /// `policy`.
+    ///
+    /// Pixels that are nodata in either band are set to this band's nodata
+    /// value, or to `other`'s if this band has none.
+    pub fn checked_zip<B: Access>(
+        &self,
+        other: &TypedRasterBand<T, B>,
+        op: IntOp,
+        policy: OverflowPolicy,
+    ) -> Result<IntOutput<T>> {
+        self.check_same_grid(other)?;
+        let a: TypedBuffer<T> = self.read_band()?.into();
+        let b: TypedBuffer<T> = other.read_band()?.into();
+        let (nodata_a, nodata_b) = (self.no_data_value(), other.no_data_value());
+        let fill = match nodata_a.or(nodata_b) {
+            Some(fill) => fill,
+            None => return a.checked_zip(&b, op, policy),
+        };
+        zip_checked(
+            &a,
+            &b,
+            op,
+            policy,
+            |x, y| Some(x) == nodata_a || Some(y) == nodata_b,
+            fill,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::errors::Error;
+    use crate::overflow::{IntOp, IntOutput, OverflowPolicy};
+
+    #[test]
+    fn overflow_policies() {
+        let a = TypedBuffer::new((2, 1), vec![65000u16, 1]);
+        let b = TypedBuffer::new((2, 1), vec![1000u16, 2]);
+        let zip = |op, policy| a.checked_zip(&b, op, policy);
+
+        assert_eq!(
+            zip(IntOp::Add, OverflowPolicy::Wrap).unwrap(),
+            IntOutput::Native(TypedBuffer::new((2, 1), vec![464, 3]))
+        );
+        assert_eq!(
+            zip(IntOp::Add, OverflowPolicy::Saturate).unwrap(),
+            IntOutput::Native(TypedBuffer::new((2, 1), vec![65535, 3]))
+        );
+        assert_eq!(
+            zip(IntOp::Sub, OverflowPolicy::Saturate).unwrap(),
+            IntOutput::Native(TypedBuffer::new((2, 1), vec![64000, 0]))
+        );
+        assert_eq!(
+            zip(IntOp::Mul, OverflowPolicy::Promote)
+                .unwrap()
+                .into_f64()
+                .data,
+            vec![65_000_000.0, 2.0]
+        );
+        match zip(IntOp::Sub, OverflowPolicy::Error) {
+            Err(Error::Overflow { col: 1, row: 0 }) => {}
+            other => panic!("expected an overflow, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn u32_products_overflow_i64() {
+        let a = TypedBuffer::new((2, 1), vec![3u32, u32::MAX]);
+        let b = TypedBuffer::new((2, 1), vec![1u32 << 31, u32::MAX]);
+        let mul = |policy| a.checked_zip(&b, IntOp::Mul, policy);
+
+        assert_eq!(
+            mul(OverflowPolicy::Wrap).unwrap(),
+            IntOutput::Native(TypedBuffer::new((2, 1), vec![1 << 31, 1]))
+        );
+        assert_eq!(
+            mul(OverflowPolicy::Saturate).unwrap(),
+            IntOutput::Native(TypedBuffer::new((2, 1), vec![u32::MAX, u32::MAX]))
+        );
+        // Both products overflow a u32, but only the second an i64.
+        for (policy, col) in [(OverflowPolicy::Error, 0), (OverflowPolicy::Promote, 1)] {
+            match mul(policy) {
+                Err(Error::Overflow { col: c, row: 0 }) if c == col => {}
+                other => panic!("expected an overflow, got {:?}", other),
+            }
+        }
+
+        let small = TypedBuffer::new((1, 1), vec![3u32]);
+        let large = TypedBuffer::new((1, 1), vec![1u32 << 31]);
+        assert_eq!(
+            small
+                .checked_zip(&large, IntOp::Mul, OverflowPolicy::Promote)
+                .unwrap()
+                .into_i64()
+                .data,
+            vec![3i64 << 31]
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use crate::buffer::TypedBuffer;
+
+/// The number of pixels to add on each side of a buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Margins {
+    pub left: usize,
+    pub top: usize,
+    pub right: usize,
+    pub bottom: usize,
+}
+
+impl Margins {
+    /// The same margin on every side.
+    pub fn uniform(margin: usize) -> Margins {
+        Margins {
+            left: margin,
+            top: margin,
+            right: margin,
+            bottom: margin,
+        }
+    }
+}
+
+/// What `TypedBuffer::pad` fills the margins with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PadMode<T> {
+    /// A single value.
+    Constant(T),
+    /// The nearest edge pixel.
+    Edge,
+    /// The pixels inside the edge, mirrored about it without repeating the
+    /// edge pixel itself.
+    Reflect,
+}
+
+/// Maps `i`, which may lie outside `0..n`, to an index inside it by
+/// mirroring about the ends.
+fn reflect_index(i: isize, n: usize) -> usize {
+    if n == 1 {
+        return 0;
+    }
+    let period = 2 * (n as isize - 1);
+    let i = i.rem_euclid(period);
+    if i < n as isize {
+        i as usize
+    } else {
+        (period - i) as usize
+    }
+}
+
+impl<T: Copy> TypedBuffer<T> {
+    /// Adds `margins` around the buffer, filled according to `mode`.
+    pub fn pad(&self, margins: Margins, mode: PadMode<T>) -> TypedBuffer<T> {
+        let (width, height) = self.size;
+        let size = (
+            width + margins.left + margins.right,
+            height + margins.top + margins.bottom,
+        );
+        if let PadMode::Constant(value) = mode {
+            let mut padded = TypedBuffer::filled(size, value);
+            for y in 0..height {
+                let start = (y + margins.top) * size.0 + margins.left;
+                padded.data[start..start + width].copy_from_slice(self.row(y));
+            }
+            return padded;
+        }
+
+        assert!(
+            width > 0 && height > 0,
+            "can't extend the edges of an empty buffer"
+        );
+        let source_index = |i: usize, margin: usize, n: usize| -> usize {
+            let i = i as isize - margin as isize;
+            match mode {
+                PadMode::Edge => i.max(0).min(n as isize - 1) as usize,
+                _ => reflect_index(i, n),
+            }
+        };
+        let mut data = Vec::with_capacity(size.0 * size.1);
+        for y in 0..size.1 {
+            let row = self.row(source_index(y, margins.top, height));
+            data.extend((0..size.0).map(|x| row[source_index(x, margins.left, width)]));
+        }
+        TypedBuffer::new(size, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::pad::{Margins, PadMode};
+
+    #[test]
+    fn pad_constant_and_edge() {
+        let buffer = TypedBuffer::new((2, 2), vec![1u8, 2, 3, 4]);
+        let margins = Margins {
+            left: 1,
+            top: 0,
+            right: 2,
+            bottom: 1,
+        };
+
+        let padded = buffer.pad(margins, PadMode::Constant(0));
+        assert_eq!(padded.size, (5, 3));
+        assert_eq!(padded.row(0), &[0, 1, 2, 0, 0]);
+        assert_eq!(padded.row(2), &[0, 0, 0, 0, 0]);
+
+        let padded = buffer.pad(margins, PadMode::Edge);
+        assert_eq!(padded.row(1), &[3, 3, 4, 4, 4]);
+        assert_eq!(padded.row(2), &[3, 3, 4, 4, 4]);
+    }
+
+    #[test]
+    fn pad_reflect() {
+        let buffer = TypedBuffer::new((3, 1), vec![1u8, 2, 3]);
+        let padded = buffer.pad(
+            Margins {
+                left: 4,
+                right: 2,
+                ..Margins::default()
+            },
+            PadMode::Reflect,
+        );
+        assert_eq!(padded.data, vec![1, 2, 3, 2, 1, 2, 3, 2, 1]);
+    }
+}
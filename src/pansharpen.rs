@@ -0,0 +1,181 @@
+use crate::buffer::TypedBuffer;
+use crate::create::DatasetBuilder;
+use crate::dataset::TypedDataset;
+use crate::errors::{Error, Result};
+use crate::resample::Interpolation;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+use std::io;
+
+/// Sharpens multispectral bands, already resampled to the size of `pan`,
+/// with the weighted Brovey transform that GDAL's pansharpening uses: each
+/// band is scaled by the ratio of the panchromatic value to the weighted
+/// sum of the multispectral values.
+///
+/// Pixels that are `nodata` in any input, or whose weighted sum is zero,
+/// are `nodata` (or zero) in every output. Results are rounded for integer
+/// pixel types.
+///
+/// Fails unless there is at least one band, with one weight each, and
+/// every band is the size of `pan`.
+pub fn brovey<T>(
+    pan: &TypedBuffer<T>,
+    ms: &[TypedBuffer<T>],
+    weights: &[f64],
+    nodata: Option<T>,
+) -> Result<Vec<TypedBuffer<T>>>
+where
+    T: Copy + GdalFrom<f64> + Into<f64> + PartialEq,
+{
+    check_weights(ms.len(), weights)?;
+    if let Some(band) = ms.iter().find(|band| band.size != pan.size) {
+        return Err(Error::Bounds(format!(
+            "multispectral band of size {:?} doesn't match the panchromatic band of size {:?}",
+            band.size, pan.size
+        )));
+    }
+    let integral = T::from(0.5).into() == 0.0;
+    let fill = nodata.unwrap_or_else(|| T::from(0.0));
+    let mut outputs: Vec<Vec<T>> = vec![Vec::with_capacity(pan.data.len()); ms.len()];
+
+    for (i, &p) in pan.data.iter().enumerate() {
+        let values: Vec<T> = ms.iter().map(|band| band.data[i]).collect();
+        let pseudo_pan: f64 = values.iter().zip(weights).map(|(&v, w)| v.into() * w).sum();
+        let invalid = Some(p) == nodata || values.iter().any(|&v| Some(v) == nodata);
+        for (output, &v) in outputs.iter_mut().zip(&values) {
+            output.push(if invalid || pseudo_pan == 0.0 {
+                fill
+            } else {
+                let sharpened = v.into() * p.into() / pseudo_pan;
+                T::from(if integral {
+                    sharpened.round()
+                } else {
+                    sharpened
+                })
+            });
+        }
+    }
+    Ok(outputs
+        .into_iter()
+        .map(|data| TypedBuffer::new(pan.size, data))
+        .collect())
+}
+
+/// Checks that there is at least one multispectral band and one weight for
+/// each of the `bands`.
+fn check_weights(bands: usize, weights: &[f64]) -> Result<()> {
+    if bands == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "no multispectral bands").into());
+    }
+    if weights.len() != bands {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} weights for {} multispectral bands",
+                weights.len(),
+                bands
+            ),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Whether two geotransforms of rasters of the given sizes cover the same
+/// extent, to within half a pixel of `a`.
+fn same_extent(a: &[f64; 6], a_size: (usize, usize), b: &[f64; 6], b_size: (usize, usize)) -> bool {
+    let corner = |gt: &[f64; 6], size: (usize, usize)| {
+        (gt[0] + size.0 as f64 * gt[1], gt[3] + size.1 as f64 * gt[5])
+    };
+    let (ax, ay) = corner(a, a_size);
+    let (bx, by) = corner(b, b_size);
+    let (tx, ty) = (a[1].abs() / 2.0, a[5].abs() / 2.0);
+    (a[0] - b[0]).abs() <= tx
+        && (a[3] - b[3]).abs() <= ty
+        && (ax - bx).abs() <= tx
+        && (ay - by).abs() <= ty
+}
+
+/// Sharpens `ms_bands` to the resolution of `pan_band`, returning an
+/// in-memory dataset with one band per multispectral band on the
+/// panchromatic grid.
+///
+/// The multispectral bands are upsampled bilinearly and then combined with
+/// `brovey`. All bands must cover the same extent, and there must be at
+/// least one multispectral band, with one weight each. The panchromatic
+/// band's nodata value, if any, marks invalid pixels in every input and
+/// output.
+pub fn pansharpen<T, A: Access, B: Access>(
+    pan_band: &TypedRasterBand<T, A>,
+    ms_bands: &[TypedRasterBand<T, B>],
+    weights: &[f64],
+) -> Result<TypedDataset<T>>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Into<f64> + PartialEq,
+{
+    check_weights(ms_bands.len(), weights)?;
+    let pan_gt = pan_band.owning_dataset().geo_transform()?;
+    let size = pan_band.size();
+    let mut ms = Vec::with_capacity(ms_bands.len());
+    for band in ms_bands {
+        let gt = band.owning_dataset().geo_transform()?;
+        if !same_extent(&pan_gt, size, &gt, band.size()) {
+            return Err(Error::Alignment(
+                "multispectral and panchromatic bands cover different extents".to_string(),
+            ));
+        }
+        let buffer: TypedBuffer<T> = band.read_band()?.into();
+        ms.push(buffer.resample(size, Interpolation::Bilinear));
+    }
+    let pan: TypedBuffer<T> = pan_band.read_band()?.into();
+    let nodata = pan_band.no_data_value();
+    let sharpened = brovey(&pan, &ms, weights, nodata)?;
+
+    let mut builder = DatasetBuilder::new("MEM", "", size)
+        .bands(ms.len())
+        .geo_transform(pan_gt)
+        .projection(&pan_band.owning_dataset().projection());
+    if let Some(nodata) = nodata {
+        builder = builder.nodata(nodata);
+    }
+    let dataset = builder.create()?;
+    for (i, band) in sharpened.iter().enumerate() {
+        dataset.write(i as isize + 1, Window::full(size), band)?;
+    }
+    Ok(dataset)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::errors::Error;
+    use crate::pansharpen::{brovey, same_extent};
+
+    #[test]
+    fn brovey_ratios() {
+        let pan = TypedBuffer::new((3, 1), vec![100u16, 50, 0]);
+        let red = TypedBuffer::new((3, 1), vec![20u16, 20, 5]);
+        let nir = TypedBuffer::new((3, 1), vec![30u16, 30, 5]);
+        let out = brovey(&pan, &[red.clone(), nir], &[0.5, 0.5], Some(0)).unwrap();
+
+        assert_eq!(out[0].data, vec![80, 40, 0]);
+        assert_eq!(out[1].data, vec![120, 60, 0]);
+
+        assert!(brovey(&pan, std::slice::from_ref(&red), &[0.5, 0.5], None).is_err());
+        assert!(brovey(&pan, &[], &[], None).is_err());
+        let small = TypedBuffer::new((2, 1), vec![1u16, 2]);
+        assert!(matches!(
+            brovey(&pan, &[small], &[1.0], None),
+            Err(Error::Bounds(_))
+        ));
+    }
+
+    #[test]
+    fn compare_extents() {
+        let pan = [0.0, 15.0, 0.0, 0.0, 0.0, -15.0];
+        let ms = [0.0, 30.0, 0.0, 0.0, 0.0, -30.0];
+        assert!(same_extent(&pan, (200, 100), &ms, (100, 50)));
+        assert!(!same_extent(&pan, (200, 100), &ms, (100, 60)));
+    }
+}
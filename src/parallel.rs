@@ -0,0 +1,220 @@
+use crate::buffer::TypedBuffer;
+use crate::config::{ConfigGuard, DatasetOpenOptions};
+use crate::dataset::TypedDataset;
+use crate::errors::Result;
+use crate::shared::thread_dataset;
+use crate::typed_rasterband::{Access, GdalFrom, ReadWrite, TypedRasterBand};
+use crate::window::Window;
+use gdal::metadata::Metadata;
+use gdal::raster::types::GdalType;
+use rayon::prelude::*;
+use std::path::Path;
+
+/// Reads a block through the current thread's handle to the dataset, which
+/// is opened on first use and kept for later calls, as `SharedTypedBand`
+/// does.
+fn read_block<T: Copy + GdalType>(
+    path: &str,
+    options: &DatasetOpenOptions,
+    band_index: isize,
+    window: Window,
+) -> Result<TypedBuffer<T>> {
+    let _guard = ConfigGuard::set(&options.config)?;
+    let dataset = thread_dataset(Path::new(path), options)?;
+    Ok(dataset
+        .read_raster_as::<T>(band_index, window.offset, window.size, window.size)?
+        .into())
+}
+
+impl<'a, T, A: Access> TypedRasterBand<'a, T, A>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Send,
+{
+    /// Applies `f` to every natural block of the band on the rayon thread
+    /// pool, returning the results in block order.
+    ///
+    /// GDAL datasets can't be shared between threads, so each worker opens
+    /// its own handle to the file the band belongs to, once, and keeps it
+    /// until `shared::close_thread_datasets` is called on that thread. This requires the
+    /// owning dataset to have been opened from a path that can be re-opened.
+    /// The handles are opened with default options; use
+    /// `TypedDataset::par_blocks` or `par_blocks_with` to keep the options
+    /// the dataset was opened with.
+    pub fn par_blocks<R, F>(&self, f: F) -> Result<Vec<(Window, R)>>
+    where
+        R: Send,
+        F: Fn(Window, TypedBuffer<T>) -> R + Sync + Send,
+    {
+        self.par_blocks_with(&DatasetOpenOptions::default(), f)
+    }
+
+    /// Like `par_blocks`, but each worker opens its handle as described by
+    /// `options`, with its config options set around every read.
+    pub fn par_blocks_with<R, F>(
+        &self,
+        options: &DatasetOpenOptions,
+        f: F,
+    ) -> Result<Vec<(Window, R)>>
+    where
+        R: Send,
+        F: Fn(Window, TypedBuffer<T>) -> R + Sync + Send,
+    {
+        let path = self.owning_dataset().description()?;
+        let band_index = self.band_index();
+
+        self.block_windows()
+            .into_par_iter()
+            .map(|window| {
+                let buffer = read_block(&path, options, band_index, window)?;
+                Ok((window, f(window, buffer)))
+            })
+            .collect()
+    }
+
+    /// Applies `f` to every natural block of the band on the rayon thread
+    /// pool, writing each result to the same window of `output`.
+    ///
+    /// Blocks are processed in batches so that results are written as they
+    /// become available rather than held in memory; all writes happen on the
+    /// calling thread. As with `par_blocks`, workers open the file with
+    /// default options.
+    pub fn par_map_blocks<U, F>(&self, output: &TypedRasterBand<U, ReadWrite>, f: F) -> Result<()>
+    where
+        U: Copy + GdalType + GdalFrom<f64> + Send,
+        F: Fn(Window, TypedBuffer<T>) -> TypedBuffer<U> + Sync + Send,
+    {
+        self.par_map_blocks_with(&DatasetOpenOptions::default(), output, f)
+    }
+
+    /// Like `par_map_blocks`, but each worker opens its handle as described
+    /// by `options`.
+    pub fn par_map_blocks_with<U, F>(
+        &self,
+        options: &DatasetOpenOptions,
+        output: &TypedRasterBand<U, ReadWrite>,
+        f: F,
+    ) -> Result<()>
+    where
+        U: Copy + GdalType + GdalFrom<f64> + Send,
+        F: Fn(Window, TypedBuffer<T>) -> TypedBuffer<U> + Sync + Send,
+    {
+        let path = self.owning_dataset().description()?;
+        let band_index = self.band_index();
+        let batch_size = 4 * rayon::current_num_threads();
+
+        for batch in self.block_windows().chunks(batch_size) {
+            let results: Result<Vec<_>> = batch
+                .par_iter()
+                .map(|&window| {
+                    let buffer = read_block(&path, options, band_index, window)?;
+                    Ok((window, f(window, buffer)))
+                })
+                .collect();
+
+            for (window, buffer) in results? {
+                output.write(window.offset, window.size, &buffer.into_buffer())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T> TypedDataset<T>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Send,
+{
+    /// Applies `f` to every natural block of band `index` on the rayon
+    /// thread pool, as `TypedRasterBand::par_blocks` does, with workers
+    /// opening the file with the dataset's `reopen_options()`.
+    pub fn par_blocks<R, F>(&self, index: isize, f: F) -> Result<Vec<(Window, R)>>
+    where
+        R: Send,
+        F: Fn(Window, TypedBuffer<T>) -> R + Sync + Send,
+    {
+        let options = self.reopen_options();
+        self.with_band(index, |band| band.par_blocks_with(&options, f))?
+    }
+
+    /// Applies `f` to every natural block of band `index`, writing each
+    /// result to `output`, as `TypedRasterBand::par_map_blocks` does, with
+    /// workers opening the file with the dataset's `reopen_options()`.
+    pub fn par_map_blocks<U, F>(
+        &self,
+        index: isize,
+        output: &TypedRasterBand<U, ReadWrite>,
+        f: F,
+    ) -> Result<()>
+    where
+        U: Copy + GdalType + GdalFrom<f64> + Send,
+        F: Fn(Window, TypedBuffer<T>) -> TypedBuffer<U> + Sync + Send,
+    {
+        let options = self.reopen_options();
+        self.with_band(index, |band| band.par_map_blocks_with(&options, output, f))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::DatasetOpenOptions;
+    use crate::dataset::TypedDataset;
+    use crate::shared::{close_thread_datasets, thread_dataset_count};
+    use crate::testing::gtiff_with_overviews;
+    use crate::typed_rasterband::TypedRasterBand;
+    use gdal::raster::dataset::Dataset;
+    use std::path::Path;
+
+    #[test]
+    fn par_blocks_sum() {
+        let path = Path::new("testdata/test_u8.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u8>::from_rasterband(&band).unwrap();
+
+        let sums = typed_band
+            .par_blocks(|_, buffer| buffer.data.iter().map(|&v| v as u64).sum::<u64>())
+            .unwrap();
+        assert_eq!(sums.len(), 14);
+        assert_eq!(sums.iter().map(|s| s.1).sum::<u64>(), 14130952);
+
+        let png_only = DatasetOpenOptions {
+            allowed_drivers: vec!["PNG".to_string()],
+            ..Default::default()
+        };
+        assert!(typed_band.par_blocks_with(&png_only, |_, _| ()).is_err());
+    }
+
+    #[test]
+    fn par_blocks_keep_overview_level() {
+        let file = gtiff_with_overviews("par_blocks_overview_level.tif");
+        let overview = DatasetOpenOptions {
+            open_options: vec![("OVERVIEW_LEVEL".to_string(), "0".to_string())],
+            ..Default::default()
+        };
+        let ds = TypedDataset::<u8>::open_with(Path::new(file.path()), &overview).unwrap();
+
+        let pixels: usize = ds
+            .par_blocks(1, |_, buffer| buffer.data.len())
+            .unwrap()
+            .iter()
+            .map(|block| block.1)
+            .sum();
+        assert_eq!(pixels, 167 * 167);
+    }
+
+    #[test]
+    fn par_blocks_reuse_thread_handles() {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let handles = pool.install(|| {
+            close_thread_datasets();
+            let ds = TypedDataset::<u8>::open(Path::new("testdata/test_u8.tif")).unwrap();
+            for _ in 0..3 {
+                ds.par_blocks(1, |_, _| ()).unwrap();
+            }
+            thread_dataset_count()
+        });
+        assert_eq!(handles, 1);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::arrow_export::ArrowPixel;
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::transform;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use arrow::array::{ArrayRef, Float64Array, PrimitiveArray};
+use arrow::datatypes::{ArrowPrimitiveType, Field, Float64Type, Schema};
+use arrow::record_batch::RecordBatch;
+use gdal::raster::types::GdalType;
+use parquet::arrow::ArrowWriter;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct PointExportOptions {
+    /// Indices of other bands in the owning dataset to write as additional
+    /// `band_<index>` columns. Each must have the same pixel type.
+    pub extra_bands: Vec<isize>,
+    /// Omit pixels where the source band is nodata.
+    pub skip_nodata: bool,
+    /// Number of raster rows to read and write per record batch.
+    pub rows_per_batch: usize,
+}
+
+impl Default for PointExportOptions {
+    fn default() -> PointExportOptions {
+        PointExportOptions {
+            extra_bands: Vec::new(),
+            skip_nodata: true,
+            rows_per_batch: 256,
+        }
+    }
+}
+
+impl<'a, T, A: Access> TypedRasterBand<'a, T, A>
+where
+    T: Copy + GdalType + GdalFrom<f64> + PartialEq + ArrowPixel,
+{
+    /// Writes one Parquet record per pixel, with the map coordinates of the
+    /// pixel centre in `x` and `y` and the pixel in `value`. Returns the
+    /// number of records written.
+    pub fn export_points_parquet<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: &PointExportOptions,
+    ) -> Result<usize> {
+        assert!(options.rows_per_batch > 0, "rows per batch must be nonzero");
+
+        let dataset = self.owning_dataset();
+        let gt = dataset.geo_transform()?;
+        let mut extra_bands = Vec::with_capacity(options.extra_bands.len());
+        for &index in &options.extra_bands {
+            extra_bands.push(dataset.rasterband(index)?);
+        }
+        let extra_bands = extra_bands
+            .iter()
+            .map(TypedRasterBand::<T>::from_rasterband)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let value_type = T::ArrowType::DATA_TYPE;
+        let mut fields = vec![
+            Field::new("x", Float64Type::DATA_TYPE, false),
+            Field::new("y", Float64Type::DATA_TYPE, false),
+            Field::new("value", value_type.clone(), false),
+        ];
+        for &index in &options.extra_bands {
+            fields.push(Field::new(
+                &format!("band_{}", index),
+                value_type.clone(),
+                false,
+            ));
+        }
+        let schema = Arc::new(Schema::new(fields));
+
+        let no_data = if options.skip_nodata {
+            self.no_data_value()
+        } else {
+            None
+        };
+        let (width, height) = dataset.size();
+        let mut writer = ArrowWriter::try_new(File::create(path)?, schema.clone(), None)?;
+        let mut count = 0;
+
+        for y0 in (0..height).step_by(options.rows_per_batch) {
+            let rows = options.rows_per_batch.min(height - y0);
+            let window = (0, y0 as isize);
+            let source: TypedBuffer<T> = self.read(window, (width, rows), (width, rows))?.into();
+            let extras = extra_bands
+                .iter()
+                .map(|b| b.read(window, (width, rows), (width, rows)))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+
+            let mut xs = Vec::new();
+            let mut ys = Vec::new();
+            let mut values = Vec::new();
+            let mut extra_values = vec![Vec::new(); extras.len()];
+            for (col, row, value) in source.pixels() {
+                if Some(value) == no_data {
+                    continue;
+                }
+                let (x, y) = transform::apply(&gt, col as f64 + 0.5, (y0 + row) as f64 + 0.5);
+                xs.push(x);
+                ys.push(y);
+                values.push(value);
+                for (v, buffer) in extra_values.iter_mut().zip(extras.iter()) {
+                    v.push(buffer.data[row * width + col]);
+                }
+            }
+
+            if values.is_empty() {
+                continue;
+            }
+            count += values.len();
+            let mut columns: Vec<ArrayRef> = vec![
+                Arc::new(Float64Array::from(xs)),
+                Arc::new(Float64Array::from(ys)),
+                Arc::new(PrimitiveArray::<T::ArrowType>::from(values)),
+            ];
+            for v in extra_values {
+                columns.push(Arc::new(PrimitiveArray::<T::ArrowType>::from(v)));
+            }
+            writer.write(&RecordBatch::try_new(schema.clone(), columns)?)?;
+        }
+
+        writer.close()?;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parquet_export::PointExportOptions;
+    use crate::typed_rasterband::TypedRasterBand;
+    use gdal::raster::dataset::Dataset;
+    use std::env;
+    use std::path::Path;
+
+    #[test]
+    fn export_parquet_points() {
+        let path = Path::new("testdata/test_u8.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u8>::from_rasterband(&band).unwrap();
+
+        let out = env::temp_dir().join("gdal_typed_rasterband_points.parquet");
+        let count = typed_band
+            .export_points_parquet(&out, &PointExportOptions::default())
+            .unwrap();
+
+        assert_eq!(count, 333 * 333);
+        assert!(out.metadata().unwrap().len() > 0);
+    }
+}
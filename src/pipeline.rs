@@ -0,0 +1,316 @@
+use crate::blocks::block_windows;
+use crate::buffer::TypedBuffer;
+use crate::errors::{Error, Result};
+use crate::pad::{Margins, PadMode};
+use crate::request::ProgressFn;
+use crate::sink::RasterSink;
+use crate::source::RasterSource;
+use crate::typed_rasterband::GdalFrom;
+use crate::window::Window;
+use std::io;
+
+/// The rows in each chunk when no chunk size is given.
+const DEFAULT_CHUNK_ROWS: usize = 256;
+
+/// One step of a `Pipeline`, turning a buffer of `T` into one of `U`.
+pub trait Stage<T, U>: Send + Sync {
+    /// The pixels of context the stage needs on each side of an output
+    /// pixel. The input is this much larger than the output on every side.
+    fn margin(&self) -> usize {
+        0
+    }
+
+    fn apply(&self, input: TypedBuffer<T>) -> TypedBuffer<U>;
+}
+
+/// A stage that applies a function to each pixel.
+pub struct Map<F>(F);
+
+pub fn map<T, U, F: Fn(T) -> U>(f: F) -> Map<F> {
+    Map(f)
+}
+
+impl<T: Copy, U: Copy, F: Fn(T) -> U + Send + Sync> Stage<T, U> for Map<F> {
+    fn apply(&self, input: TypedBuffer<T>) -> TypedBuffer<U> {
+        input.map(&self.0)
+    }
+}
+
+/// A stage that replaces each pixel with the mean of the square of side
+/// `2 * radius + 1` around it. Means are rounded for integer pixel types.
+pub struct FocalMean {
+    radius: usize,
+}
+
+pub fn focal_mean(radius: usize) -> FocalMean {
+    FocalMean { radius }
+}
+
+impl<T: Copy + Into<f64> + GdalFrom<f64>> Stage<T, T> for FocalMean {
+    fn margin(&self) -> usize {
+        self.radius
+    }
+
+    fn apply(&self, input: TypedBuffer<T>) -> TypedBuffer<T> {
+        let r = self.radius;
+        let size = (
+            input.size.0.saturating_sub(2 * r),
+            input.size.1.saturating_sub(2 * r),
+        );
+        let count = ((2 * r + 1) * (2 * r + 1)) as f64;
+        let integral = T::from(0.5).into() == 0.0;
+
+        let mut data = Vec::with_capacity(size.0 * size.1);
+        for y in 0..size.1 {
+            for x in 0..size.0 {
+                let sum: f64 = (y..=y + 2 * r)
+                    .flat_map(|row| &input.row(row)[x..=x + 2 * r])
+                    .map(|&v| v.into())
+                    .sum();
+                let mean = sum / count;
+                data.push(T::from(if integral { mean.round() } else { mean }));
+            }
+        }
+        TypedBuffer::new(size, data)
+    }
+}
+
+type Transform<'s, T, U> = dyn Fn(TypedBuffer<T>) -> TypedBuffer<U> + Send + Sync + 's;
+
+/// A chain of stages from a source, run a chunk at a time into a sink:
+///
+/// ```ignore
+/// band.pipe(map(|v: u16| v as f32 * 0.1))
+///     .pipe(focal_mean(2))
+///     .sink(&mut output)?;
+/// ```
+///
+/// Chunks are read with enough extra pixels around them for every stage's
+/// margin, extending edge pixels where the chunk meets the edge of the
+/// source, so the output doesn't depend on how it was chunked.
+pub struct Pipeline<'s, T, U> {
+    source: &'s dyn RasterSource<T>,
+    transform: Box<Transform<'s, T, U>>,
+    margin: usize,
+    chunk_size: Option<(usize, usize)>,
+    #[cfg(feature = "rayon")]
+    parallel: bool,
+    progress: Option<Box<ProgressFn<'s>>>,
+}
+
+impl<'s, T: Copy + Send + 's> Pipeline<'s, T, T> {
+    /// A pipeline that passes `source` through unchanged.
+    pub fn new(source: &'s dyn RasterSource<T>) -> Pipeline<'s, T, T> {
+        Pipeline {
+            source,
+            transform: Box::new(|buffer| buffer),
+            margin: 0,
+            chunk_size: None,
+            #[cfg(feature = "rayon")]
+            parallel: false,
+            progress: None,
+        }
+    }
+}
+
+impl<'s, T: Copy + Send + 's, U: Copy + Send + 's> Pipeline<'s, T, U> {
+    /// Adds `stage` to the end of the pipeline.
+    pub fn pipe<V, S>(self, stage: S) -> Pipeline<'s, T, V>
+    where
+        S: Stage<U, V> + 's,
+    {
+        let margin = self.margin + stage.margin();
+        let transform = self.transform;
+        Pipeline {
+            source: self.source,
+            transform: Box::new(move |buffer| stage.apply(transform(buffer))),
+            margin,
+            chunk_size: self.chunk_size,
+            #[cfg(feature = "rayon")]
+            parallel: self.parallel,
+            progress: self.progress,
+        }
+    }
+
+    /// The size of each chunk of output. By default chunks span the source
+    /// and are 256 rows tall.
+    pub fn chunk_size(mut self, size: (usize, usize)) -> Self {
+        self.chunk_size = Some(size);
+        self
+    }
+
+    /// Runs the stages of several chunks at once on the rayon thread pool.
+    /// Reads and writes still happen on the calling thread.
+    #[cfg(feature = "rayon")]
+    pub fn parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Calls `progress` with the fraction of the output written after each
+    /// chunk. Returning `false` cancels the run.
+    pub fn progress<F: FnMut(f64) -> bool + 's>(mut self, progress: F) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// The chunks read at once.
+    fn batch_size(&self) -> usize {
+        #[cfg(feature = "rayon")]
+        {
+            if self.parallel {
+                return 4 * rayon::current_num_threads();
+            }
+        }
+        1
+    }
+
+    fn apply_batch(&self, inputs: Vec<TypedBuffer<T>>) -> Vec<TypedBuffer<U>> {
+        let transform = &self.transform;
+        #[cfg(feature = "rayon")]
+        {
+            if self.parallel {
+                use rayon::prelude::*;
+                return inputs.into_par_iter().map(transform).collect();
+            }
+        }
+        inputs.into_iter().map(transform).collect()
+    }
+
+    /// Reads `window` along with the margin around it.
+    fn read_chunk(&self, window: Window) -> Result<TypedBuffer<T>> {
+        let m = self.margin;
+        let expanded = Window::new(
+            (window.offset.0 - m as isize, window.offset.1 - m as isize),
+            (window.size.0 + 2 * m, window.size.1 + 2 * m),
+        );
+        let buffer = self.source.read_window(expanded)?;
+        if m == 0 {
+            return Ok(buffer);
+        }
+        let inner = expanded
+            .intersection(&Window::full(self.source.size()))
+            .unwrap();
+        let left = (inner.offset.0 - expanded.offset.0) as usize;
+        let top = (inner.offset.1 - expanded.offset.1) as usize;
+        let margins = Margins {
+            left,
+            top,
+            right: expanded.size.0 - inner.size.0 - left,
+            bottom: expanded.size.1 - inner.size.1 - top,
+        };
+        Ok(buffer.pad(margins, PadMode::Edge))
+    }
+
+    /// Runs the pipeline over the whole source, writing to `sink`, which
+    /// must be the same size, and records one `pipeline` entry in the
+    /// sink's history.
+    pub fn sink<K: RasterSink<U>>(mut self, sink: &mut K) -> Result<()> {
+        let size = self.source.size();
+        if sink.size() != size {
+            return Err(Error::Bounds(format!(
+                "sink size {:?} doesn't match source size {:?}",
+                sink.size(),
+                size
+            )));
+        }
+        let chunk_size = self
+            .chunk_size
+            .unwrap_or((size.0, DEFAULT_CHUNK_ROWS.min(size.1)));
+        let windows = if size.0 * size.1 == 0 {
+            Vec::new()
+        } else {
+            block_windows(size, chunk_size)
+        };
+        let total = (size.0 * size.1) as f64;
+        let mut done = 0;
+
+        for batch in windows.chunks(self.batch_size()) {
+            let inputs = batch
+                .iter()
+                .map(|&window| self.read_chunk(window))
+                .collect::<Result<Vec<_>>>()?;
+            for (&window, output) in batch.iter().zip(self.apply_batch(inputs)) {
+                sink.write_window(window, &output)?;
+                done += window.pixel_count();
+                if let Some(progress) = self.progress.as_mut() {
+                    if !progress(done as f64 / total) {
+                        return Err(io::Error::new(
+                            io::ErrorKind::Interrupted,
+                            "pipeline cancelled",
+                        )
+                        .into());
+                    }
+                }
+            }
+        }
+        sink.flush()?;
+        sink.record_history("pipeline", &[("margin", self.margin.to_string())])
+    }
+}
+
+/// Starts a `Pipeline` from any source.
+pub trait Pipe<T: Copy> {
+    fn pipe<'s, U, S>(&'s self, stage: S) -> Pipeline<'s, T, U>
+    where
+        T: Send + 's,
+        U: Copy + Send + 's,
+        S: Stage<T, U> + 's;
+}
+
+impl<T: Copy, R: RasterSource<T>> Pipe<T> for R {
+    fn pipe<'s, U, S>(&'s self, stage: S) -> Pipeline<'s, T, U>
+    where
+        T: Send + 's,
+        U: Copy + Send + 's,
+        S: Stage<T, U> + 's,
+    {
+        Pipeline::new(self).pipe(stage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::pipeline::{focal_mean, map, Pipe};
+
+    #[test]
+    fn map_and_focal_mean() {
+        let source = TypedBuffer::new((4, 3), (0..12u8).collect());
+        let mut output = TypedBuffer::filled((4, 3), 0.0f32);
+
+        let mut calls = 0;
+        source
+            .pipe(map(|v: u8| v as f32 * 2.0))
+            .pipe(focal_mean(1))
+            .chunk_size((3, 2))
+            .progress(|_| {
+                calls += 1;
+                true
+            })
+            .sink(&mut output)
+            .unwrap();
+
+        assert_eq!(calls, 4);
+        // The centre pixels see only real neighbours, the corners extended
+        // edges.
+        assert_eq!(output.get(1, 1), 10.0);
+        assert_eq!(output.get(2, 1), 12.0);
+        assert!((output.get(0, 0) - 30.0 / 9.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn cancel_pipeline() {
+        let source = TypedBuffer::filled((4, 4), 1u8);
+        let mut output = source.clone();
+        let result = source
+            .pipe(map(|v: u8| v + 1))
+            .chunk_size((4, 1))
+            .progress(|done| done < 0.5)
+            .sink(&mut output);
+
+        assert!(result.is_err());
+        assert_eq!(output.row(1), &[2, 2, 2, 2]);
+        assert_eq!(output.row(2), &[1, 1, 1, 1]);
+    }
+}
@@ -0,0 +1,65 @@
+/// Conversion of pixel values to and from little-endian bytes.
+pub trait PixelBytes: Copy {
+    const SIZE: usize;
+
+    fn write_le(self, out: &mut Vec<u8>);
+
+    /// Reads a value from the first `SIZE` bytes of `bytes`.
+    fn read_le(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_pixel_bytes {
+    ($t:ty, $size:expr) => {
+        impl PixelBytes for $t {
+            const SIZE: usize = $size;
+
+            fn write_le(self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_le_bytes());
+            }
+
+            fn read_le(bytes: &[u8]) -> $t {
+                let mut b = [0u8; $size];
+                b.copy_from_slice(&bytes[..$size]);
+                <$t>::from_le_bytes(b)
+            }
+        }
+    };
+    ($t:ty, $bits:ty, $size:expr) => {
+        impl PixelBytes for $t {
+            const SIZE: usize = $size;
+
+            fn write_le(self, out: &mut Vec<u8>) {
+                self.to_bits().write_le(out);
+            }
+
+            fn read_le(bytes: &[u8]) -> $t {
+                <$t>::from_bits(<$bits>::read_le(bytes))
+            }
+        }
+    };
+}
+
+impl_pixel_bytes!(u8, 1);
+impl_pixel_bytes!(u16, 2);
+impl_pixel_bytes!(u32, 4);
+impl_pixel_bytes!(u64, 8);
+impl_pixel_bytes!(i16, 2);
+impl_pixel_bytes!(i32, 4);
+impl_pixel_bytes!(f32, u32, 4);
+impl_pixel_bytes!(f64, u64, 8);
+
+#[cfg(test)]
+mod tests {
+    use super::PixelBytes;
+
+    #[test]
+    fn pixel_bytes_round_trip() {
+        let mut bytes = Vec::new();
+        513u16.write_le(&mut bytes);
+        (-1.5f32).write_le(&mut bytes);
+
+        assert_eq!(&bytes[..2], &[1, 2]);
+        assert_eq!(u16::read_le(&bytes), 513);
+        assert_eq!(f32::read_le(&bytes[2..]), -1.5);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::blocks::block_window;
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+use std::collections::BTreeSet;
+
+/// Collects many small window reads and serves them with a few large,
+/// block-aligned reads.
+///
+/// Random access into a compressed raster decodes a whole block for every
+/// request; planning the reads up front decodes each block touched by any
+/// request exactly once.
+#[derive(Debug, Clone, Default)]
+pub struct ReadPlanner {
+    requests: Vec<Window>,
+}
+
+impl ReadPlanner {
+    pub fn new() -> ReadPlanner {
+        ReadPlanner::default()
+    }
+
+    /// Adds a request, returning its index in the results of `execute`.
+    pub fn add(&mut self, window: Window) -> usize {
+        self.requests.push(window);
+        self.requests.len() - 1
+    }
+
+    pub fn requests(&self) -> &[Window] {
+        &self.requests
+    }
+
+    /// The reads needed to serve every request. Each read covers a run of
+    /// adjacent blocks in one row of the block grid, and the reads are
+    /// sorted in row-major order.
+    pub fn plan(&self, raster_size: (usize, usize), block_size: (usize, usize)) -> Vec<Window> {
+        let full = Window::full(raster_size);
+        let mut blocks = BTreeSet::new();
+        for request in &self.requests {
+            if let Some(w) = request.intersection(&full) {
+                let (x0, y0) = (w.offset.0 as usize, w.offset.1 as usize);
+                for by in y0 / block_size.1..=(y0 + w.size.1 - 1) / block_size.1 {
+                    for bx in x0 / block_size.0..=(x0 + w.size.0 - 1) / block_size.0 {
+                        blocks.insert((by, bx));
+                    }
+                }
+            }
+        }
+
+        let mut reads = Vec::new();
+        let mut blocks = blocks.into_iter().peekable();
+        while let Some((by, first)) = blocks.next() {
+            let mut last = first;
+            while blocks.peek() == Some(&(by, last + 1)) {
+                last += 1;
+                blocks.next();
+            }
+            let start = block_window(raster_size, block_size, (first, by));
+            let end = block_window(raster_size, block_size, (last, by));
+            let width = (end.offset.0 - start.offset.0) as usize + end.size.0;
+            reads.push(Window::new(start.offset, (width, start.size.1)));
+        }
+        reads
+    }
+
+    /// Executes the plan against `band`, returning one buffer per request in
+    /// the order they were added.
+    ///
+    /// Only one planned read is held in memory at a time. Parts of requests
+    /// that fall outside the raster are filled with the band's nodata value,
+    /// or zero if it has none.
+    pub fn execute<T, A>(&self, band: &TypedRasterBand<T, A>) -> Result<Vec<TypedBuffer<T>>>
+    where
+        T: Copy + GdalType + GdalFrom<f64>,
+        A: Access,
+    {
+        let fill = band.no_data_value().unwrap_or_else(|| T::from(0.0));
+        let mut results: Vec<_> = self
+            .requests
+            .iter()
+            .map(|w| TypedBuffer::filled(w.size, fill))
+            .collect();
+
+        for read in self.plan(band.size(), band.block_size()) {
+            let buffer: TypedBuffer<T> = band.read(read.offset, read.size, read.size)?.into();
+            for (request, result) in self.requests.iter().zip(results.iter_mut()) {
+                let overlap = match request.intersection(&read) {
+                    Some(overlap) => overlap,
+                    None => continue,
+                };
+                for y in 0..overlap.size.1 {
+                    let src_x = (overlap.offset.0 - read.offset.0) as usize;
+                    let src_y = (overlap.offset.1 - read.offset.1) as usize + y;
+                    let dst_x = (overlap.offset.0 - request.offset.0) as usize;
+                    let dst_y = (overlap.offset.1 - request.offset.1) as usize + y;
+
+                    let src = &buffer.row(src_y)[src_x..src_x + overlap.size.0];
+                    let start = dst_y * result.size.0 + dst_x;
+                    result.data[start..start + overlap.size.0].copy_from_slice(src);
+                }
+            }
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::planner::ReadPlanner;
+    use crate::typed_rasterband::TypedRasterBand;
+    use crate::window::Window;
+    use gdal::raster::dataset::Dataset;
+    use std::path::Path;
+
+    #[test]
+    fn plan_merges_adjacent_blocks() {
+        let mut planner = ReadPlanner::new();
+        planner.add(Window::new((9, 9), (1, 1)));
+        planner.add(Window::new((1, 1), (1, 1)));
+        planner.add(Window::new((3, 2), (2, 1)));
+
+        assert_eq!(
+            planner.plan((10, 10), (4, 4)),
+            vec![Window::new((0, 0), (8, 4)), Window::new((8, 8), (2, 2))]
+        );
+    }
+
+    #[test]
+    fn execute_planned_reads() {
+        let path = Path::new("testdata/test_u8.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u8>::from_rasterband(&band).unwrap();
+
+        let mut planner = ReadPlanner::new();
+        planner.add(Window::new((100, 100), (4, 1)));
+        planner.add(Window::new((0, 0), (2, 2)));
+        let results = planner.execute(&typed_band).unwrap();
+
+        assert_eq!(results[0].data, vec![119, 139, 155, 179]);
+        assert_eq!(results[1].data, vec![152, 161, 139, 164]);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::blocks::block_window;
+use crate::buffer::TypedBuffer;
+use crate::cache::BlockCache;
+use crate::errors::Result;
+use crate::shared::SharedTypedBand;
+use crate::typed_rasterband::GdalFrom;
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+/// Reads windows of a band on a background thread, one window ahead of the
+/// consumer.
+///
+/// While the caller processes window N the background thread reads window
+/// N+1, then waits for it to be taken before reading further, so at most two
+/// buffers are alive at once.
+pub struct PrefetchingReader<T> {
+    receiver: Receiver<Result<(Window, TypedBuffer<T>)>>,
+}
+
+impl<T> PrefetchingReader<T>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Send + 'static,
+{
+    /// Reads each of `windows` in turn, at full resolution.
+    pub fn new(band: SharedTypedBand<T>, windows: Vec<Window>) -> PrefetchingReader<T> {
+        // A zero-capacity channel makes each send wait for the consumer, so
+        // the reader never gets more than one window ahead.
+        let (sender, receiver) = mpsc::sync_channel(0);
+        thread::spawn(move || {
+            for window in windows {
+                let block = band
+                    .read(window, window.size)
+                    .map(|buffer| (window, buffer));
+                if sender.send(block).is_err() {
+                    // The reader was dropped.
+                    break;
+                }
+            }
+        });
+        PrefetchingReader { receiver }
+    }
+
+    /// Reads the natural blocks of `band` in row-major order.
+    pub fn blocks(band: SharedTypedBand<T>) -> Result<PrefetchingReader<T>> {
+        let windows = band.with_band(|b| b.block_windows())?;
+        Ok(PrefetchingReader::new(band, windows))
+    }
+}
+
+impl<T> Iterator for PrefetchingReader<T> {
+    type Item = Result<(Window, TypedBuffer<T>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+/// The column and row of each natural block intersecting `window`, in
+/// row-major order.
+fn blocks_in(
+    window: Window,
+    raster_size: (usize, usize),
+    block_size: (usize, usize),
+) -> Vec<(usize, usize)> {
+    let window = match window.intersection(&Window::full(raster_size)) {
+        Some(window) => window,
+        None => return Vec::new(),
+    };
+    let (x0, y0) = (window.offset.0 as usize, window.offset.1 as usize);
+    let (x1, y1) = (x0 + window.size.0, y0 + window.size.1);
+    let mut blocks = Vec::new();
+    for row in y0 / block_size.1..y1.div_ceil(block_size.1) {
+        for col in x0 / block_size.0..x1.div_ceil(block_size.0) {
+            blocks.push((col, row));
+        }
+    }
+    blocks
+}
+
+/// A background read started by `SharedTypedBand::prefetch`.
+pub struct Prefetch {
+    handle: JoinHandle<Result<usize>>,
+}
+
+impl Prefetch {
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Waits for the reads to finish, returning the number of blocks read
+    /// or the first error.
+    pub fn wait(self) -> Result<usize> {
+        self.handle.join().expect("prefetch thread panicked")
+    }
+}
+
+impl<T> SharedTypedBand<T>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Send + Sync + 'static,
+{
+    /// Starts reading every natural block intersecting `window` on a
+    /// background thread, so that later reads of it are fast. The read stops
+    /// at the first error, which `Prefetch::wait` returns.
+    ///
+    /// With a `cache`, the decoded blocks are kept there for
+    /// `BlockCache::get_or_read`. Without one, only caches GDAL shares
+    /// between threads are warmed, such as the `/vsicurl/` cache of remote
+    /// file contents; blocks GDAL decodes are cached per dataset handle, and
+    /// the background thread has its own.
+    pub fn prefetch(&self, window: Window, cache: Option<Arc<BlockCache<T>>>) -> Prefetch {
+        let band = self.clone();
+        let handle = thread::spawn(move || {
+            let (raster_size, block_size) = band.with_band(|b| (b.size(), b.block_size()))?;
+            let blocks = blocks_in(window, raster_size, block_size);
+            for &block in &blocks {
+                match &cache {
+                    Some(cache) => {
+                        cache.get_or_read(&band, block)?;
+                    }
+                    None => {
+                        let window = block_window(raster_size, block_size, block);
+                        band.read(window, window.size)?;
+                    }
+                }
+            }
+            Ok(blocks.len())
+        });
+        Prefetch { handle }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::BlockCache;
+    use crate::prefetch::{blocks_in, PrefetchingReader};
+    use crate::shared::SharedTypedBand;
+    use crate::window::Window;
+    use std::sync::Arc;
+
+    #[test]
+    fn prefetch_band_blocks() {
+        let shared = SharedTypedBand::<u8>::new("testdata/test_u8.tif", 1).unwrap();
+        let blocks: Vec<_> = PrefetchingReader::blocks(shared)
+            .unwrap()
+            .map(|b| b.unwrap())
+            .collect();
+
+        assert_eq!(blocks.len(), 14);
+        assert_eq!(blocks[13].0, Window::new((0, 312), (333, 21)));
+        assert_eq!(blocks[0].1.get(0, 1), 139);
+    }
+
+    #[test]
+    fn blocks_under_window() {
+        let window = Window::new((300, 20), (100, 10));
+        assert_eq!(
+            blocks_in(window, (333, 333), (128, 24)),
+            vec![(2, 0), (2, 1)]
+        );
+        assert!(blocks_in(Window::new((400, 0), (5, 5)), (333, 333), (128, 24)).is_empty());
+    }
+
+    #[test]
+    fn prefetch_into_cache() {
+        let shared = SharedTypedBand::<u8>::new("testdata/test_u8.tif", 1).unwrap();
+        let cache = Arc::new(BlockCache::new(1 << 20));
+        let prefetch = shared.prefetch(Window::new((0, 30), (10, 30)), Some(cache.clone()));
+
+        assert_eq!(prefetch.wait().unwrap(), 2);
+        assert_eq!(cache.len(), 2);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::blocks::block_window;
+use crate::buffer::TypedBuffer;
+use crate::errors::{check_cpl_err, Result};
+use crate::trace::{buffer_bytes, instrument};
+use crate::typed_rasterband::{Access, GdalFrom, ReadWrite, TypedRasterBand};
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+use gdal_sys::{GDALRWFlag, GDALRasterIO, GDALReadBlock};
+use std::os::raw::c_void;
+
+impl<'a, T: Copy + GdalType + GdalFrom<f64>> TypedRasterBand<'a, T, ReadWrite> {
+    /// Writes `data`, a row-major block of `shape` pixels, to `window`.
+    ///
+    /// The slice is handed straight to GDAL, so unlike `write` no `Buffer`
+    /// needs to be built. If `shape` differs from the window size, GDAL
+    /// resamples the data to fit.
+    pub fn write_slice(&self, window: Window, data: &[T], shape: (usize, usize)) -> Result<()> {
+        assert_eq!(
+            shape.0 * shape.1,
+            data.len(),
+            "slice length doesn't match shape"
+        );
+        let rv = instrument("write", window, buffer_bytes::<T>(shape), || unsafe {
+            GDALRasterIO(
+                self.rasterband()._c_ptr(),
+                GDALRWFlag::GF_Write,
+                window.offset.0 as i32,
+                window.offset.1 as i32,
+                window.size.0 as i32,
+                window.size.1 as i32,
+                // GDAL only reads from the buffer when writing.
+                data.as_ptr() as *mut c_void,
+                shape.0 as i32,
+                shape.1 as i32,
+                T::gdal_type(),
+                0,
+                0,
+            )
+        });
+        check_cpl_err(rv)
+    }
+}
+
+impl<'a, T: Copy + GdalType + GdalFrom<f64>, A: Access> TypedRasterBand<'a, T, A> {
+    /// Reads the whole band block by block in its native layout.
+    ///
+    /// This skips RasterIO's windowing and type-conversion machinery. Strips
+    /// that span the full raster width are decoded directly into the output
+    /// buffer; other blocks are decoded into a scratch block and copied a row
+    /// at a time.
+    ///
+    /// `cargo bench --bench full_band` compares it with `read_band` on the
+    /// striped `test_u16.tif` fixture and on a 64x64-tiled copy of it. No
+    /// numbers are given here because none have been measured yet; record
+    /// them from that bench before relying on the speedup.
+    ///
+    /// `read_band` doesn't use this path. `GDALReadBlock` skips GDAL's block
+    /// cache, so reading windows of the band afterwards decodes every block
+    /// again, and for pixel-interleaved files it decodes all bands of a
+    /// block to return one.
+    pub fn read_band_fast(&self) -> Result<TypedBuffer<T>> {
+        let (width, height) = self.size();
+        let block_size = self.block_size();
+        let blocks_x = width.div_ceil(block_size.0);
+        let blocks_y = height.div_ceil(block_size.1);
+
+        let fill = T::from(0.0);
+        let mut output = TypedBuffer::filled((width, height), fill);
+        let mut scratch = vec![fill; block_size.0 * block_size.1];
+        let c_band = unsafe { self.rasterband()._c_ptr() };
+
+        for by in 0..blocks_y {
+            for bx in 0..blocks_x {
+                let window = block_window((width, height), block_size, (bx, by));
+                let (x0, y0) = (window.offset.0 as usize, window.offset.1 as usize);
+
+                if block_size.0 == width && window.size.1 == block_size.1 {
+                    let start = y0 * width;
+                    let target = &mut output.data[start..start + scratch.len()];
+                    let rv = unsafe {
+                        GDALReadBlock(c_band, 0, by as i32, target.as_mut_ptr() as *mut c_void)
+                    };
+                    check_cpl_err(rv)?;
+                    continue;
+                }
+
+                let rv = unsafe {
+                    GDALReadBlock(
+                        c_band,
+                        bx as i32,
+                        by as i32,
+                        scratch.as_mut_ptr() as *mut c_void,
+                    )
+                };
+                check_cpl_err(rv)?;
+                for row in 0..window.size.1 {
+                    let src = &scratch[row * block_size.0..row * block_size.0 + window.size.0];
+                    let start = (y0 + row) * width + x0;
+                    output.data[start..start + window.size.0].copy_from_slice(src);
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::typed_rasterband::{ReadWrite, TypedRasterBand};
+    use crate::window::Window;
+    use gdal::raster::dataset::Dataset;
+    use gdal::raster::driver::Driver;
+    use std::path::Path;
+
+    #[test]
+    fn write_band_slice() {
+        let driver = Driver::get("MEM").unwrap();
+        let ds = driver
+            .create_with_band_type::<u16>("", 4, 4, 1)
+            .expect("failed to create dataset");
+        let band = ds.rasterband(1).unwrap();
+        let typed_band =
+            TypedRasterBand::<u16, ReadWrite>::from_writable_rasterband(&band).unwrap();
+
+        let data = [1, 2, 3, 4, 5, 6];
+        typed_band
+            .write_slice(Window::new((1, 1), (3, 2)), &data, (3, 2))
+            .unwrap();
+        let buffer = typed_band.read((1, 2), (3, 1), (3, 1)).unwrap();
+        assert_eq!(buffer.data, vec![4, 5, 6]);
+    }
+
+    #[test]
+    fn fast_full_band_read() {
+        let path = Path::new("testdata/test_u16.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u16>::from_rasterband(&band).unwrap();
+
+        let fast = typed_band.read_band_fast().unwrap();
+        let slow: TypedBuffer<u16> = typed_band.read_band().unwrap().into();
+        assert_eq!(fast, slow);
+    }
+}
@@ -0,0 +1,195 @@
+use crate::blocks::block_window;
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+
+/// The parts of `window` that fall in each natural block of a raster, in
+/// row-major block order.
+pub fn block_pieces(
+    window: Window,
+    raster_size: (usize, usize),
+    block_size: (usize, usize),
+) -> Vec<Window> {
+    let window = match window.intersection(&Window::full(raster_size)) {
+        Some(window) => window,
+        None => return Vec::new(),
+    };
+    let (x0, y0) = (window.offset.0 as usize, window.offset.1 as usize);
+    let (x1, y1) = (x0 + window.size.0, y0 + window.size.1);
+    let mut pieces = Vec::new();
+    for row in y0 / block_size.1..y1.div_ceil(block_size.1) {
+        for col in x0 / block_size.0..x1.div_ceil(block_size.0) {
+            let block = block_window(raster_size, block_size, (col, row));
+            pieces.extend(block.intersection(&window));
+        }
+    }
+    pieces
+}
+
+/// A running sum that carries the rounding error of each addition forward
+/// (Neumaier's variant of Kahan summation), so that summing millions of
+/// pixels, or values of very different magnitudes, stays accurate to the
+/// last bit or so instead of drifting with the count.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CompensatedSum {
+    sum: f64,
+    compensation: f64,
+}
+
+impl CompensatedSum {
+    pub fn new() -> CompensatedSum {
+        CompensatedSum::default()
+    }
+
+    pub fn add(&mut self, value: f64) {
+        let t = self.sum + value;
+        // Whichever operand is smaller lost its low bits in `t`.
+        self.compensation += if self.sum.abs() >= value.abs() {
+            (self.sum - t) + value
+        } else {
+            (value - t) + self.sum
+        };
+        self.sum = t;
+    }
+
+    pub fn value(&self) -> f64 {
+        self.sum + self.compensation
+    }
+}
+
+impl Extend<f64> for CompensatedSum {
+    fn extend<I: IntoIterator<Item = f64>>(&mut self, values: I) {
+        for value in values {
+            self.add(value);
+        }
+    }
+}
+
+impl<'a, T, A: Access> TypedRasterBand<'a, T, A>
+where
+    T: Copy + GdalType + GdalFrom<f64> + PartialEq,
+{
+    /// Folds the pixels of `window` into `init` with `fold`, reading one
+    /// natural block at a time so the window is never held in memory. Nodata
+    /// pixels are skipped, and the window is clipped to the band.
+    ///
+    /// Pixels are visited block by block, so `fold` shouldn't depend on
+    /// their order.
+    pub fn reduce<B, F>(&self, window: Window, init: B, mut fold: F) -> Result<B>
+    where
+        F: FnMut(B, T) -> B,
+    {
+        let no_data = self.no_data_value();
+        let mut acc = init;
+        for piece in block_pieces(window, self.size(), self.block_size()) {
+            let buffer: TypedBuffer<T> = self.read(piece.offset, piece.size, piece.size)?.into();
+            acc = buffer
+                .data
+                .into_iter()
+                .filter(|&v| Some(v) != no_data)
+                .fold(acc, &mut fold);
+        }
+        Ok(acc)
+    }
+}
+
+impl<'a, T, A: Access> TypedRasterBand<'a, T, A>
+where
+    T: Copy + GdalType + GdalFrom<f64> + PartialEq + Into<f64>,
+{
+    /// The compensated sum of the valid pixels of `window`, skipping nodata
+    /// and NaN pixels.
+    pub fn sum(&self, window: Window) -> Result<f64> {
+        Ok(self.sum_and_count(window)?.0)
+    }
+
+    /// The mean of the valid pixels of `window`, or `None` if there are
+    /// none.
+    pub fn mean(&self, window: Window) -> Result<Option<f64>> {
+        let (sum, count) = self.sum_and_count(window)?;
+        Ok(if count == 0 {
+            None
+        } else {
+            Some(sum / count as f64)
+        })
+    }
+
+    fn sum_and_count(&self, window: Window) -> Result<(f64, usize)> {
+        let (sum, count) =
+            self.reduce(window, (CompensatedSum::new(), 0), |(mut sum, count), v| {
+                let v: f64 = v.into();
+                if v.is_nan() {
+                    return (sum, count);
+                }
+                sum.add(v);
+                (sum, count + 1)
+            })?;
+        Ok((sum.value(), count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reduce::{block_pieces, CompensatedSum};
+    use crate::typed_rasterband::TypedRasterBand;
+    use crate::window::Window;
+    use gdal::raster::dataset::Dataset;
+    use std::path::Path;
+
+    #[test]
+    fn split_window_by_block() {
+        let pieces = block_pieces(Window::new((5, 20), (10, 10)), (333, 333), (333, 24));
+        assert_eq!(
+            pieces,
+            vec![Window::new((5, 20), (10, 4)), Window::new((5, 24), (10, 6))]
+        );
+        assert!(block_pieces(Window::new((400, 0), (5, 5)), (333, 333), (333, 24)).is_empty());
+    }
+
+    #[test]
+    fn compensated_sum_is_exact() {
+        let mut sum = CompensatedSum::new();
+        sum.extend(vec![1.0, 1e100, 1.0, -1e100]);
+        assert_eq!(sum.value(), 2.0);
+
+        // Each 1.0 is below half an ulp of 1e16, so naive f64 addition drops
+        // every one of them.
+        let mut values = vec![1e16];
+        values.extend(vec![1.0; 1000]);
+        let naive: f64 = values.iter().sum();
+        let mut sum = CompensatedSum::new();
+        sum.extend(values);
+        assert_eq!(naive, 1e16);
+        assert_eq!(sum.value(), 1e16 + 1000.0);
+
+        let tenths = vec![0.1; 10];
+        let naive: f64 = tenths.iter().sum();
+        let mut sum = CompensatedSum::new();
+        sum.extend(tenths);
+        assert_ne!(naive, 1.0);
+        assert_eq!(sum.value(), 1.0);
+    }
+
+    #[test]
+    fn reduce_window() {
+        let path = Path::new("testdata/test_u8.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u8>::from_rasterband(&band).unwrap();
+
+        let sum = typed_band
+            .reduce(Window::new((0, 0), (2, 1)), 0u32, |acc, v| acc + v as u32)
+            .unwrap();
+        assert_eq!(sum, 152 + 161);
+        let count = typed_band
+            .reduce(Window::new((0, 20), (10, 10)), 0, |acc, _| acc + 1)
+            .unwrap();
+        assert_eq!(count, 100);
+        assert_eq!(
+            typed_band.sum(Window::new((0, 0), (2, 1))).unwrap(),
+            (152 + 161) as f64
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use crate::bitmask::BitMaskBuffer;
+use crate::buffer::TypedBuffer;
+use crate::components::Connectivity;
+use crate::errors::Result;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use gdal::raster::types::GdalType;
+
+impl<T: Copy + PartialEq> TypedBuffer<T> {
+    /// Grows a region outwards from each seed pixel, adding neighbours for
+    /// which `predicate(seed_value, value)` holds, and returns the mask of
+    /// every pixel reached. Each seed's region is compared with that
+    /// seed's own value.
+    ///
+    /// Pixels equal to `nodata` are never added, and seeds that are nodata
+    /// or outside the buffer are skipped.
+    pub fn region_grow<F>(
+        &self,
+        seeds: &[(usize, usize)],
+        connectivity: Connectivity,
+        nodata: Option<T>,
+        predicate: F,
+    ) -> BitMaskBuffer
+    where
+        F: Fn(T, T) -> bool,
+    {
+        let (width, height) = self.size;
+        let mut mask = BitMaskBuffer::filled(self.size, false);
+        // Tracks the pixels visited from the current seed, since those
+        // reached from another seed may still pass this seed's predicate.
+        let mut visited = vec![false; width * height];
+        let mut touched = Vec::new();
+        let mut stack = Vec::new();
+
+        for &(sx, sy) in seeds {
+            if sx >= width || sy >= height {
+                continue;
+            }
+            let seed_value = self.get(sx, sy);
+            if Some(seed_value) == nodata {
+                continue;
+            }
+            for i in touched.drain(..) {
+                visited[i] = false;
+            }
+            visited[sy * width + sx] = true;
+            touched.push(sy * width + sx);
+            stack.push((sx, sy));
+            while let Some((x, y)) = stack.pop() {
+                mask.set(x, y, true);
+                for &(dx, dy) in connectivity.offsets() {
+                    let (nx, ny) = (x as isize + dx, y as isize + dy);
+                    if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                        continue;
+                    }
+                    let (nx, ny) = (nx as usize, ny as usize);
+                    let j = ny * width + nx;
+                    let value = self.data[j];
+                    if !visited[j] && Some(value) != nodata && predicate(seed_value, value) {
+                        visited[j] = true;
+                        touched.push(j);
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+        }
+        mask
+    }
+}
+
+impl<'a, T, A: Access> TypedRasterBand<'a, T, A>
+where
+    T: Copy + GdalType + GdalFrom<f64> + PartialEq,
+{
+    /// Reads the band and grows regions from `seeds` with
+    /// `TypedBuffer::region_grow`, never adding nodata pixels.
+    pub fn region_grow<F>(
+        &self,
+        seeds: &[(usize, usize)],
+        connectivity: Connectivity,
+        predicate: F,
+    ) -> Result<BitMaskBuffer>
+    where
+        F: Fn(T, T) -> bool,
+    {
+        let buffer: TypedBuffer<T> = self.read_band()?.into();
+        Ok(buffer.region_grow(seeds, connectivity, self.no_data_value(), predicate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bitmask::PixelMask;
+    use crate::buffer::TypedBuffer;
+    use crate::components::Connectivity;
+
+    #[test]
+    fn grow_within_tolerance() {
+        let dem = TypedBuffer::new(
+            (4, 2),
+            vec![
+                10i16, 12, 30, 14, //
+                11, 15, 13, 9,
+            ],
+        );
+        let within_5 = |seed: i16, v: i16| (v - seed).abs() <= 5;
+
+        let region = dem.region_grow(&[(0, 0)], Connectivity::Four, None, within_5);
+        assert_eq!(region.count_set(), 7);
+        assert!(!region.is_set(2, 0));
+
+        let blocked = dem.region_grow(&[(0, 0)], Connectivity::Four, Some(13), within_5);
+        assert_eq!(blocked.count_set(), 4);
+        assert!(!blocked.is_set(3, 0));
+    }
+}
@@ -0,0 +1,152 @@
+use crate::buffer::TypedBuffer;
+use crate::colormap::{Colormap, RgbaBuffer};
+use crate::errors::Result;
+use crate::source::RasterSource;
+
+/// Where the light that shades a surface comes from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Light {
+    /// Degrees clockwise from north.
+    pub azimuth: f64,
+    /// Degrees above the horizon.
+    pub altitude: f64,
+    /// Multiplies elevations, to exaggerate relief or convert their units to
+    /// those of the cell size.
+    pub z_factor: f64,
+}
+
+/// Light from the northwest at 45°, as `gdaldem` uses.
+impl Default for Light {
+    fn default() -> Light {
+        Light {
+            azimuth: 315.0,
+            altitude: 45.0,
+            z_factor: 1.0,
+        }
+    }
+}
+
+/// Shades `dem` by `light` using Horn's method, from 0 for pixels facing
+/// away from the light to 1 for those facing it. `cell_size` is the width
+/// and height of a pixel in the same units as elevations.
+///
+/// Nodata pixels are NaN. Nodata neighbours and those past the edge are
+/// treated as level with the pixel being shaded.
+pub fn hillshade<T: Copy + Into<f64> + PartialEq>(
+    dem: &TypedBuffer<T>,
+    cell_size: (f64, f64),
+    light: &Light,
+    nodata: Option<T>,
+) -> TypedBuffer<f32> {
+    let (width, height) = dem.size;
+    let (azimuth, altitude) = (light.azimuth.to_radians(), light.altitude.to_radians());
+    let (lx, ly, lz) = (
+        azimuth.sin() * altitude.cos(),
+        azimuth.cos() * altitude.cos(),
+        altitude.sin(),
+    );
+
+    let mut data = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let centre = dem.get(x, y);
+            if Some(centre) == nodata {
+                data.push(f32::NAN);
+                continue;
+            }
+            let z = |dx: isize, dy: isize| -> f64 {
+                let (nx, ny) = (x as isize + dx, y as isize + dy);
+                let v = if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                    centre
+                } else {
+                    dem.get(nx as usize, ny as usize)
+                };
+                let v = if Some(v) == nodata { centre } else { v };
+                v.into() * light.z_factor
+            };
+            let east = ((z(1, -1) + 2.0 * z(1, 0) + z(1, 1))
+                - (z(-1, -1) + 2.0 * z(-1, 0) + z(-1, 1)))
+                / (8.0 * cell_size.0);
+            let north = ((z(-1, -1) + 2.0 * z(0, -1) + z(1, -1))
+                - (z(-1, 1) + 2.0 * z(0, 1) + z(1, 1)))
+                / (8.0 * cell_size.1);
+            let shade = (lz - east * lx - north * ly) / (1.0 + east * east + north * north).sqrt();
+            data.push(shade.max(0.0) as f32);
+        }
+    }
+    TypedBuffer::new(dem.size, data)
+}
+
+/// Renders an elevation source through `colormap`, darkened by its
+/// hillshade under `light`.
+///
+/// The cell size comes from the source's geotransform, or is 1 if it has
+/// none.
+pub fn render_shaded_relief<T, S>(dem: &S, colormap: &Colormap, light: &Light) -> Result<RgbaBuffer>
+where
+    T: Copy + Into<f64> + PartialEq,
+    S: RasterSource<T>,
+{
+    let nodata = dem.no_data_value();
+    let cell_size = dem
+        .geo_transform()
+        .map_or((1.0, 1.0), |gt| (gt[1].abs(), gt[5].abs()));
+    let buffer = dem.read_full()?;
+    let shade = hillshade(&buffer, cell_size, light, nodata);
+
+    let data = buffer
+        .data
+        .iter()
+        .zip(&shade.data)
+        .map(|(&v, &s)| {
+            if Some(v) == nodata {
+                return colormap.nodata_color;
+            }
+            let [r, g, b, a] = colormap.color(v.into());
+            let s = s as f64;
+            [
+                (r as f64 * s).round() as u8,
+                (g as f64 * s).round() as u8,
+                (b as f64 * s).round() as u8,
+                a,
+            ]
+        })
+        .collect();
+    Ok(TypedBuffer::new(buffer.size, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::colormap::Colormap;
+    use crate::relief::{hillshade, render_shaded_relief, Light};
+
+    #[test]
+    fn shade_slopes() {
+        // A surface rising one unit per pixel to the east.
+        let dem = TypedBuffer::new((4, 3), (0..12).map(|i| (i % 4) as f32).collect());
+        let from_west = Light {
+            azimuth: 270.0,
+            ..Light::default()
+        };
+
+        let shade = hillshade(&dem, (1.0, 1.0), &from_west, None);
+        assert!((shade.get(1, 1) - 1.0).abs() < 1e-6);
+        let flat = hillshade(
+            &TypedBuffer::filled((3, 3), 5u8),
+            (1.0, 1.0),
+            &from_west,
+            None,
+        );
+        assert!((flat.get(1, 1) - 0.5f32.sqrt()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn render_relief() {
+        let dem = TypedBuffer::new((3, 1), vec![0u8, 0, 0]);
+        let colormap = Colormap::new(vec![(0.0, [200, 100, 0, 255])]);
+        let rgba = render_shaded_relief(&dem, &colormap, &Light::default()).unwrap();
+
+        assert_eq!(rgba.get(1, 0), [141, 71, 0, 255]);
+    }
+}
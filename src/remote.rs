@@ -0,0 +1,290 @@
+use crate::dataset::TypedDataset;
+use crate::typed_rasterband::GdalFrom;
+use gdal::raster::types::GdalType;
+use gdal_sys::{
+    CPLSetConfigOption, VSIFree, VSINetworkStatsGetAsSerializedJSON, VSINetworkStatsReset,
+};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_void;
+use std::ptr;
+
+/// Tuning for how GDAL reads remote files over HTTP, set per dataset. Unset
+/// fields keep GDAL's defaults.
+///
+/// The chunk and cache sizes are process-wide, so they are set separately
+/// by `set_remote_cache_options`.
+#[derive(Debug, Clone, Default)]
+pub struct RemoteReadOptions {
+    /// Whether adjacent ranges are merged into a single request
+    /// (`GDAL_HTTP_MERGE_CONSECUTIVE_RANGES`).
+    pub merge_consecutive_ranges: Option<bool>,
+    /// Whether several ranges may be requested at once
+    /// (`GDAL_HTTP_MULTIRANGE`).
+    pub multirange: Option<bool>,
+    /// Whether a HEAD request is issued to learn the file size before reading
+    /// (`CPL_VSIL_CURL_USE_HEAD`).
+    pub use_head: Option<bool>,
+    /// Skip listing the file's directory when opening, which saves a request
+    /// per file (`GDAL_DISABLE_READDIR_ON_OPEN`).
+    pub disable_read_dir_on_open: bool,
+}
+
+fn yes_no(value: bool) -> String {
+    if value { "YES" } else { "NO" }.to_string()
+}
+
+impl RemoteReadOptions {
+    /// The GDAL config options these settings correspond to.
+    pub fn config_options(&self) -> Vec<(String, String)> {
+        let mut config = Vec::new();
+        if let Some(merge) = self.merge_consecutive_ranges {
+            config.push((
+                "GDAL_HTTP_MERGE_CONSECUTIVE_RANGES".to_string(),
+                yes_no(merge),
+            ));
+        }
+        if let Some(multirange) = self.multirange {
+            let value = if multirange { "PARALLEL" } else { "SERIAL" };
+            config.push(("GDAL_HTTP_MULTIRANGE".to_string(), value.to_string()));
+        }
+        if let Some(use_head) = self.use_head {
+            config.push(("CPL_VSIL_CURL_USE_HEAD".to_string(), yes_no(use_head)));
+        }
+        if self.disable_read_dir_on_open {
+            config.push((
+                "GDAL_DISABLE_READDIR_ON_OPEN".to_string(),
+                "EMPTY_DIR".to_string(),
+            ));
+        }
+        config
+    }
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64>> TypedDataset<T> {
+    /// Attaches remote read tuning to the dataset's config options.
+    pub fn set_remote_read_options(&mut self, options: &RemoteReadOptions) {
+        for (key, value) in options.config_options() {
+            self.set_config_option(&key, &value);
+        }
+    }
+}
+
+/// The sizes GDAL uses to download and cache remote files. Unset fields keep
+/// GDAL's defaults.
+#[derive(Debug, Clone, Default)]
+pub struct RemoteCacheOptions {
+    /// The size of each range request, in bytes (`CPL_VSIL_CURL_CHUNK_SIZE`).
+    pub chunk_size: Option<usize>,
+    /// The size of the cache of downloaded chunks, in bytes, shared by all
+    /// remote files (`CPL_VSIL_CURL_CACHE_SIZE`).
+    pub cache_size: Option<usize>,
+}
+
+impl RemoteCacheOptions {
+    /// The GDAL config options these settings correspond to.
+    pub fn config_options(&self) -> Vec<(String, String)> {
+        let mut config = Vec::new();
+        if let Some(size) = self.chunk_size {
+            config.push(("CPL_VSIL_CURL_CHUNK_SIZE".to_string(), size.to_string()));
+        }
+        if let Some(size) = self.cache_size {
+            config.push(("CPL_VSIL_CURL_CACHE_SIZE".to_string(), size.to_string()));
+        }
+        config
+    }
+}
+
+/// Sets the chunk and cache sizes for every remote file in the process.
+/// GDAL reads them once, when the first remote file is opened, so call this
+/// before then; later calls have no effect.
+pub fn set_remote_cache_options(options: &RemoteCacheOptions) {
+    for (key, value) in options.config_options() {
+        let key = CString::new(key).unwrap();
+        let value = CString::new(value).unwrap();
+        unsafe { CPLSetConfigOption(key.as_ptr(), value.as_ptr()) };
+    }
+}
+
+/// Totals of the HTTP requests GDAL has issued for remote files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteReadStats {
+    pub requests: u64,
+    pub bytes_downloaded: u64,
+    /// GDAL's full report, broken down by filesystem, file and method.
+    pub json: String,
+}
+
+/// Starts collecting the statistics reported by `remote_read_stats`. GDAL
+/// only checks this setting once, so call it before the first remote read.
+pub fn enable_remote_read_stats() {
+    let key = CString::new("CPL_VSIL_NETWORK_STATS_ENABLED").unwrap();
+    let value = CString::new("YES").unwrap();
+    unsafe { CPLSetConfigOption(key.as_ptr(), value.as_ptr()) };
+}
+
+/// Clears the collected statistics.
+pub fn reset_remote_read_stats() {
+    unsafe { VSINetworkStatsReset() };
+}
+
+/// Reports the requests and bytes GDAL has issued for remote files since
+/// statistics were enabled or last reset, across every dataset in the
+/// process. Requires GDAL 3.2 or later.
+pub fn remote_read_stats() -> Option<RemoteReadStats> {
+    let json = unsafe {
+        let c_json = VSINetworkStatsGetAsSerializedJSON(ptr::null_mut());
+        if c_json.is_null() {
+            return None;
+        }
+        let json = CStr::from_ptr(c_json).to_string_lossy().into_owned();
+        VSIFree(c_json as *mut c_void);
+        json
+    };
+    Some(parse_stats(json))
+}
+
+/// Totals the per-method counts in the top-level `methods` object of GDAL's
+/// report. The nested per-file breakdowns repeat the same counts, so only
+/// the top level is read.
+fn parse_stats(json: String) -> RemoteReadStats {
+    let (requests, bytes_downloaded) = match top_level_object(&json, "methods") {
+        Some(methods) => (
+            sum_field(methods, "count"),
+            sum_field(methods, "downloaded_bytes"),
+        ),
+        None => (0, 0),
+    };
+    RemoteReadStats {
+        requests,
+        bytes_downloaded,
+        json,
+    }
+}
+
+/// The length of the object at the start of `json`, including its braces.
+fn object_len(json: &str) -> Option<usize> {
+    let (mut depth, mut in_string, mut escaped) = (0, false, false);
+    for (i, c) in json.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Finds the object stored under `key` in the outermost JSON object.
+fn top_level_object<'a>(json: &'a str, key: &str) -> Option<&'a str> {
+    let pattern = format!("\"{}\"", key);
+    let (mut depth, mut in_string, mut escaped) = (0, false, false);
+    for (i, c) in json.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            '"' => {
+                if depth == 1 && json[i..].starts_with(&pattern) {
+                    let rest = json[i + pattern.len()..].trim_start();
+                    if let Some(rest) = rest.strip_prefix(':') {
+                        let rest = rest.trim_start();
+                        if rest.starts_with('{') {
+                            return object_len(rest).map(|len| &rest[..len]);
+                        }
+                    }
+                }
+                in_string = true;
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Sums the numbers stored under `field` anywhere in `json`.
+fn sum_field(json: &str, field: &str) -> u64 {
+    let pattern = format!("\"{}\"", field);
+    json.match_indices(&pattern)
+        .filter_map(|(i, _)| {
+            let rest = json[i + pattern.len()..].trim_start().strip_prefix(':')?;
+            let rest = rest.trim_start();
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            rest[..end].parse::<u64>().ok()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::remote::{parse_stats, RemoteCacheOptions, RemoteReadOptions};
+
+    #[test]
+    fn parse_network_stats() {
+        let json = r#"{
+            "methods": {
+                "GET": {"count": 3, "downloaded_bytes": 49152},
+                "HEAD": {"count": 1}
+            },
+            "handlers": {
+                "vsis3": {"methods": {"GET": {"count": 3, "downloaded_bytes": 49152}}}
+            }
+        }"#;
+        let stats = parse_stats(json.to_string());
+
+        assert_eq!(stats.requests, 4);
+        assert_eq!(stats.bytes_downloaded, 49152);
+    }
+
+    #[test]
+    fn remote_read_config_options() {
+        let options = RemoteReadOptions {
+            merge_consecutive_ranges: Some(true),
+            multirange: Some(false),
+            ..RemoteReadOptions::default()
+        };
+        assert_eq!(
+            options.config_options(),
+            vec![
+                (
+                    "GDAL_HTTP_MERGE_CONSECUTIVE_RANGES".to_string(),
+                    "YES".to_string()
+                ),
+                ("GDAL_HTTP_MULTIRANGE".to_string(), "SERIAL".to_string()),
+            ]
+        );
+
+        let cache = RemoteCacheOptions {
+            chunk_size: Some(65536),
+            ..RemoteCacheOptions::default()
+        };
+        assert_eq!(
+            cache.config_options(),
+            vec![("CPL_VSIL_CURL_CHUNK_SIZE".to_string(), "65536".to_string())]
+        );
+    }
+}
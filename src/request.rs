@@ -0,0 +1,369 @@
+use crate::buffer::TypedBuffer;
+use crate::config::ConfigGuard;
+use crate::dataset::TypedDataset;
+use crate::error_handler::with_error_context;
+use crate::errors::{check_cpl_err, Error, Result};
+use crate::trace::{buffer_bytes, instrument};
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+use gdal_sys::{GDALRIOResampleAlg, GDALRWFlag, GDALRasterIOEx, GDALRasterIOExtraArg};
+use std::io;
+use std::os::raw::{c_char, c_double, c_int, c_void};
+use std::ptr;
+use std::time::{Duration, Instant};
+
+/// How pixels are resampled when the output size differs from the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Resampling {
+    #[default]
+    Nearest,
+    Bilinear,
+    Cubic,
+    CubicSpline,
+    Lanczos,
+    Average,
+    Mode,
+    Gauss,
+}
+
+impl Resampling {
+    fn to_gdal(self) -> GDALRIOResampleAlg::Type {
+        match self {
+            Resampling::Nearest => GDALRIOResampleAlg::GRIORA_NearestNeighbour,
+            Resampling::Bilinear => GDALRIOResampleAlg::GRIORA_Bilinear,
+            Resampling::Cubic => GDALRIOResampleAlg::GRIORA_Cubic,
+            Resampling::CubicSpline => GDALRIOResampleAlg::GRIORA_CubicSpline,
+            Resampling::Lanczos => GDALRIOResampleAlg::GRIORA_Lanczos,
+            Resampling::Average => GDALRIOResampleAlg::GRIORA_Average,
+            Resampling::Mode => GDALRIOResampleAlg::GRIORA_Mode,
+            Resampling::Gauss => GDALRIOResampleAlg::GRIORA_Gauss,
+        }
+    }
+
+    /// The name the GDAL command-line utilities use for the method.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Resampling::Nearest => "near",
+            Resampling::Bilinear => "bilinear",
+            Resampling::Cubic => "cubic",
+            Resampling::CubicSpline => "cubicspline",
+            Resampling::Lanczos => "lanczos",
+            Resampling::Average => "average",
+            Resampling::Mode => "mode",
+            Resampling::Gauss => "gauss",
+        }
+    }
+}
+
+pub(crate) type ProgressFn<'r> = dyn FnMut(f64) -> bool + 'r;
+
+pub(crate) unsafe extern "C" fn call_progress(
+    complete: c_double,
+    _message: *const c_char,
+    data: *mut c_void,
+) -> c_int {
+    let progress = &mut *(data as *mut &mut ProgressFn<'_>);
+    progress(complete) as c_int
+}
+
+/// A time limit for a GDAL operation, enforced through its progress
+/// callback.
+pub(crate) struct Deadline {
+    end: Instant,
+    timeout: Duration,
+    expired: bool,
+}
+
+impl Deadline {
+    pub(crate) fn new(timeout: Duration) -> Deadline {
+        Deadline {
+            end: Instant::now() + timeout,
+            timeout,
+            expired: false,
+        }
+    }
+
+    /// Whether the operation may carry on, for returning from a progress
+    /// callback.
+    pub(crate) fn check(&mut self) -> bool {
+        if Instant::now() >= self.end {
+            self.expired = true;
+        }
+        !self.expired
+    }
+
+    pub(crate) fn expired(&self) -> bool {
+        self.expired
+    }
+
+    /// GDAL only calls progress callbacks between chunks of work, so this
+    /// also caps each HTTP request at the whole timeout, in case one never
+    /// returns.
+    pub(crate) fn http_config(&self) -> Vec<(String, String)> {
+        let secs = self.timeout.as_secs_f64().ceil().max(1.0);
+        vec![("GDAL_HTTP_TIMEOUT".to_string(), secs.to_string())]
+    }
+
+    /// The error for an operation cancelled by the deadline, which
+    /// `retry::is_retryable` treats as transient.
+    pub(crate) fn error(&self, operation: &str) -> Error {
+        io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!("{} timed out after {:?}", operation, self.timeout),
+        )
+        .into()
+    }
+}
+
+/// A read from a band, configured one option at a time.
+///
+/// Created by `TypedRasterBand::read_request`. The window defaults to the
+/// whole band and the output size to the window size.
+pub struct ReadRequest<'r, 'a, T: Copy + GdalType, A: Access> {
+    band: &'r TypedRasterBand<'a, T, A>,
+    window: Option<Window>,
+    out_size: Option<(usize, usize)>,
+    resampling: Resampling,
+    progress: Option<Box<ProgressFn<'r>>>,
+    timeout: Option<Duration>,
+}
+
+impl<'r, 'a, T: Copy + GdalType + GdalFrom<f64>, A: Access> ReadRequest<'r, 'a, T, A> {
+    pub fn window(mut self, window: Window) -> Self {
+        self.window = Some(window);
+        self
+    }
+
+    pub fn out_size(mut self, size: (usize, usize)) -> Self {
+        self.out_size = Some(size);
+        self
+    }
+
+    pub fn resample(mut self, resampling: Resampling) -> Self {
+        self.resampling = resampling;
+        self
+    }
+
+    /// Calls `f` with the fraction of the read completed so far. Returning
+    /// `false` cancels the read.
+    pub fn progress<F: FnMut(f64) -> bool + 'r>(mut self, f: F) -> Self {
+        self.progress = Some(Box::new(f));
+        self
+    }
+
+    /// Cancels the read with an `io::ErrorKind::TimedOut` error if it takes
+    /// longer than `timeout`, so that a stuck network read can't hang a
+    /// batch job.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    fn resolved_window(&self) -> Window {
+        self.window
+            .unwrap_or_else(|| Window::new((0, 0), self.band.size()))
+    }
+
+    /// Reads into a new buffer.
+    pub fn execute(self) -> Result<TypedBuffer<T>> {
+        let size = self.out_size.unwrap_or(self.resolved_window().size);
+        let mut buffer = TypedBuffer::filled(size, T::from(0.0));
+        self.read_into_slice(&mut buffer.data, size)?;
+        Ok(buffer)
+    }
+
+    /// Reads into `buffer`, whose size is used as the output size.
+    pub fn execute_into(self, buffer: &mut TypedBuffer<T>) -> Result<()> {
+        let size = buffer.size;
+        self.read_into_slice(&mut buffer.data, size)
+    }
+
+    /// Reads into a new buffer, along with a mask that is `false` where the
+    /// pixel is the band's nodata value. A NaN nodata value marks NaN pixels.
+    pub fn execute_masked(self) -> Result<(TypedBuffer<T>, TypedBuffer<bool>)>
+    where
+        T: PartialEq,
+    {
+        let nodata = self.band.no_data_value();
+        let buffer = self.execute()?;
+        let mask = match nodata {
+            Some(nodata) => buffer.nodata_mask(nodata).map(|is_nodata| !is_nodata),
+            None => TypedBuffer::filled(buffer.size, true),
+        };
+        Ok((buffer, mask))
+    }
+
+    fn read_into_slice(mut self, data: &mut [T], size: (usize, usize)) -> Result<()> {
+        assert_eq!(
+            size.0 * size.1,
+            data.len(),
+            "buffer length doesn't match size"
+        );
+        let window = self.resolved_window();
+        let mut deadline = self.timeout.map(Deadline::new);
+        let _guard = match &deadline {
+            Some(deadline) => Some(ConfigGuard::set(&deadline.http_config())?),
+            None => None,
+        };
+
+        let has_callback = self.progress.is_some() || deadline.is_some();
+        let mut user_progress = self.progress.take();
+        let mut callback = |complete: f64| {
+            if let Some(deadline) = &mut deadline {
+                if !deadline.check() {
+                    return false;
+                }
+            }
+            user_progress.as_mut().is_none_or(|f| f(complete))
+        };
+        let mut progress: Option<&mut ProgressFn<'_>> = if has_callback {
+            Some(&mut callback)
+        } else {
+            None
+        };
+        let (pfn_progress, progress_data) = match progress {
+            Some(ref mut f) => (
+                Some(call_progress as unsafe extern "C" fn(_, _, _) -> _),
+                f as *mut &mut ProgressFn<'_> as *mut c_void,
+            ),
+            None => (None, ptr::null_mut()),
+        };
+        let mut extra = GDALRasterIOExtraArg {
+            nVersion: 1,
+            eResampleAlg: self.resampling.to_gdal(),
+            pfnProgress: pfn_progress,
+            pProgressData: progress_data,
+            bFloatingPointWindowValidity: 0,
+            dfXOff: 0.0,
+            dfYOff: 0.0,
+            dfXSize: 0.0,
+            dfYSize: 0.0,
+        };
+
+        let band = self.band;
+        let result = with_error_context("read", Some(window), || {
+            let rv = instrument("read", window, buffer_bytes::<T>(size), || unsafe {
+                GDALRasterIOEx(
+                    band.rasterband()._c_ptr(),
+                    GDALRWFlag::GF_Read,
+                    window.offset.0 as i32,
+                    window.offset.1 as i32,
+                    window.size.0 as i32,
+                    window.size.1 as i32,
+                    data.as_mut_ptr() as *mut c_void,
+                    size.0 as i32,
+                    size.1 as i32,
+                    T::gdal_type(),
+                    0,
+                    0,
+                    &mut extra,
+                )
+            });
+            check_cpl_err(rv)
+        });
+        match deadline {
+            Some(deadline) if deadline.expired() => Err(deadline.error("read")),
+            _ => result,
+        }
+    }
+}
+
+impl<'a, T: Copy + GdalType + GdalFrom<f64>, A: Access> TypedRasterBand<'a, T, A> {
+    /// Starts building a read from this band.
+    pub fn read_request<'r>(&'r self) -> ReadRequest<'r, 'a, T, A> {
+        ReadRequest {
+            band: self,
+            window: None,
+            out_size: None,
+            resampling: Resampling::default(),
+            progress: None,
+            timeout: None,
+        }
+    }
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64>> TypedDataset<T> {
+    /// Reads `window` of band `index`, failing with an
+    /// `io::ErrorKind::TimedOut` error if it takes longer than `timeout`.
+    pub fn read_with_timeout(
+        &self,
+        index: isize,
+        window: Window,
+        timeout: Duration,
+    ) -> Result<TypedBuffer<T>> {
+        self.with_band(index, |band| {
+            band.read_request()
+                .window(window)
+                .timeout(timeout)
+                .execute()
+        })?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::request::{Deadline, Resampling};
+    use crate::retry::is_retryable;
+    use crate::typed_rasterband::TypedRasterBand;
+    use crate::window::Window;
+    use gdal::raster::dataset::Dataset;
+    use std::path::Path;
+    use std::time::Duration;
+
+    #[test]
+    fn read_request_defaults() {
+        let path = Path::new("testdata/test_u8.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u8>::from_rasterband(&band).unwrap();
+
+        let buffer = typed_band.read_request().execute().unwrap();
+        assert_eq!(buffer.size, (333, 333));
+
+        let buffer = typed_band
+            .read_request()
+            .window(Window::new((0, 0), (2, 2)))
+            .execute()
+            .unwrap();
+        assert_eq!(buffer.data, vec![152, 161, 139, 164]);
+    }
+
+    #[test]
+    fn read_request_resampled_with_progress() {
+        let path = Path::new("testdata/test_u8.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u8>::from_rasterband(&band).unwrap();
+
+        let mut last = 0.0;
+        let buffer = typed_band
+            .read_request()
+            .window(Window::new((0, 0), (2, 2)))
+            .out_size((1, 1))
+            .resample(Resampling::Average)
+            .progress(|complete| {
+                last = complete;
+                true
+            })
+            .execute()
+            .unwrap();
+        assert_eq!(buffer.data, vec![154]);
+        assert_eq!(last, 1.0);
+    }
+
+    #[test]
+    fn deadline_expires() {
+        let mut deadline = Deadline::new(Duration::from_millis(0));
+        assert!(!deadline.check());
+        assert!(deadline.expired());
+        assert!(is_retryable(&deadline.error("read")));
+
+        let mut deadline = Deadline::new(Duration::from_millis(1500));
+        assert!(deadline.check());
+        assert_eq!(
+            deadline.http_config(),
+            vec![("GDAL_HTTP_TIMEOUT".to_string(), "2".to_string())]
+        );
+    }
+}
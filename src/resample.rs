@@ -0,0 +1,95 @@
+use crate::buffer::TypedBuffer;
+use crate::typed_rasterband::GdalFrom;
+
+/// How `TypedBuffer::resample` computes new pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    Nearest,
+    Bilinear,
+}
+
+/// The source coordinate of the centre of output pixel `i`, along an axis
+/// scaled by `scale`, in units of source pixels from the first centre.
+fn source_coordinate(i: usize, scale: f64) -> f64 {
+    (i as f64 + 0.5) * scale - 0.5
+}
+
+impl<T: Copy + Into<f64> + GdalFrom<f64>> TypedBuffer<T> {
+    /// Resizes the buffer to `size` pixels, treating pixels as areas the way
+    /// GDAL does, so that the corners of the buffer stay in place.
+    ///
+    /// Bilinear results are rounded to the nearest whole number for
+    /// integer pixel types.
+    pub fn resample(&self, size: (usize, usize), method: Interpolation) -> TypedBuffer<T> {
+        let (width, height) = self.size;
+        assert!(
+            (width > 0 && height > 0) || size.0 * size.1 == 0,
+            "can't resample an empty buffer"
+        );
+        let scale_x = width as f64 / size.0.max(1) as f64;
+        let scale_y = height as f64 / size.1.max(1) as f64;
+        let integral = T::from(0.5).into() == 0.0;
+
+        let mut data = Vec::with_capacity(size.0 * size.1);
+        for y in 0..size.1 {
+            let sy = source_coordinate(y, scale_y);
+            for x in 0..size.0 {
+                let sx = source_coordinate(x, scale_x);
+                let value = match method {
+                    Interpolation::Nearest => {
+                        let nx = ((sx + 0.5).floor().max(0.0) as usize).min(width - 1);
+                        let ny = ((sy + 0.5).floor().max(0.0) as usize).min(height - 1);
+                        self.get(nx, ny)
+                    }
+                    Interpolation::Bilinear => {
+                        let sx = sx.max(0.0).min((width - 1) as f64);
+                        let sy = sy.max(0.0).min((height - 1) as f64);
+                        let (x0, y0) = (sx.floor() as usize, sy.floor() as usize);
+                        let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+                        let (fx, fy) = (sx - x0 as f64, sy - y0 as f64);
+
+                        let top = lerp(self.get(x0, y0).into(), self.get(x1, y0).into(), fx);
+                        let bottom = lerp(self.get(x0, y1).into(), self.get(x1, y1).into(), fx);
+                        let v = lerp(top, bottom, fy);
+                        T::from(if integral { v.round() } else { v })
+                    }
+                };
+                data.push(value);
+            }
+        }
+        TypedBuffer::new(size, data)
+    }
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::resample::Interpolation;
+
+    #[test]
+    fn resample_nearest() {
+        let buffer = TypedBuffer::new((2, 2), vec![1u8, 2, 3, 4]);
+
+        let up = buffer.resample((4, 4), Interpolation::Nearest);
+        assert_eq!(up.row(0), &[1, 1, 2, 2]);
+        assert_eq!(up.row(3), &[3, 3, 4, 4]);
+
+        let down = up.resample((2, 2), Interpolation::Nearest);
+        assert_eq!(down, buffer);
+    }
+
+    #[test]
+    fn resample_bilinear() {
+        let buffer = TypedBuffer::new((2, 1), vec![0.0f32, 4.0]);
+        let up = buffer.resample((4, 1), Interpolation::Bilinear);
+        assert_eq!(up.data, vec![0.0, 1.0, 3.0, 4.0]);
+
+        let buffer = TypedBuffer::new((2, 1), vec![0u16, 3]);
+        let up = buffer.resample((4, 1), Interpolation::Bilinear);
+        assert_eq!(up.data, vec![0, 1, 2, 3]);
+    }
+}
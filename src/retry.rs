@@ -0,0 +1,164 @@
+use crate::buffer::TypedBuffer;
+use crate::dataset::TypedDataset;
+use crate::errors::{Error, Result};
+use crate::typed_rasterband::GdalFrom;
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+/// Fragments of GDAL and curl error messages that mark a failure as
+/// transient.
+const TRANSIENT_MESSAGES: &[&str] = &[
+    "HTTP error code : 429",
+    "HTTP error code : 500",
+    "HTTP error code : 502",
+    "HTTP error code : 503",
+    "HTTP error code : 504",
+    "timed out",
+    "Timeout",
+    "Connection reset",
+    "Couldn't connect",
+    "Could not resolve host",
+    "SSL connect error",
+];
+
+fn transient_message(msg: &str) -> bool {
+    TRANSIENT_MESSAGES.iter().any(|m| msg.contains(m))
+}
+
+/// Whether `err` looks like a transient network failure worth retrying, such
+/// as a 5xx response, throttling or a timeout.
+pub fn is_retryable(err: &Error) -> bool {
+    match err {
+        Error::Cpl { msg, .. } => transient_message(msg),
+        Error::Gdal(e) => transient_message(&e.to_string()),
+        Error::Context {
+            messages, source, ..
+        } => messages.iter().any(|m| transient_message(&m.msg)) || is_retryable(source),
+        Error::Io(e) => matches!(
+            e.kind(),
+            io::ErrorKind::TimedOut
+                | io::ErrorKind::Interrupted
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+        ),
+        _ => false,
+    }
+}
+
+/// How to retry operations that fail with transient network errors.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// The total number of attempts, including the first.
+    pub max_attempts: u32,
+    /// The wait after the first failure, which grows by `multiplier` after
+    /// each later failure up to `max_backoff`.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+    /// Decides which errors are retried.
+    pub retryable: fn(&Error) -> bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 4,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            retryable: is_retryable,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The wait before retry `retry`, counting from zero.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let secs = self.initial_backoff.as_secs_f64() * self.multiplier.powi(retry as i32);
+        Duration::from_secs_f64(secs.min(self.max_backoff.as_secs_f64()))
+    }
+
+    /// Calls `f` until it succeeds, fails with an error that isn't
+    /// retryable, or runs out of attempts, returning the last result.
+    pub fn run<R, F>(&self, mut f: F) -> Result<R>
+    where
+        F: FnMut() -> Result<R>,
+    {
+        let mut retry = 0;
+        loop {
+            match f() {
+                Err(e) if retry + 1 < self.max_attempts && (self.retryable)(&e) => {
+                    thread::sleep(self.backoff(retry));
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64>> TypedDataset<T> {
+    /// Reads `window` of band `index`, retrying transient failures according
+    /// to `policy`.
+    pub fn read_with_retry(
+        &self,
+        index: isize,
+        window: Window,
+        policy: &RetryPolicy,
+    ) -> Result<TypedBuffer<T>> {
+        policy.run(|| self.read(index, window))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::Error;
+    use crate::retry::RetryPolicy;
+    use std::io;
+    use std::time::Duration;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            initial_backoff: Duration::from_millis(0),
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[test]
+    fn retry_transient_errors() {
+        let mut attempts = 0;
+        let result = policy().run(|| {
+            attempts += 1;
+            if attempts < 3 {
+                Err(Error::Cpl {
+                    class: 3,
+                    number: 11,
+                    msg: "HTTP error code : 503".to_string(),
+                })
+            } else {
+                Ok(attempts)
+            }
+        });
+
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[test]
+    fn fail_fast_on_permanent_errors() {
+        let mut attempts = 0;
+        let result: Result<(), _> = policy().run(|| {
+            attempts += 1;
+            Err(io::Error::from(io::ErrorKind::NotFound).into())
+        });
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+        assert_eq!(
+            RetryPolicy::default().backoff(10),
+            RetryPolicy::default().max_backoff
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use crate::buffer::TypedBuffer;
+
+/// A buffer stored as runs of repeated values in row-major order, which is
+/// far smaller than the pixels themselves for mostly uniform masks and
+/// classifications.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RleBuffer<T> {
+    pub size: (usize, usize),
+    /// Each value with the number of times it repeats.
+    pub runs: Vec<(T, u32)>,
+}
+
+impl<T: Copy> RleBuffer<T> {
+    /// The number of pixels the runs cover.
+    pub fn pixel_count(&self) -> usize {
+        self.runs.iter().map(|&(_, n)| n as usize).sum()
+    }
+
+    /// Iterates over the pixels in row-major order.
+    pub fn pixels<'a>(&'a self) -> impl Iterator<Item = T> + 'a {
+        self.runs
+            .iter()
+            .flat_map(|&(v, n)| std::iter::repeat_n(v, n as usize))
+    }
+}
+
+impl<T: Copy + Eq> TypedBuffer<T> {
+    /// Run-length encodes the buffer.
+    pub fn to_rle(&self) -> RleBuffer<T> {
+        let mut runs: Vec<(T, u32)> = Vec::new();
+        for &v in &self.data {
+            match runs.last_mut() {
+                Some((last, n)) if *last == v && *n < u32::MAX => *n += 1,
+                _ => runs.push((v, 1)),
+            }
+        }
+        RleBuffer {
+            size: self.size,
+            runs,
+        }
+    }
+}
+
+impl<T: Copy> TypedBuffer<T> {
+    /// Expands a run-length encoded buffer.
+    pub fn from_rle(rle: &RleBuffer<T>) -> TypedBuffer<T> {
+        TypedBuffer::new(rle.size, rle.pixels().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+
+    #[test]
+    fn rle_round_trip() {
+        let buffer = TypedBuffer::new((4, 3), vec![0u8, 0, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0]);
+        let rle = buffer.to_rle();
+
+        assert_eq!(rle.runs, vec![(0, 5), (1, 2), (0, 5)]);
+        assert_eq!(rle.pixel_count(), 12);
+        assert_eq!(TypedBuffer::from_rle(&rle), buffer);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rle_wrong_length() {
+        let buffer = TypedBuffer::new((2, 2), vec![3i16; 4]);
+        let mut rle = buffer.to_rle();
+        rle.runs[0].1 = 3;
+        TypedBuffer::from_rle(&rle);
+    }
+}
@@ -0,0 +1,182 @@
+use crate::bitmask::PixelMask;
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::rng::SplitMix64;
+use crate::transform;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use gdal::raster::types::GdalType;
+use std::collections::HashMap;
+
+/// Which pixels `sample_random` draws from.
+pub enum SamplingStrategy<'m> {
+    /// Any valid pixel, with equal probability.
+    Uniform,
+    /// Up to `n` pixels of each distinct value, treating values as class
+    /// labels.
+    StratifiedByClass,
+    /// Only valid pixels that are set in the mask, which must be the size
+    /// of the band.
+    Masked(&'m dyn PixelMask),
+}
+
+/// A pixel drawn by `sample_random`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample<T> {
+    /// The map coordinates of the pixel centre.
+    pub x: f64,
+    pub y: f64,
+    pub col: usize,
+    pub row: usize,
+    pub value: T,
+}
+
+/// Keeps a uniform random selection of up to `capacity` of the items
+/// offered to it, using Vitter's algorithm R.
+struct Reservoir<T> {
+    capacity: usize,
+    seen: usize,
+    items: Vec<T>,
+}
+
+impl<T> Reservoir<T> {
+    fn new(capacity: usize) -> Reservoir<T> {
+        Reservoir {
+            capacity,
+            seen: 0,
+            items: Vec::new(),
+        }
+    }
+
+    fn offer(&mut self, item: T, rng: &mut SplitMix64) {
+        self.seen += 1;
+        if self.items.len() < self.capacity {
+            self.items.push(item);
+        } else {
+            let j = (rng.next_f64() * self.seen as f64) as usize;
+            if j < self.capacity {
+                self.items[j] = item;
+            }
+        }
+    }
+}
+
+impl<'a, T, A: Access> TypedRasterBand<'a, T, A>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Into<f64> + PartialEq,
+{
+    /// Draws `n` valid pixels at random following `strategy`, or all of them
+    /// if there are fewer. The band is read one block at a time, and the
+    /// same `seed` always draws the same pixels.
+    ///
+    /// Samples are ordered by row and column, and stratified samples by
+    /// class first.
+    pub fn sample_random(
+        &self,
+        n: usize,
+        strategy: &SamplingStrategy,
+        seed: u64,
+    ) -> Result<Vec<Sample<T>>> {
+        if let SamplingStrategy::Masked(mask) = strategy {
+            assert_eq!(mask.size(), self.size(), "mask and band sizes differ");
+        }
+        let gt = self.owning_dataset().geo_transform()?;
+        let no_data = self.no_data_value();
+        let mut rng = SplitMix64(seed);
+        let mut reservoir = Reservoir::new(n);
+        let mut classes: HashMap<u64, Reservoir<(usize, usize, T)>> = HashMap::new();
+
+        for window in self.block_windows() {
+            let buffer: TypedBuffer<T> = self.read(window.offset, window.size, window.size)?.into();
+            let (x0, y0) = (window.offset.0 as usize, window.offset.1 as usize);
+            for (x, y, value) in buffer.pixels() {
+                let (col, row) = (x0 + x, y0 + y);
+                if Some(value) == no_data || value.into().is_nan() {
+                    continue;
+                }
+                match strategy {
+                    SamplingStrategy::Uniform => reservoir.offer((col, row, value), &mut rng),
+                    SamplingStrategy::Masked(mask) => {
+                        if mask.is_set(col, row) {
+                            reservoir.offer((col, row, value), &mut rng);
+                        }
+                    }
+                    SamplingStrategy::StratifiedByClass => classes
+                        .entry(value.into().to_bits())
+                        .or_insert_with(|| Reservoir::new(n))
+                        .offer((col, row, value), &mut rng),
+                }
+            }
+        }
+
+        let mut picked = reservoir.items;
+        picked.sort_by_key(|&(col, row, _)| (row, col));
+        let mut labels: Vec<(u64, Reservoir<_>)> = classes.into_iter().collect();
+        labels.sort_by(|a, b| f64::from_bits(a.0).total_cmp(&f64::from_bits(b.0)));
+        for (_, class) in labels {
+            let mut items = class.items;
+            items.sort_by_key(|&(col, row, _)| (row, col));
+            picked.extend(items);
+        }
+
+        Ok(picked
+            .into_iter()
+            .map(|(col, row, value)| {
+                let (x, y) = transform::apply(&gt, col as f64 + 0.5, row as f64 + 0.5);
+                Sample {
+                    x,
+                    y,
+                    col,
+                    row,
+                    value,
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::rng::SplitMix64;
+    use crate::sampling::{Reservoir, SamplingStrategy};
+    use crate::typed_rasterband::TypedRasterBand;
+    use gdal::raster::dataset::Dataset;
+    use std::path::Path;
+
+    #[test]
+    fn reservoir_keeps_capacity() {
+        let mut rng = SplitMix64(7);
+        let mut reservoir = Reservoir::new(10);
+        for i in 0..1000 {
+            reservoir.offer(i, &mut rng);
+        }
+        assert_eq!(reservoir.seen, 1000);
+        assert_eq!(reservoir.items.len(), 10);
+        assert!(reservoir.items.iter().any(|&i| i >= 10));
+    }
+
+    #[test]
+    fn sample_band() {
+        let path = Path::new("testdata/test_u8.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u8>::from_rasterband(&band).unwrap();
+
+        let samples = typed_band
+            .sample_random(50, &SamplingStrategy::Uniform, 1)
+            .unwrap();
+        assert_eq!(samples.len(), 50);
+        let again = typed_band
+            .sample_random(50, &SamplingStrategy::Uniform, 1)
+            .unwrap();
+        assert_eq!(samples, again);
+
+        let mut mask = TypedBuffer::filled((333, 333), false);
+        mask.data[0] = true;
+        let masked = typed_band
+            .sample_random(5, &SamplingStrategy::Masked(&mask), 1)
+            .unwrap();
+        assert_eq!(masked.len(), 1);
+        assert_eq!((masked[0].col, masked[0].row, masked[0].value), (0, 0, 152));
+    }
+}
@@ -0,0 +1,278 @@
+use crate::buffer::TypedBuffer;
+use crate::config::{open_dataset, ConfigGuard, DatasetOpenOptions};
+use crate::dataset::TypedDataset;
+use crate::errors::Result;
+use crate::trace::{buffer_bytes, instrument};
+use crate::typed_rasterband::{GdalFrom, TypedRasterBand};
+use crate::window::Window;
+use gdal::metadata::Metadata;
+use gdal::raster::dataset::Dataset;
+use gdal::raster::types::GdalType;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::thread;
+
+thread_local! {
+    static DATASETS: RefCell<HashMap<(PathBuf, DatasetOpenOptions), Rc<Dataset>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Returns this thread's handle to the dataset at `path` opened with
+/// `options`, opening it on first use.
+pub(crate) fn thread_dataset(path: &Path, options: &DatasetOpenOptions) -> Result<Rc<Dataset>> {
+    DATASETS.with(|datasets| {
+        let key = (path.to_path_buf(), options.clone());
+        if let Some(dataset) = datasets.borrow().get(&key) {
+            return Ok(dataset.clone());
+        }
+        let dataset = Rc::new(open_dataset(path, options)?);
+        datasets.borrow_mut().insert(key, dataset.clone());
+        Ok(dataset)
+    })
+}
+
+/// The number of dataset handles cached on the current thread.
+#[cfg(all(test, feature = "rayon"))]
+pub(crate) fn thread_dataset_count() -> usize {
+    DATASETS.with(|datasets| datasets.borrow().len())
+}
+
+/// Closes the dataset handles opened by `SharedTypedBand` and the parallel
+/// block methods on the current thread. Handles still in use are closed once their last user finishes.
+pub fn close_thread_datasets() {
+    DATASETS.with(|datasets| datasets.borrow_mut().clear());
+}
+
+/// A typed band that can be shared between threads.
+///
+/// Rather than holding a GDAL handle, which isn't thread-safe, the band is
+/// identified by its path and index, and each thread lazily opens and caches
+/// its own dataset handle.
+#[derive(Debug)]
+pub struct SharedTypedBand<T> {
+    path: PathBuf,
+    band_index: isize,
+    options: DatasetOpenOptions,
+    pixel_type: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for SharedTypedBand<T> {
+    fn clone(&self) -> SharedTypedBand<T> {
+        SharedTypedBand {
+            path: self.path.clone(),
+            band_index: self.band_index,
+            options: self.options.clone(),
+            pixel_type: PhantomData,
+        }
+    }
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64>> SharedTypedBand<T> {
+    /// Creates a shared band, checking on the current thread that the band
+    /// exists and has pixel type `T`.
+    pub fn new<P: Into<PathBuf>>(path: P, band_index: isize) -> Result<SharedTypedBand<T>> {
+        SharedTypedBand::new_with(path, band_index, DatasetOpenOptions::default())
+    }
+
+    /// Creates a shared band whose dataset is opened on each thread as
+    /// described by `options`, with its config options set around every
+    /// read.
+    pub fn new_with<P: Into<PathBuf>>(
+        path: P,
+        band_index: isize,
+        options: DatasetOpenOptions,
+    ) -> Result<SharedTypedBand<T>> {
+        let shared = SharedTypedBand {
+            path: path.into(),
+            band_index,
+            options,
+            pixel_type: PhantomData,
+        };
+        shared.with_band(|_| ())?;
+        Ok(shared)
+    }
+
+    /// Creates a shared band referring to the same file and band as `band`,
+    /// opened on each thread with `options`. A band doesn't know how its
+    /// dataset was opened, so pass the dataset's `reopen_options()`, or use
+    /// `TypedDataset::shared_band`.
+    pub fn from_band(
+        band: &TypedRasterBand<T>,
+        options: DatasetOpenOptions,
+    ) -> Result<SharedTypedBand<T>> {
+        let path = band.owning_dataset().description()?;
+        SharedTypedBand::new_with(path, band.band_index(), options)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn band_index(&self) -> isize {
+        self.band_index
+    }
+
+    /// How the band's dataset is opened on each thread.
+    pub fn options(&self) -> &DatasetOpenOptions {
+        &self.options
+    }
+
+    /// Calls `f` with the band, opened through this thread's dataset handle.
+    pub fn with_band<R, F>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&TypedRasterBand<T>) -> R,
+    {
+        let _guard = ConfigGuard::set(&self.options.config)?;
+        let dataset = thread_dataset(&self.path, &self.options)?;
+        let band = dataset.rasterband(self.band_index)?;
+        let typed_band = TypedRasterBand::from_rasterband(&band)?;
+        Ok(f(&typed_band))
+    }
+
+    pub fn read(&self, window: Window, size: (usize, usize)) -> Result<TypedBuffer<T>> {
+        let buffer = instrument("read", window, buffer_bytes::<T>(size), || {
+            self.with_band(|band| band.read(window.offset, window.size, size))
+        })??;
+        Ok(buffer.into())
+    }
+
+    pub fn size(&self) -> Result<(usize, usize)> {
+        self.with_band(|band| band.size())
+    }
+
+    pub fn no_data_value(&self) -> Result<Option<T>> {
+        self.with_band(|band| band.no_data_value())
+    }
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64>> TypedDataset<T> {
+    /// A shared handle to band `index`, reopened on each thread with the
+    /// dataset's drivers, open options and config options.
+    pub fn shared_band(&self, index: isize) -> Result<SharedTypedBand<T>> {
+        let path = self.dataset().description()?;
+        SharedTypedBand::new_with(path, index, self.reopen_options())
+    }
+}
+
+impl<T> TypedDataset<T>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Send + 'static,
+{
+    /// Reads the same window from each of `bands` concurrently, one thread
+    /// per band, returning the buffers in the order the bands were given.
+    ///
+    /// Each thread reads through its own dataset handle, so the dataset must
+    /// have been opened from a path that can be re-opened.
+    pub fn read_bands_parallel(
+        &self,
+        bands: &[isize],
+        window: Window,
+    ) -> Result<Vec<TypedBuffer<T>>> {
+        let path = PathBuf::from(self.dataset().description()?);
+        let handles: Vec<_> = bands
+            .iter()
+            .map(|&band_index| {
+                let shared = SharedTypedBand::<T> {
+                    path: path.clone(),
+                    band_index,
+                    options: self.reopen_options(),
+                    pixel_type: PhantomData,
+                };
+                thread::spawn(move || shared.read(window, window.size))
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("band read panicked"))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::DatasetOpenOptions;
+    use crate::dataset::TypedDataset;
+    use crate::shared::SharedTypedBand;
+    use crate::testing::gtiff_with_overviews;
+    use crate::window::Window;
+    use std::path::Path;
+    use std::thread;
+
+    #[test]
+    fn shared_band_across_threads() {
+        let shared = SharedTypedBand::<u16>::new("testdata/test_u16.tif", 1).unwrap();
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let shared = shared.clone();
+                thread::spawn(move || shared.read(Window::new((100, 100), (2, 1)), (2, 1)))
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap().unwrap().data, vec![6656, 6764]);
+        }
+    }
+
+    #[test]
+    fn read_bands_in_parallel() {
+        let ds = TypedDataset::<u8>::open(Path::new("testdata/test_u8.tif")).unwrap();
+        let buffers = ds
+            .read_bands_parallel(&[1, 1], Window::new((0, 0), (2, 1)))
+            .unwrap();
+
+        assert_eq!(buffers.len(), 2);
+        assert_eq!(buffers[1].data, vec![152, 161]);
+    }
+
+    #[test]
+    fn shared_band_incorrect_type() {
+        assert!(SharedTypedBand::<u8>::new("testdata/test_u16.tif", 1).is_err());
+    }
+
+    #[test]
+    fn shared_band_with_options() {
+        let png_only = DatasetOpenOptions {
+            allowed_drivers: vec!["PNG".to_string()],
+            ..Default::default()
+        };
+        assert!(SharedTypedBand::<u8>::new_with("testdata/test_u8.tif", 1, png_only).is_err());
+
+        let path = Path::new("testdata/test_u8.tif");
+        let gtiff = DatasetOpenOptions {
+            allowed_drivers: vec!["GTiff".to_string()],
+            ..Default::default()
+        };
+        let ds = TypedDataset::<u8>::open_with(path, &gtiff).unwrap();
+        assert_eq!(ds.reopen_options().allowed_drivers, gtiff.allowed_drivers);
+    }
+
+    #[test]
+    fn shared_band_keeps_overview_level() {
+        let file = gtiff_with_overviews("shared_overview_level.tif");
+        let path = Path::new(file.path());
+        let overview = DatasetOpenOptions {
+            open_options: vec![("OVERVIEW_LEVEL".to_string(), "0".to_string())],
+            ..Default::default()
+        };
+        let ds = TypedDataset::<u8>::open_with(path, &overview).unwrap();
+        assert_eq!(ds.size(), (167, 167));
+
+        let shared = ds.shared_band(1).unwrap();
+        let size = thread::spawn(move || shared.size()).join().unwrap();
+        assert_eq!(size.unwrap(), (167, 167));
+
+        let from_band = ds
+            .with_band(1, |band| {
+                SharedTypedBand::from_band(band, ds.reopen_options())
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(from_band.size().unwrap(), (167, 167));
+        let full = SharedTypedBand::<u8>::new(path, 1).unwrap();
+        assert_eq!(full.size().unwrap(), (333, 333));
+    }
+}
@@ -0,0 +1,87 @@
+use crate::errors::Result;
+use crate::window::Window;
+use gdal::raster::dataset::Dataset;
+use std::fs;
+use std::path::Path;
+
+/// Where an exported image lies on the ground.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Georeference {
+    pub geo_transform: [f64; 6],
+    /// The spatial reference as WKT, if known.
+    pub projection: Option<String>,
+}
+
+impl Georeference {
+    /// The georeference of `window` of `dataset`.
+    pub fn from_dataset(dataset: &Dataset, window: Window) -> Result<Georeference> {
+        let projection = dataset.projection();
+        Ok(Georeference {
+            geo_transform: window.geo_transform(&dataset.geo_transform()?),
+            projection: if projection.is_empty() {
+                None
+            } else {
+                Some(projection)
+            },
+        })
+    }
+
+    /// The contents of a world file, which gives the centre of the top-left
+    /// pixel rather than its corner.
+    pub fn world_file(&self) -> String {
+        let gt = &self.geo_transform;
+        let lines = [
+            gt[1],
+            gt[4],
+            gt[2],
+            gt[5],
+            gt[0] + gt[1] / 2.0 + gt[2] / 2.0,
+            gt[3] + gt[4] / 2.0 + gt[5] / 2.0,
+        ];
+        lines.iter().map(|v| format!("{}\n", v)).collect()
+    }
+
+    /// Writes a `.wld` world file next to `image_path`, and a `.prj` file if
+    /// the projection is known.
+    pub fn write_sidecars<P: AsRef<Path>>(&self, image_path: P) -> Result<()> {
+        let image_path = image_path.as_ref();
+        fs::write(image_path.with_extension("wld"), self.world_file())?;
+        if let Some(projection) = &self.projection {
+            fs::write(image_path.with_extension("prj"), projection)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sidecar::Georeference;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn world_file_contents() {
+        let georef = Georeference {
+            geo_transform: [1000.0, 10.0, 0.0, 2000.0, 0.0, -10.0],
+            projection: None,
+        };
+        assert_eq!(georef.world_file(), "10\n0\n0\n-10\n1005\n1995\n");
+    }
+
+    #[test]
+    fn write_sidecar_files() {
+        let dir = env::temp_dir().join("gdal_typed_rasterband_sidecars");
+        fs::create_dir_all(&dir).unwrap();
+        let georef = Georeference {
+            geo_transform: [0.0, 1.0, 0.0, 0.0, 0.0, -1.0],
+            projection: Some("GEOGCS[\"WGS 84\"]".to_string()),
+        };
+        georef.write_sidecars(dir.join("tile.png")).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.join("tile.prj")).unwrap(),
+            "GEOGCS[\"WGS 84\"]"
+        );
+        assert!(dir.join("tile.wld").exists());
+    }
+}
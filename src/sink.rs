@@ -0,0 +1,159 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::{Error, Result};
+use crate::typed_rasterband::{GdalFrom, ReadWrite, TypedRasterBand};
+use crate::window::Window;
+use crate::writer::BandWriter;
+use gdal::raster::types::GdalType;
+
+/// Anything that pixels can be written to a window at a time.
+///
+/// Windows must lie within the sink and match the size of the buffer;
+/// writes that don't fail with `Error::Bounds`.
+pub trait RasterSink<T: Copy> {
+    fn size(&self) -> (usize, usize);
+
+    fn write_window(&mut self, window: Window, buffer: &TypedBuffer<T>) -> Result<()>;
+
+    /// Makes every write so far visible to readers of the sink.
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Records `operation` in the sink's processing history, if it keeps
+    /// one. Operations that write to a sink call this once they finish.
+    fn record_history(&self, _operation: &str, _parameters: &[(&str, String)]) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn check_window(
+    window: Window,
+    buffer_size: (usize, usize),
+    sink_size: (usize, usize),
+) -> Result<()> {
+    if window.size != buffer_size {
+        return Err(Error::Bounds(format!(
+            "buffer size {:?} doesn't match window size {:?}",
+            buffer_size, window.size
+        )));
+    }
+    if window.pixel_count() != 0 && window.intersection(&Window::full(sink_size)) != Some(window) {
+        return Err(Error::Bounds(format!(
+            "write window at {:?} of size {:?} extends outside the sink of size {:?}",
+            window.offset, window.size, sink_size
+        )));
+    }
+    Ok(())
+}
+
+impl<'a, T: Copy + GdalType + GdalFrom<f64>> RasterSink<T> for TypedRasterBand<'a, T, ReadWrite> {
+    fn size(&self) -> (usize, usize) {
+        TypedRasterBand::size(self)
+    }
+
+    fn write_window(&mut self, window: Window, buffer: &TypedBuffer<T>) -> Result<()> {
+        check_window(window, buffer.size, TypedRasterBand::size(self))?;
+        self.write_slice(window, &buffer.data, buffer.size)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.flush_cache()
+    }
+
+    fn record_history(&self, operation: &str, parameters: &[(&str, String)]) -> Result<()> {
+        TypedRasterBand::record_history(self, operation, parameters)
+    }
+}
+
+impl<'a, 'b, T: Copy + GdalType + GdalFrom<f64>> RasterSink<T> for BandWriter<'a, 'b, T> {
+    fn size(&self) -> (usize, usize) {
+        self.band().size()
+    }
+
+    fn write_window(&mut self, window: Window, buffer: &TypedBuffer<T>) -> Result<()> {
+        check_window(window, buffer.size, self.band().size())?;
+        self.write(window, buffer);
+        Ok(())
+    }
+
+    /// Unlike `BandWriter::flush`, records nothing; the operation writing
+    /// to the sink records itself.
+    fn flush(&mut self) -> Result<()> {
+        self.write_blocks().map(|_| ())
+    }
+
+    fn record_history(&self, operation: &str, parameters: &[(&str, String)]) -> Result<()> {
+        self.band().record_history(operation, parameters)
+    }
+}
+
+impl<T: Copy> RasterSink<T> for TypedBuffer<T> {
+    fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    fn write_window(&mut self, window: Window, buffer: &TypedBuffer<T>) -> Result<()> {
+        check_window(window, buffer.size, self.size)?;
+        let (x0, y0) = (window.offset.0 as usize, window.offset.1 as usize);
+        for y in 0..window.size.1 {
+            let start = (y0 + y) * self.size.0 + x0;
+            self.data[start..start + window.size.0].copy_from_slice(buffer.row(y));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::errors::Error;
+    use crate::sink::RasterSink;
+    use crate::typed_rasterband::{ReadWrite, TypedRasterBand};
+    use crate::window::Window;
+    use gdal::raster::driver::Driver;
+
+    fn write_square<S: RasterSink<u8>>(sink: &mut S) {
+        let square = TypedBuffer::filled((2, 2), 9);
+        sink.write_window(Window::new((1, 1), (2, 2)), &square)
+            .unwrap();
+        sink.flush().unwrap();
+    }
+
+    #[test]
+    fn write_to_buffer() {
+        let mut buffer = TypedBuffer::filled((4, 3), 0u8);
+        write_square(&mut buffer);
+        assert_eq!(buffer.data, vec![0, 0, 0, 0, 0, 9, 9, 0, 0, 9, 9, 0]);
+    }
+
+    #[test]
+    fn reject_bad_windows() {
+        let mut buffer = TypedBuffer::filled((4, 3), 0u8);
+        let square = TypedBuffer::filled((2, 2), 9);
+        assert!(matches!(
+            buffer.write_window(Window::new((3, 0), (2, 2)), &square),
+            Err(Error::Bounds(_))
+        ));
+        assert!(matches!(
+            buffer.write_window(Window::new((0, 0), (1, 2)), &square),
+            Err(Error::Bounds(_))
+        ));
+        assert_eq!(buffer.data, vec![0; 12]);
+    }
+
+    #[test]
+    fn write_to_bands() {
+        let driver = Driver::get("MEM").unwrap();
+        let ds = driver
+            .create_with_band_type::<u8>("", 4, 3, 1)
+            .expect("failed to create dataset");
+        let band = ds.rasterband(1).unwrap();
+        let mut typed_band =
+            TypedRasterBand::<u8, ReadWrite>::from_writable_rasterband(&band).unwrap();
+        write_square(&mut typed_band);
+        write_square(&mut typed_band.writer());
+
+        let row = typed_band.read((0, 1), (4, 1), (4, 1)).unwrap();
+        assert_eq!(row.data, vec![0, 9, 9, 0]);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::timeseries::RasterTimeSeries;
+use crate::typed_rasterband::GdalFrom;
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How `interpolate_gaps` fills missing observations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GapFill {
+    /// Interpolates linearly in time between the nearest valid observations
+    /// on either side. Gaps at the start or end of the series are left.
+    Linear,
+    /// Repeats the last valid observation. Gaps at the start are left.
+    ForwardFill,
+}
+
+/// A window of a time series read into memory as `f64` frames, with
+/// missing observations stored as NaN.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeStack {
+    pub timestamps: Vec<SystemTime>,
+    pub window: Window,
+    pub frames: Vec<TypedBuffer<f64>>,
+}
+
+impl<T> RasterTimeSeries<T>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Into<f64>,
+{
+    /// Reads `window` at every time step, replacing nodata with NaN.
+    pub fn read_stack(&self, window: Window) -> Result<TimeStack> {
+        let mut frames = Vec::with_capacity(self.len());
+        for band in self.bands() {
+            let nodata = band.no_data_value()?.map(Into::into);
+            let frame = band.read(window, window.size)?.map(|v| {
+                let v: f64 = v.into();
+                if Some(v) == nodata {
+                    f64::NAN
+                } else {
+                    v
+                }
+            });
+            frames.push(frame);
+        }
+        Ok(TimeStack {
+            timestamps: self.timestamps(),
+            window,
+            frames,
+        })
+    }
+}
+
+fn seconds(t: SystemTime) -> f64 {
+    match t.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs_f64(),
+        Err(e) => -e.duration().as_secs_f64(),
+    }
+}
+
+/// Fills the NaNs in `values`, observed at `times`.
+pub fn interpolate_series(values: &mut [f64], times: &[f64], method: GapFill) {
+    let mut last_valid = None;
+    for i in 0..values.len() {
+        if !values[i].is_nan() {
+            if let (GapFill::Linear, Some(prev)) = (method, last_valid) {
+                for j in prev + 1..i {
+                    let f = (times[j] - times[prev]) / (times[i] - times[prev]);
+                    values[j] = values[prev] + f * (values[i] - values[prev]);
+                }
+            }
+            last_valid = Some(i);
+        } else if let (GapFill::ForwardFill, Some(prev)) = (method, last_valid) {
+            values[i] = values[prev];
+        }
+    }
+}
+
+/// Solves `a x = b` by Gaussian elimination with partial pivoting.
+fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Vec<f64> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| a[i][col].abs().partial_cmp(&a[j][col].abs()).unwrap())
+            .unwrap();
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        for row in col + 1..n {
+            let f = a[row][col] / a[col][col];
+            let (above, below) = a.split_at_mut(row);
+            for (x, p) in below[0][col..].iter_mut().zip(&above[col][col..]) {
+                *x -= f * p;
+            }
+            b[row] -= f * b[col];
+        }
+    }
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    x
+}
+
+/// The weights that evaluate, at offset `at` from the window's centre, the
+/// least-squares polynomial of degree `order` through `window` samples.
+fn savitzky_golay_weights(window: usize, order: usize, at: f64) -> Vec<f64> {
+    let m = (window / 2) as f64;
+    let powers = |x: f64| (0..=order).map(|p| x.powi(p as i32)).collect::<Vec<_>>();
+    let rows: Vec<Vec<f64>> = (0..window).map(|i| powers(i as f64 - m)).collect();
+
+    let mut normal = vec![vec![0.0; order + 1]; order + 1];
+    for row in &rows {
+        for i in 0..=order {
+            for j in 0..=order {
+                normal[i][j] += row[i] * row[j];
+            }
+        }
+    }
+    let y = solve(normal, powers(at));
+    rows.iter()
+        .map(|row| row.iter().zip(&y).map(|(a, b)| a * b).sum())
+        .collect()
+}
+
+/// Smooths evenly spaced `values` with a Savitzky-Golay filter. Samples
+/// within half a window of either end are evaluated from the polynomial fit
+/// to the first or last full window. NaNs propagate to every output whose
+/// window contains them, so fill gaps first.
+pub fn savitzky_golay_series(values: &[f64], window: usize, order: usize) -> Vec<f64> {
+    assert!(window % 2 == 1, "window length must be odd");
+    assert!(
+        order < window,
+        "polynomial order must be less than the window length"
+    );
+    let n = values.len();
+    if n < window {
+        return values.to_vec();
+    }
+
+    let m = window / 2;
+    let weights: Vec<_> = (0..window)
+        .map(|k| savitzky_golay_weights(window, order, k as f64 - m as f64))
+        .collect();
+    (0..n)
+        .map(|t| {
+            let start = t.saturating_sub(m).min(n - window);
+            let w = &weights[t - start];
+            values[start..start + window]
+                .iter()
+                .zip(w)
+                .map(|(v, w)| v * w)
+                .sum()
+        })
+        .collect()
+}
+
+impl TimeStack {
+    /// Applies `f` to each pixel's series, in place.
+    fn map_series<F: FnMut(&mut Vec<f64>)>(&mut self, mut f: F) {
+        let mut series = Vec::with_capacity(self.frames.len());
+        for i in 0..self.window.pixel_count() {
+            series.clear();
+            series.extend(self.frames.iter().map(|frame| frame.data[i]));
+            f(&mut series);
+            for (frame, &v) in self.frames.iter_mut().zip(&series) {
+                frame.data[i] = v;
+            }
+        }
+    }
+
+    /// Fills missing observations along the time axis of every pixel.
+    pub fn interpolate_gaps(&mut self, method: GapFill) {
+        let times: Vec<f64> = self.timestamps.iter().map(|&t| seconds(t)).collect();
+        self.map_series(|series| interpolate_series(series, &times, method));
+    }
+
+    /// Smooths every pixel's series with a Savitzky-Golay filter of odd
+    /// length `window` and polynomial degree `order`, treating time steps
+    /// as evenly spaced.
+    pub fn savitzky_golay(&self, window: usize, order: usize) -> TimeStack {
+        let mut smoothed = self.clone();
+        smoothed.map_series(|series| *series = savitzky_golay_series(series, window, order));
+        smoothed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::smoothing::{interpolate_series, savitzky_golay_series, GapFill};
+
+    #[test]
+    fn fill_gaps() {
+        let times = [0.0, 1.0, 3.0, 4.0];
+        let mut values = [f64::NAN, 2.0, f64::NAN, 8.0];
+        interpolate_series(&mut values, &times, GapFill::Linear);
+        assert!(values[0].is_nan());
+        assert_eq!(&values[1..], &[2.0, 6.0, 8.0]);
+
+        let mut values = [1.0, f64::NAN, f64::NAN, 4.0];
+        interpolate_series(&mut values, &times, GapFill::ForwardFill);
+        assert_eq!(values, [1.0, 1.0, 1.0, 4.0]);
+    }
+
+    #[test]
+    fn savitzky_golay_preserves_polynomials() {
+        // A quadratic filter reproduces a quadratic exactly, edges included.
+        let values: Vec<f64> = (0..9).map(|x| (x * x) as f64 - 3.0 * x as f64).collect();
+        let smoothed = savitzky_golay_series(&values, 5, 2);
+        for (a, b) in values.iter().zip(&smoothed) {
+            assert!((a - b).abs() < 1e-9);
+        }
+    }
+}
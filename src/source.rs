@@ -0,0 +1,174 @@
+use crate::buffer::TypedBuffer;
+use crate::derived::DerivedBand;
+use crate::errors::Result;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use crate::view::BandView;
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+
+/// Anything that pixels can be read from a window at a time.
+///
+/// Windows are clipped to the source, so a read near an edge may return a
+/// smaller buffer than requested.
+pub trait RasterSource<T: Copy> {
+    fn size(&self) -> (usize, usize);
+
+    fn read_window(&self, window: Window) -> Result<TypedBuffer<T>>;
+
+    fn no_data_value(&self) -> Option<T> {
+        None
+    }
+
+    fn geo_transform(&self) -> Option<[f64; 6]> {
+        None
+    }
+
+    /// The WKT of the source's CRS, if it has one.
+    fn projection(&self) -> Option<String> {
+        None
+    }
+
+    /// Reads the whole source.
+    fn read_full(&self) -> Result<TypedBuffer<T>> {
+        self.read_window(Window::full(self.size()))
+    }
+}
+
+/// An empty buffer, for reads that don't overlap a source.
+fn empty<T: Copy>() -> TypedBuffer<T> {
+    TypedBuffer::new((0, 0), Vec::new())
+}
+
+impl<'a, T: Copy + GdalType + GdalFrom<f64>, A: Access> RasterSource<T>
+    for TypedRasterBand<'a, T, A>
+{
+    fn size(&self) -> (usize, usize) {
+        TypedRasterBand::size(self)
+    }
+
+    fn read_window(&self, window: Window) -> Result<TypedBuffer<T>> {
+        match window.intersection(&Window::full(TypedRasterBand::size(self))) {
+            Some(window) => Ok(self.read(window.offset, window.size, window.size)?.into()),
+            None => Ok(empty()),
+        }
+    }
+
+    fn no_data_value(&self) -> Option<T> {
+        TypedRasterBand::no_data_value(self)
+    }
+
+    fn geo_transform(&self) -> Option<[f64; 6]> {
+        self.owning_dataset().geo_transform().ok()
+    }
+
+    fn projection(&self) -> Option<String> {
+        let wkt = self.owning_dataset().projection();
+        if wkt.is_empty() {
+            None
+        } else {
+            Some(wkt)
+        }
+    }
+}
+
+impl<'a, 'b, T: Copy + GdalType + GdalFrom<f64>, A: Access> RasterSource<T>
+    for BandView<'a, 'b, T, A>
+{
+    fn size(&self) -> (usize, usize) {
+        BandView::size(self)
+    }
+
+    fn read_window(&self, window: Window) -> Result<TypedBuffer<T>> {
+        self.read(window)
+    }
+
+    fn no_data_value(&self) -> Option<T> {
+        BandView::no_data_value(self)
+    }
+
+    fn geo_transform(&self) -> Option<[f64; 6]> {
+        BandView::geo_transform(self)
+    }
+
+    fn projection(&self) -> Option<String> {
+        BandView::projection(self)
+    }
+}
+
+impl<'f, T: Copy> RasterSource<T> for DerivedBand<'f, T> {
+    fn size(&self) -> (usize, usize) {
+        DerivedBand::size(self)
+    }
+
+    fn read_window(&self, window: Window) -> Result<TypedBuffer<T>> {
+        self.read(window)
+    }
+
+    fn no_data_value(&self) -> Option<T> {
+        DerivedBand::no_data_value(self)
+    }
+
+    fn geo_transform(&self) -> Option<[f64; 6]> {
+        DerivedBand::geo_transform(self)
+    }
+}
+
+/// Buffers have no nodata value or geotransform of their own.
+impl<T: Copy> RasterSource<T> for TypedBuffer<T> {
+    fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    fn read_window(&self, window: Window) -> Result<TypedBuffer<T>> {
+        let window = match window.intersection(&Window::full(self.size)) {
+            Some(window) => window,
+            None => return Ok(empty()),
+        };
+        let (x0, y0) = (window.offset.0 as usize, window.offset.1 as usize);
+        let mut data = Vec::with_capacity(window.pixel_count());
+        for y in y0..y0 + window.size.1 {
+            data.extend_from_slice(&self.row(y)[x0..x0 + window.size.0]);
+        }
+        Ok(TypedBuffer::new(window.size, data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::derived::DerivedBand;
+    use crate::source::RasterSource;
+    use crate::typed_rasterband::TypedRasterBand;
+    use crate::window::Window;
+    use gdal::raster::dataset::Dataset;
+    use std::path::Path;
+
+    fn corner<S: RasterSource<u8>>(source: &S) -> Vec<u8> {
+        source
+            .read_window(Window::new((-1, -1), (3, 3)))
+            .unwrap()
+            .data
+    }
+
+    #[test]
+    fn in_memory_sources() {
+        let buffer = TypedBuffer::new((3, 2), vec![1u8, 2, 3, 4, 5, 6]);
+        assert_eq!(corner(&buffer), vec![1, 2, 4, 5]);
+        assert_eq!(buffer.read_full().unwrap(), buffer);
+
+        let derived = DerivedBand::new((3, 2), |window| buffer.read_window(window));
+        assert_eq!(corner(&derived), vec![1, 2, 4, 5]);
+    }
+
+    #[test]
+    fn band_sources() {
+        let path = Path::new("testdata/test_u8.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u8>::from_rasterband(&band).unwrap();
+
+        assert_eq!(corner(&typed_band), vec![152, 161, 139, 164]);
+        let view = typed_band.view(Window::new((1, 0), (10, 10)));
+        assert_eq!(corner(&view), vec![161, 164]);
+    }
+}
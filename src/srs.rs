@@ -0,0 +1,95 @@
+#[cfg(feature = "stac")]
+use gdal_sys::{
+    OCTDestroyCoordinateTransformation, OCTNewCoordinateTransformation, OCTTransform,
+    OSRAutoIdentifyEPSG, OSRGetAuthorityCode, OSRGetAuthorityName,
+};
+use gdal_sys::{
+    OGRSpatialReferenceH, OSRDestroySpatialReference, OSRGetInvFlattening, OSRGetSemiMajor,
+    OSRIsGeographic, OSRIsSame, OSRNewSpatialReference, OSRSetFromUserInput,
+};
+#[cfg(feature = "stac")]
+use std::ffi::CStr;
+use std::ffi::CString;
+use std::ptr;
+
+/// An OGR spatial reference, destroyed on drop.
+pub(crate) struct SpatialRef(OGRSpatialReferenceH);
+
+impl SpatialRef {
+    pub(crate) fn from_user_input(definition: &str) -> Option<SpatialRef> {
+        let c_definition = CString::new(definition).ok()?;
+        let srs = SpatialRef(unsafe { OSRNewSpatialReference(ptr::null()) });
+        if srs.0.is_null() || unsafe { OSRSetFromUserInput(srs.0, c_definition.as_ptr()) } != 0 {
+            return None;
+        }
+        Some(srs)
+    }
+
+    #[cfg(feature = "stac")]
+    pub(crate) fn epsg_code(&self) -> Option<i64> {
+        unsafe {
+            OSRAutoIdentifyEPSG(self.0);
+            let name = OSRGetAuthorityName(self.0, ptr::null());
+            let code = OSRGetAuthorityCode(self.0, ptr::null());
+            if name.is_null() || code.is_null() || CStr::from_ptr(name).to_bytes() != b"EPSG" {
+                return None;
+            }
+            CStr::from_ptr(code).to_str().ok()?.parse().ok()
+        }
+    }
+
+    #[cfg(feature = "stac")]
+    /// Transforms `points` to longitude and latitude in place.
+    pub(crate) fn to_lon_lat(&self, points: &mut [(f64, f64)]) -> bool {
+        let target = match SpatialRef::from_user_input("CRS:84") {
+            Some(target) => target,
+            None => return false,
+        };
+        let (mut xs, mut ys): (Vec<f64>, Vec<f64>) = points.iter().cloned().unzip();
+        let ok = unsafe {
+            let ct = OCTNewCoordinateTransformation(self.0, target.0);
+            if ct.is_null() {
+                return false;
+            }
+            let ok = OCTTransform(
+                ct,
+                points.len() as i32,
+                xs.as_mut_ptr(),
+                ys.as_mut_ptr(),
+                ptr::null_mut(),
+            ) != 0;
+            OCTDestroyCoordinateTransformation(ct);
+            ok
+        };
+        for (point, xy) in points.iter_mut().zip(xs.into_iter().zip(ys)) {
+            *point = xy;
+        }
+        ok
+    }
+
+    pub(crate) fn is_same(&self, other: &SpatialRef) -> bool {
+        unsafe { OSRIsSame(self.0, other.0) != 0 }
+    }
+
+    pub(crate) fn is_geographic(&self) -> bool {
+        unsafe { OSRIsGeographic(self.0) != 0 }
+    }
+
+    /// The semi-major axis and inverse flattening of the ellipsoid, with an
+    /// inverse flattening of 0 for a sphere.
+    pub(crate) fn ellipsoid(&self) -> (f64, f64) {
+        let mut err = 0;
+        unsafe {
+            (
+                OSRGetSemiMajor(self.0, &mut err),
+                OSRGetInvFlattening(self.0, &mut err),
+            )
+        }
+    }
+}
+
+impl Drop for SpatialRef {
+    fn drop(&mut self) {
+        unsafe { OSRDestroySpatialReference(self.0) }
+    }
+}
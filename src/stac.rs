@@ -0,0 +1,322 @@
+use crate::dataset::TypedDataset;
+use crate::errors::Result;
+use crate::srs::SpatialRef;
+use crate::transform;
+use crate::translate::gdal_type_name;
+use crate::typed_rasterband::GdalFrom;
+use gdal::metadata::Metadata;
+use gdal::raster::types::GdalType;
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Maps an asset href onto the GDAL path that reads it.
+pub fn gdal_path(href: &str) -> String {
+    let schemes = [
+        ("s3://", "/vsis3/"),
+        ("gs://", "/vsigs/"),
+        ("az://", "/vsiaz/"),
+        ("http://", "/vsicurl/http://"),
+        ("https://", "/vsicurl/https://"),
+    ];
+    for (scheme, prefix) in schemes.iter() {
+        if let Some(rest) = href.strip_prefix(scheme) {
+            return format!("{}{}", prefix, rest);
+        }
+    }
+    href.to_string()
+}
+
+/// Resolves a relative href against the item's `self` link, if it has one.
+fn resolve_href(item: &Value, href: &str) -> String {
+    if href.contains("://") || href.starts_with('/') {
+        return href.to_string();
+    }
+    let self_href = item
+        .get("links")
+        .and_then(Value::as_array)
+        .and_then(|links| {
+            links
+                .iter()
+                .find(|link| link.get("rel").and_then(Value::as_str) == Some("self"))
+        })
+        .and_then(|link| link.get("href"))
+        .and_then(Value::as_str);
+    match self_href.and_then(|base| base.rfind('/').map(|i| &base[..=i])) {
+        Some(base) => format!("{}{}", base, href.trim_start_matches("./")),
+        None => href.to_string(),
+    }
+}
+
+/// Finds the href of asset `asset_key` in a STAC item, or of one of its
+/// alternate locations (from the alternate-assets extension) if `alternate`
+/// names one. Relative hrefs are resolved against the item's `self` link.
+pub fn asset_href(item: &Value, asset_key: &str, alternate: Option<&str>) -> io::Result<String> {
+    let asset = item
+        .get("assets")
+        .and_then(|assets| assets.get(asset_key))
+        .ok_or_else(|| invalid(format!("STAC item has no asset {:?}", asset_key)))?;
+    let asset = match alternate {
+        Some(name) => asset
+            .get("alternate")
+            .and_then(|alternates| alternates.get(name))
+            .ok_or_else(|| invalid(format!("asset {:?} has no alternate {:?}", asset_key, name)))?,
+        None => asset,
+    };
+    let href = asset
+        .get("href")
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid(format!("asset {:?} has no href", asset_key)))?;
+    Ok(resolve_href(item, href))
+}
+
+fn open_href<T: Copy + GdalType + GdalFrom<f64>>(href: &str) -> Result<TypedDataset<T>> {
+    let mut config = Vec::new();
+    if href.contains("://") && href.contains('?') {
+        // Signed URLs carry their credentials in the query string. They
+        // usually only authorize GET, and listing the parent directory would
+        // fail, so both are turned off.
+        config.push(("CPL_VSIL_CURL_USE_HEAD".to_string(), "NO".to_string()));
+        config.push((
+            "GDAL_DISABLE_READDIR_ON_OPEN".to_string(),
+            "EMPTY_DIR".to_string(),
+        ));
+    }
+    TypedDataset::open_with_config(Path::new(&gdal_path(href)), &[], config)
+}
+
+/// Opens asset `asset_key` of the STAC item in `item_json`.
+pub fn open_stac_asset<T>(item_json: &str, asset_key: &str) -> Result<TypedDataset<T>>
+where
+    T: Copy + GdalType + GdalFrom<f64>,
+{
+    let item: Value = serde_json::from_str(item_json).map_err(io::Error::from)?;
+    open_href(&asset_href(&item, asset_key, None)?)
+}
+
+/// Opens the alternate location `alternate` of asset `asset_key`, such as
+/// an `s3` alternate to an asset whose main href is HTTPS.
+pub fn open_stac_asset_alternate<T>(
+    item_json: &str,
+    asset_key: &str,
+    alternate: &str,
+) -> Result<TypedDataset<T>>
+where
+    T: Copy + GdalType + GdalFrom<f64>,
+{
+    let item: Value = serde_json::from_str(item_json).map_err(io::Error::from)?;
+    open_href(&asset_href(&item, asset_key, Some(alternate))?)
+}
+
+const PROJECTION_EXTENSION: &str =
+    "https://stac-extensions.github.io/projection/v1.1.0/schema.json";
+const RASTER_EXTENSION: &str = "https://stac-extensions.github.io/raster/v1.1.0/schema.json";
+const FILE_EXTENSION: &str = "https://stac-extensions.github.io/file/v2.1.0/schema.json";
+
+/// What `to_stac_item` can't learn from the dataset itself.
+#[derive(Debug, Clone)]
+pub struct StacItemOptions {
+    pub id: String,
+    /// The href of the data asset, or the dataset's own path if `None`.
+    pub asset_href: Option<String>,
+    pub asset_key: String,
+    pub media_type: String,
+    /// An RFC 3339 timestamp.
+    pub datetime: Option<String>,
+    /// Include band statistics, computing them if GDAL hasn't stored them.
+    pub statistics: bool,
+    /// Let GDAL use overviews or a subsample for statistics.
+    pub approx_statistics: bool,
+}
+
+impl StacItemOptions {
+    pub fn new(id: &str) -> StacItemOptions {
+        StacItemOptions {
+            id: id.to_string(),
+            asset_href: None,
+            asset_key: "data".to_string(),
+            media_type: "image/tiff; application=geotiff".to_string(),
+            datetime: None,
+            statistics: true,
+            approx_statistics: true,
+        }
+    }
+}
+
+/// The STAC raster extension's name for `T`.
+fn stac_data_type<T: GdalType>() -> String {
+    match gdal_type_name::<T>().to_lowercase().as_str() {
+        "byte" => "uint8".to_string(),
+        name => name.to_string(),
+    }
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64>> TypedDataset<T> {
+    /// Describes the dataset as a STAC item with a single asset, filling in
+    /// the projection, raster and file extensions.
+    ///
+    /// The geometry is the dataset's extent in longitude and latitude, and
+    /// is null if the dataset has no geotransform or its projection can't be
+    /// transformed.
+    pub fn to_stac_item(&self, options: &StacItemOptions) -> Result<Value> {
+        let (width, height) = self.size();
+        let path = self.dataset().description()?;
+        let wkt = self.projection();
+        let srs = if wkt.is_empty() {
+            None
+        } else {
+            SpatialRef::from_user_input(&wkt)
+        };
+
+        let mut properties = Map::new();
+        properties.insert("datetime".to_string(), json!(options.datetime));
+        properties.insert("proj:shape".to_string(), json!([height, width]));
+        match srs.as_ref().and_then(SpatialRef::epsg_code) {
+            Some(code) => properties.insert("proj:epsg".to_string(), json!(code)),
+            None if wkt.is_empty() => properties.insert("proj:epsg".to_string(), Value::Null),
+            None => properties.insert("proj:wkt2".to_string(), json!(wkt)),
+        };
+
+        let mut geometry = Value::Null;
+        let mut bbox = None;
+        if let Some(gt) = self.geo_transform() {
+            properties.insert(
+                "proj:transform".to_string(),
+                json!([gt[1], gt[2], gt[0], gt[4], gt[5], gt[3]]),
+            );
+            let (w, h) = (width as f64, height as f64);
+            let mut corners: Vec<(f64, f64)> = [(0.0, 0.0), (w, 0.0), (w, h), (0.0, h)]
+                .iter()
+                .map(|&(x, y)| transform::apply(&gt, x, y))
+                .collect();
+            if srs.as_ref().is_some_and(|srs| srs.to_lon_lat(&mut corners)) {
+                let xs = corners.iter().map(|c| c.0);
+                let ys = corners.iter().map(|c| c.1);
+                bbox = Some(json!([
+                    xs.clone().fold(f64::INFINITY, f64::min),
+                    ys.clone().fold(f64::INFINITY, f64::min),
+                    xs.fold(f64::NEG_INFINITY, f64::max),
+                    ys.fold(f64::NEG_INFINITY, f64::max),
+                ]));
+                corners.push(corners[0]);
+                let ring: Vec<[f64; 2]> = corners.iter().map(|&(x, y)| [x, y]).collect();
+                geometry = json!({"type": "Polygon", "coordinates": [ring]});
+            }
+        }
+
+        let mut bands = Vec::new();
+        for index in 1..=self.band_count() {
+            let band = self.with_band(index, |band| -> Result<Value> {
+                let mut entry = Map::new();
+                entry.insert("data_type".to_string(), json!(stac_data_type::<T>()));
+                if let Some(nodata) = band.rasterband().no_data_value() {
+                    entry.insert("nodata".to_string(), json!(nodata));
+                }
+                if let Some(scale) = band.scale() {
+                    entry.insert("scale".to_string(), json!(scale));
+                }
+                if let Some(offset) = band.offset() {
+                    entry.insert("offset".to_string(), json!(offset));
+                }
+                if options.statistics {
+                    let stats = band.statistics(options.approx_statistics)?;
+                    entry.insert(
+                        "statistics".to_string(),
+                        json!({
+                            "minimum": stats.min,
+                            "maximum": stats.max,
+                            "mean": stats.mean,
+                            "stddev": stats.std_dev,
+                        }),
+                    );
+                }
+                Ok(Value::Object(entry))
+            })??;
+            bands.push(band);
+        }
+
+        let mut asset = Map::new();
+        asset.insert(
+            "href".to_string(),
+            json!(options.asset_href.clone().unwrap_or_else(|| path.clone())),
+        );
+        asset.insert("type".to_string(), json!(options.media_type));
+        asset.insert("roles".to_string(), json!(["data"]));
+        asset.insert("raster:bands".to_string(), Value::Array(bands));
+        if let Ok(metadata) = fs::metadata(&path) {
+            asset.insert("file:size".to_string(), json!(metadata.len()));
+        }
+        let mut assets = Map::new();
+        assets.insert(options.asset_key.clone(), Value::Object(asset));
+
+        let mut item = json!({
+            "type": "Feature",
+            "stac_version": "1.0.0",
+            "stac_extensions": [PROJECTION_EXTENSION, RASTER_EXTENSION, FILE_EXTENSION],
+            "id": options.id,
+            "geometry": geometry,
+            "properties": Value::Object(properties),
+            "links": [],
+            "assets": Value::Object(assets),
+        });
+        if let (Some(bbox), Some(item)) = (bbox, item.as_object_mut()) {
+            item.insert("bbox".to_string(), bbox);
+        }
+        Ok(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dataset::TypedDataset;
+    use crate::stac::{asset_href, gdal_path, StacItemOptions};
+    use serde_json::Value;
+    use std::path::Path;
+
+    const ITEM: &str = r#"{
+        "type": "Feature",
+        "links": [{"rel": "self", "href": "https://example.com/items/a/item.json"}],
+        "assets": {
+            "red": {
+                "href": "./B04.tif",
+                "alternate": {"s3": {"href": "s3://bucket/a/B04.tif"}}
+            }
+        }
+    }"#;
+
+    #[test]
+    fn resolve_asset_hrefs() {
+        let item: Value = serde_json::from_str(ITEM).unwrap();
+
+        let href = asset_href(&item, "red", None).unwrap();
+        assert_eq!(href, "https://example.com/items/a/B04.tif");
+        assert_eq!(
+            gdal_path(&href),
+            "/vsicurl/https://example.com/items/a/B04.tif"
+        );
+
+        let href = asset_href(&item, "red", Some("s3")).unwrap();
+        assert_eq!(gdal_path(&href), "/vsis3/bucket/a/B04.tif");
+        assert!(asset_href(&item, "nir", None).is_err());
+    }
+
+    #[test]
+    fn describe_dataset_as_item() {
+        let ds = TypedDataset::<u8>::open(Path::new("testdata/test_u8.tif")).unwrap();
+        let item = ds.to_stac_item(&StacItemOptions::new("test_u8")).unwrap();
+
+        assert_eq!(item["id"], "test_u8");
+        assert_eq!(item["properties"]["proj:shape"][0], 333);
+        let asset = &item["assets"]["data"];
+        assert_eq!(asset["href"], "testdata/test_u8.tif");
+        assert!(asset["file:size"].as_u64().unwrap() > 0);
+        let band = &asset["raster:bands"][0];
+        assert_eq!(band["data_type"], "uint8");
+        assert!(band["statistics"]["maximum"].as_f64().unwrap() >= 152.0);
+    }
+}
@@ -0,0 +1,186 @@
+use crate::errors::{check_cpl_err, Result};
+use crate::reduce::CompensatedSum;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+use gdal_sys::{GDALGetRasterStatistics, GDALSetDefaultHistogramEx, GDALSetRasterStatistics};
+
+/// Summary statistics of a band, as computed (or cached) by GDAL.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Statistics {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+impl<'a, T: Copy + GdalType + GdalFrom<f64>, A: Access> TypedRasterBand<'a, T, A> {
+    /// Returns band statistics, computing them if GDAL doesn't already have
+    /// them stored. With `approx_ok`, GDAL may use overviews or a subsample.
+    pub fn statistics(&self, approx_ok: bool) -> Result<Statistics> {
+        let mut stats = Statistics {
+            min: 0.0,
+            max: 0.0,
+            mean: 0.0,
+            std_dev: 0.0,
+        };
+        let rv = unsafe {
+            GDALGetRasterStatistics(
+                self.rasterband()._c_ptr(),
+                approx_ok as i32,
+                1,
+                &mut stats.min,
+                &mut stats.max,
+                &mut stats.mean,
+                &mut stats.std_dev,
+            )
+        };
+        check_cpl_err(rv)?;
+        Ok(stats)
+    }
+
+    /// Stores `stats` with the band as `STATISTICS_*` metadata, so that
+    /// tools such as QGIS can use them instead of scanning the band.
+    ///
+    /// GDAL saves them in the file when the dataset is flushed or closed,
+    /// or in a `.aux.xml` sidecar if the format can't hold them or the
+    /// dataset is read-only.
+    pub fn persist_statistics(&self, stats: &Statistics) -> Result<()> {
+        check_cpl_err(unsafe {
+            GDALSetRasterStatistics(
+                self.rasterband()._c_ptr(),
+                stats.min,
+                stats.max,
+                stats.mean,
+                stats.std_dev,
+            )
+        })
+    }
+
+    /// Stores an equal-width histogram of `counts.len()` buckets spanning
+    /// `min..max` as the band's default histogram, saved like
+    /// `persist_statistics`.
+    pub fn persist_histogram(&self, min: f64, max: f64, counts: &[u64]) -> Result<()> {
+        let mut counts = counts.to_vec();
+        check_cpl_err(unsafe {
+            GDALSetDefaultHistogramEx(
+                self.rasterband()._c_ptr(),
+                min,
+                max,
+                counts.len() as i32,
+                counts.as_mut_ptr(),
+            )
+        })
+    }
+}
+
+impl<'a, T, A: Access> TypedRasterBand<'a, T, A>
+where
+    T: Copy + GdalType + GdalFrom<f64> + PartialEq + Into<f64>,
+{
+    /// Computes statistics of every valid pixel in one pass, with
+    /// compensated sums so that large float bands don't lose precision.
+    /// Nodata and NaN pixels are skipped; a band with no valid pixels has
+    /// NaN statistics.
+    ///
+    /// Deviations are summed from the first valid pixel rather than zero,
+    /// which keeps the variance accurate when it's small next to the mean.
+    pub fn exact_statistics(&self) -> Result<Statistics> {
+        let init = (
+            None,
+            0usize,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            CompensatedSum::new(),
+            CompensatedSum::new(),
+        );
+        let (shift, count, min, max, sum, sum_sq) = self.reduce(
+            Window::full(self.size()),
+            init,
+            |(shift, count, min, max, mut sum, mut sum_sq), v| {
+                let v: f64 = v.into();
+                if v.is_nan() {
+                    return (shift, count, min, max, sum, sum_sq);
+                }
+                let shift = shift.unwrap_or(v);
+                sum.add(v - shift);
+                sum_sq.add((v - shift) * (v - shift));
+                (Some(shift), count + 1, min.min(v), max.max(v), sum, sum_sq)
+            },
+        )?;
+        let (shift, n) = match shift {
+            Some(shift) => (shift, count as f64),
+            None => {
+                return Ok(Statistics {
+                    min: f64::NAN,
+                    max: f64::NAN,
+                    mean: f64::NAN,
+                    std_dev: f64::NAN,
+                })
+            }
+        };
+        let mean_offset = sum.value() / n;
+        let variance = (sum_sq.value() / n - mean_offset * mean_offset).max(0.0);
+        Ok(Statistics {
+            min,
+            max,
+            mean: shift + mean_offset,
+            std_dev: variance.sqrt(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::statistics::Statistics;
+    use crate::typed_rasterband::TypedRasterBand;
+    use gdal::metadata::Metadata;
+    use gdal::raster::dataset::Dataset;
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn band_statistics() {
+        let path = Path::new("testdata/test_u16.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u16>::from_rasterband(&band).unwrap();
+
+        let stats = typed_band.statistics(false).unwrap();
+        assert_eq!(stats.min, 5959.0);
+        assert_eq!(stats.max, 33558.0);
+
+        let exact = typed_band.exact_statistics().unwrap();
+        assert_eq!((exact.min, exact.max), (stats.min, stats.max));
+        assert!((exact.mean - stats.mean).abs() < 1e-6);
+        assert!((exact.std_dev - stats.std_dev).abs() < 1e-6);
+    }
+
+    #[test]
+    fn persist_statistics_to_sidecar() {
+        let path = env::temp_dir().join("gdal_typed_rasterband_stats.tif");
+        fs::copy("testdata/test_u16.tif", &path).unwrap();
+        let _ = fs::remove_file(path.with_extension("tif.aux.xml"));
+        {
+            let ds = Dataset::open(&path).unwrap();
+            let band = ds.rasterband(1).unwrap();
+            let typed_band = TypedRasterBand::<u16>::from_rasterband(&band).unwrap();
+            let stats = Statistics {
+                min: 1.0,
+                max: 2.0,
+                mean: 1.5,
+                std_dev: 0.5,
+            };
+            typed_band.persist_statistics(&stats).unwrap();
+            typed_band.persist_histogram(1.0, 2.0, &[3, 4]).unwrap();
+        }
+
+        let ds = Dataset::open(&path).unwrap();
+        let band = ds.rasterband(1).unwrap();
+        assert_eq!(
+            band.metadata_item("STATISTICS_MEAN", "").as_deref(),
+            Some("1.5")
+        );
+    }
+}
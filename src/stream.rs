@@ -0,0 +1,59 @@
+use crate::async_read::AsyncTypedBand;
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::typed_rasterband::GdalFrom;
+use crate::window::Window;
+use futures::stream::{self, Stream, StreamExt};
+use gdal::raster::types::GdalType;
+
+impl<T> AsyncTypedBand<T>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Send + 'static,
+{
+    /// Streams the natural blocks of the band in row-major order.
+    ///
+    /// Up to `read_ahead` blocks are read ahead of the consumer. Reads stop
+    /// being issued while the consumer is busy, so a slow sink applies
+    /// backpressure rather than letting decoded blocks pile up in memory.
+    pub async fn block_stream(
+        &self,
+        read_ahead: usize,
+    ) -> Result<impl Stream<Item = Result<(Window, TypedBuffer<T>)>>> {
+        assert!(read_ahead > 0, "read-ahead depth must be nonzero");
+
+        let shared = self.band().clone();
+        let windows = tokio::task::spawn_blocking(move || shared.with_band(|b| b.block_windows()))
+            .await
+            .map_err(std::io::Error::other)??;
+
+        let band = self.clone();
+        Ok(stream::iter(windows)
+            .map(move |window| {
+                let band = band.clone();
+                async move {
+                    let buffer = band.read_async(window, window.size).await?;
+                    Ok((window, buffer))
+                }
+            })
+            .buffered(read_ahead))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::async_read::AsyncTypedBand;
+    use crate::shared::SharedTypedBand;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn stream_band_blocks() {
+        let shared = SharedTypedBand::<u8>::new("testdata/test_u8.tif", 1).unwrap();
+        let band = AsyncTypedBand::new(shared, 4);
+
+        let blocks: Vec<_> = band.block_stream(2).await.unwrap().collect().await;
+        assert_eq!(blocks.len(), 14);
+        let (window, buffer) = blocks[0].as_ref().unwrap();
+        assert_eq!(window.offset, (0, 0));
+        assert_eq!(buffer.get(0, 0), 152);
+    }
+}
@@ -0,0 +1,124 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use gdal::raster::types::GdalType;
+
+/// Pixel types that stretched buffers can be displayed as.
+pub trait DisplayType: Copy + GdalFrom<f64> {
+    /// The brightest value.
+    const MAX: f64;
+}
+
+impl DisplayType for u8 {
+    const MAX: f64 = 255.0;
+}
+
+impl DisplayType for u16 {
+    const MAX: f64 = 65535.0;
+}
+
+impl<T: Copy + Into<f64> + PartialEq> TypedBuffer<T> {
+    /// The pixels as `f64`, with nodata and NaN pixels as `None`.
+    fn valid_values(&self, nodata: Option<T>) -> Vec<Option<f64>> {
+        self.data
+            .iter()
+            .map(|&v| {
+                let f = v.into();
+                if Some(v) == nodata || f.is_nan() {
+                    None
+                } else {
+                    Some(f)
+                }
+            })
+            .collect()
+    }
+
+    /// Spreads pixels evenly over the output range by mapping each through
+    /// the cumulative histogram of the buffer. Nodata pixels become 0.
+    pub fn equalize_histogram<U: DisplayType>(&self, nodata: Option<T>) -> TypedBuffer<U> {
+        let values = self.valid_values(nodata);
+        let mut sorted: Vec<f64> = values.iter().filter_map(|&v| v).collect();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        // The darkest value maps to 0, as in the usual formulation.
+        let lowest = sorted
+            .first()
+            .map_or(0, |&min| sorted.partition_point(|&s| s <= min));
+        let range = (sorted.len() - lowest) as f64;
+        let data = values
+            .iter()
+            .map(|v| match v {
+                Some(v) if range > 0.0 => {
+                    let rank = sorted.partition_point(|s| s <= v) - lowest;
+                    U::from((rank as f64 / range * U::MAX).round())
+                }
+                _ => U::from(0.0),
+            })
+            .collect();
+        TypedBuffer::new(self.size, data)
+    }
+
+    /// Rescales pixels from the buffer's extremes to the output range,
+    /// raising each to the power of `1 / gamma`. A gamma above 1 brightens
+    /// the darker pixels. Nodata pixels become 0.
+    pub fn gamma<U: DisplayType>(&self, gamma: f64, nodata: Option<T>) -> TypedBuffer<U> {
+        assert!(gamma > 0.0, "gamma must be positive");
+        let values = self.valid_values(nodata);
+        let (min, max) = values
+            .iter()
+            .filter_map(|&v| v)
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                (lo.min(v), hi.max(v))
+            });
+        let range = max - min;
+        let data = values
+            .iter()
+            .map(|v| match v {
+                Some(v) if range > 0.0 => {
+                    U::from((((v - min) / range).powf(1.0 / gamma) * U::MAX).round())
+                }
+                _ => U::from(0.0),
+            })
+            .collect();
+        TypedBuffer::new(self.size, data)
+    }
+}
+
+impl<'a, T, A: Access> TypedRasterBand<'a, T, A>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Into<f64> + PartialEq,
+{
+    /// Reads the band and equalizes its histogram, ignoring nodata.
+    pub fn equalize_histogram<U: DisplayType>(&self) -> Result<TypedBuffer<U>> {
+        let buffer: TypedBuffer<T> = self.read_band()?.into();
+        Ok(buffer.equalize_histogram(self.no_data_value()))
+    }
+
+    /// Reads the band and applies `gamma` to it, ignoring nodata.
+    pub fn gamma<U: DisplayType>(&self, gamma: f64) -> Result<TypedBuffer<U>> {
+        let buffer: TypedBuffer<T> = self.read_band()?.into();
+        Ok(buffer.gamma(gamma, self.no_data_value()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+
+    #[test]
+    fn equalize_buffer() {
+        let buffer = TypedBuffer::new((3, 2), vec![10u16, 10, 11, 12, 500, 0]);
+        let equalized = buffer.equalize_histogram::<u8>(Some(0));
+
+        assert_eq!(equalized.data, vec![0, 0, 85, 170, 255, 0]);
+    }
+
+    #[test]
+    fn gamma_buffer() {
+        let buffer = TypedBuffer::new((3, 1), vec![0.0f32, 0.25, 1.0]);
+
+        assert_eq!(buffer.gamma::<u8>(1.0, None).data, vec![0, 64, 255]);
+        assert_eq!(buffer.gamma::<u8>(2.0, None).data, vec![0, 128, 255]);
+        assert_eq!(buffer.gamma::<u16>(0.5, None).data[1], 4096);
+    }
+}
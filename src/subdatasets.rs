@@ -0,0 +1,75 @@
+use crate::config::DatasetOpenOptions;
+use crate::dataset::TypedDataset;
+use crate::errors::Result;
+use crate::typed_rasterband::GdalFrom;
+use gdal::metadata::Metadata;
+use gdal::raster::dataset::Dataset;
+use gdal::raster::types::GdalType;
+use std::path::Path;
+
+/// A subdataset of a container such as a NetCDF, HDF5 or GRIB file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subdataset {
+    /// The name to open the subdataset by, e.g. `NETCDF:"file.nc":ndvi`.
+    pub name: String,
+    pub description: String,
+}
+
+/// Lists the subdatasets of `dataset`, in the order the driver reports them.
+pub fn subdatasets(dataset: &Dataset) -> Vec<Subdataset> {
+    let mut subdatasets = Vec::new();
+    for n in 1.. {
+        let name = match dataset.metadata_item(&format!("SUBDATASET_{}_NAME", n), "SUBDATASETS") {
+            Some(name) => name,
+            None => break,
+        };
+        let description = dataset
+            .metadata_item(&format!("SUBDATASET_{}_DESC", n), "SUBDATASETS")
+            .unwrap_or_default();
+        subdatasets.push(Subdataset { name, description });
+    }
+    subdatasets
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64>> TypedDataset<T> {
+    pub fn subdatasets(&self) -> Vec<Subdataset> {
+        subdatasets(self.dataset())
+    }
+
+    /// Opens a subdataset by name, as listed by `subdatasets`.
+    pub fn open_subdataset(name: &str) -> Result<TypedDataset<T>> {
+        TypedDataset::open_subdataset_with(name, &DatasetOpenOptions::default())
+    }
+
+    /// Opens a subdataset by name, as described by `options`.
+    pub fn open_subdataset_with(
+        name: &str,
+        options: &DatasetOpenOptions,
+    ) -> Result<TypedDataset<T>> {
+        TypedDataset::open_with(Path::new(name), options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::DatasetOpenOptions;
+    use crate::dataset::TypedDataset;
+    use std::path::Path;
+
+    #[test]
+    fn plain_file_has_no_subdatasets() {
+        let ds = TypedDataset::<u8>::open(Path::new("testdata/test_u8.tif")).unwrap();
+        assert!(ds.subdatasets().is_empty());
+    }
+
+    #[test]
+    fn open_subdataset_with_options() {
+        let png_only = DatasetOpenOptions {
+            allowed_drivers: vec!["PNG".to_string()],
+            ..Default::default()
+        };
+        let name = "testdata/test_u8.tif";
+        assert!(TypedDataset::<u8>::open_subdataset_with(name, &png_only).is_err());
+        assert!(TypedDataset::<u8>::open_subdataset(name).is_ok());
+    }
+}
@@ -0,0 +1,343 @@
+use crate::buffer::TypedBuffer;
+use crate::create::DatasetBuilder;
+use crate::dataset::TypedDataset;
+use crate::errors::{Error, Result};
+use crate::rng::SplitMix64;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+use std::fmt;
+
+/// The pixel values of a synthetic raster. Every pattern stays within
+/// 0–255, so it can be stored in any pixel type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pattern {
+    /// A ramp from 0 at the top left to 255 at the bottom right.
+    Gradient,
+    /// Alternating square cells of 0 and 255, `cell` pixels wide.
+    Checkerboard(usize),
+    /// Uniformly distributed whole numbers, the same for every run with the
+    /// same seed.
+    Random(u64),
+}
+
+/// Generates a buffer of `size` pixels following `pattern`.
+pub fn pattern<T: Copy + GdalFrom<f64>>(size: (usize, usize), pattern: Pattern) -> TypedBuffer<T> {
+    let (width, height) = size;
+    let mut data = Vec::with_capacity(width * height);
+    match pattern {
+        Pattern::Gradient => {
+            let steps = (width + height).saturating_sub(2).max(1) as f64;
+            for y in 0..height {
+                for x in 0..width {
+                    data.push(T::from(((x + y) as f64 * 255.0 / steps).round()));
+                }
+            }
+        }
+        Pattern::Checkerboard(cell) => {
+            assert!(cell > 0, "checkerboard cell size must be nonzero");
+            for y in 0..height {
+                for x in 0..width {
+                    let dark = (x / cell + y / cell) % 2 == 1;
+                    data.push(T::from(if dark { 0.0 } else { 255.0 }));
+                }
+            }
+        }
+        Pattern::Random(seed) => {
+            let mut rng = SplitMix64(seed);
+            for _ in 0..width * height {
+                data.push(T::from((rng.next_u64() % 256) as f64));
+            }
+        }
+    }
+    TypedBuffer::new(size, data)
+}
+
+/// Replaces roughly `fraction` of the pixels of `buffer` with `nodata`,
+/// choosing them deterministically from `seed`.
+pub fn punch_nodata_holes<T: Copy>(
+    buffer: &mut TypedBuffer<T>,
+    nodata: T,
+    fraction: f64,
+    seed: u64,
+) {
+    let mut rng = SplitMix64(seed);
+    for v in buffer.data.iter_mut() {
+        if rng.next_f64() < fraction {
+            *v = nodata;
+        }
+    }
+}
+
+/// Creates an in-memory single-band dataset holding `buffer`.
+pub fn dataset_from_buffer<T>(buffer: &TypedBuffer<T>, nodata: Option<T>) -> Result<TypedDataset<T>>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Into<f64>,
+{
+    let mut builder = DatasetBuilder::new("MEM", "", buffer.size);
+    if let Some(nodata) = nodata {
+        builder = builder.nodata(nodata);
+    }
+    let dataset = builder.create()?;
+    dataset.write(1, Window::new((0, 0), buffer.size), buffer)?;
+    Ok(dataset)
+}
+
+/// Creates an in-memory single-band dataset of `size` pixels following
+/// `pattern`.
+pub fn synthetic_dataset<T>(size: (usize, usize), pattern: Pattern) -> Result<TypedDataset<T>>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Into<f64>,
+{
+    dataset_from_buffer(&self::pattern(size, pattern), None)
+}
+
+/// Like `synthetic_dataset`, but with roughly `fraction` of the pixels set to
+/// `nodata`, which is also recorded as the band's nodata value.
+pub fn synthetic_dataset_with_holes<T>(
+    size: (usize, usize),
+    pattern: Pattern,
+    nodata: T,
+    fraction: f64,
+    seed: u64,
+) -> Result<TypedDataset<T>>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Into<f64>,
+{
+    let mut buffer = self::pattern(size, pattern);
+    punch_nodata_holes(&mut buffer, nodata, fraction, seed);
+    dataset_from_buffer(&buffer, Some(nodata))
+}
+
+/// How `compare_bands` treats nodata pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodataMode {
+    /// Compare nodata pixels like any other value.
+    Compare,
+    /// Skip pixels that are nodata in either band.
+    IgnoreEither,
+    /// Require nodata in the same places in both bands, but don't compare
+    /// the values there.
+    MatchMask,
+}
+
+/// How two bands differ.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BandDiff {
+    pub size: (usize, usize),
+    /// The number of pixels that differ by more than the tolerance.
+    pub differing: usize,
+    /// The largest difference found, which is infinite where only one pixel
+    /// is nodata or NaN.
+    pub max_delta: f64,
+    /// The first differing pixel in row-major order, as `(x, y, a, b)`.
+    pub first: Option<(usize, usize, f64, f64)>,
+}
+
+impl BandDiff {
+    pub fn is_equal(&self) -> bool {
+        self.differing == 0
+    }
+}
+
+impl fmt::Display for BandDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} pixels differ; max delta {}",
+            self.differing,
+            self.size.0 * self.size.1,
+            self.max_delta
+        )?;
+        if let Some((x, y, a, b)) = self.first {
+            write!(f, "; first at ({}, {}): {} vs {}", x, y, a, b)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares two buffers of the same size pixel by pixel.
+pub fn compare_buffers<T: Copy + Into<f64>>(
+    a: &TypedBuffer<T>,
+    b: &TypedBuffer<T>,
+    nodata: (Option<T>, Option<T>),
+    tolerance: f64,
+    mode: NodataMode,
+) -> BandDiff {
+    assert_eq!(a.size, b.size, "buffers differ in size");
+    let is_nodata = |v: f64, nodata: Option<T>| nodata.is_some_and(|n| n.into() == v);
+
+    let mut diff = BandDiff {
+        size: a.size,
+        differing: 0,
+        max_delta: 0.0,
+        first: None,
+    };
+    for (i, (&va, &vb)) in a.data.iter().zip(&b.data).enumerate() {
+        let (va, vb): (f64, f64) = (va.into(), vb.into());
+        let (na, nb) = (is_nodata(va, nodata.0), is_nodata(vb, nodata.1));
+        let delta = match mode {
+            NodataMode::IgnoreEither if na || nb => continue,
+            NodataMode::MatchMask if na && nb => continue,
+            NodataMode::MatchMask if na != nb => f64::INFINITY,
+            _ if va.is_nan() && vb.is_nan() => continue,
+            _ if va.is_nan() || vb.is_nan() => f64::INFINITY,
+            _ => (va - vb).abs(),
+        };
+        if delta > tolerance {
+            diff.differing += 1;
+            diff.max_delta = diff.max_delta.max(delta);
+            if diff.first.is_none() {
+                diff.first = Some((i % a.size.0, i / a.size.0, va, vb));
+            }
+        }
+    }
+    diff
+}
+
+/// Compares two bands pixel by pixel, treating differences of at most
+/// `tolerance` as equal.
+pub fn compare_bands<T, A: Access, B: Access>(
+    a: &TypedRasterBand<T, A>,
+    b: &TypedRasterBand<T, B>,
+    tolerance: f64,
+    mode: NodataMode,
+) -> Result<BandDiff>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Into<f64>,
+{
+    if a.size() != b.size() {
+        return Err(Error::Alignment(format!(
+            "bands are {:?} and {:?} pixels",
+            a.size(),
+            b.size()
+        )));
+    }
+    let (buffer_a, buffer_b): (TypedBuffer<T>, TypedBuffer<T>) =
+        (a.read_band()?.into(), b.read_band()?.into());
+    Ok(compare_buffers(
+        &buffer_a,
+        &buffer_b,
+        (a.no_data_value(), b.no_data_value()),
+        tolerance,
+        mode,
+    ))
+}
+
+/// Panics with a report of how the bands differ unless every pixel matches
+/// within `tolerance`.
+pub fn assert_bands_equal<T, A: Access, B: Access>(
+    a: &TypedRasterBand<T, A>,
+    b: &TypedRasterBand<T, B>,
+    tolerance: f64,
+    mode: NodataMode,
+) where
+    T: Copy + GdalType + GdalFrom<f64> + Into<f64>,
+{
+    match compare_bands(a, b, tolerance, mode) {
+        Ok(diff) if diff.is_equal() => {}
+        Ok(diff) => panic!("bands differ: {}", diff),
+        Err(e) => panic!("couldn't compare bands: {}", e),
+    }
+}
+
+/// Copies `testdata/test_u8.tif` to `/vsimem/<name>` with a 2x overview,
+/// for tests of the `OVERVIEW_LEVEL` open option.
+#[cfg(test)]
+pub(crate) fn gtiff_with_overviews(name: &str) -> crate::vsi::MemFile {
+    use crate::errors::check_cpl_err;
+    use std::ffi::CString;
+    use std::path::Path;
+    use std::ptr;
+
+    let source = TypedDataset::<u8>::open(Path::new("testdata/test_u8.tif")).unwrap();
+    let bytes = source.to_bytes("GTiff", &[]).unwrap();
+    let file = crate::vsi::MemFile::from_bytes(name, &bytes).unwrap();
+    let ds = TypedDataset::<u8>::open_rw(Path::new(file.path())).unwrap();
+    let resampling = CString::new("NEAREST").unwrap();
+    let mut factors = [2];
+    let rv = unsafe {
+        gdal_sys::GDALBuildOverviews(
+            ds.dataset()._c_ptr(),
+            resampling.as_ptr(),
+            1,
+            factors.as_mut_ptr(),
+            0,
+            ptr::null_mut(),
+            None,
+            ptr::null_mut(),
+        )
+    };
+    check_cpl_err(rv).unwrap();
+    file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::testing::{
+        compare_buffers, pattern, punch_nodata_holes, synthetic_dataset_with_holes, NodataMode,
+        Pattern,
+    };
+    use crate::window::Window;
+
+    #[test]
+    fn generate_patterns() {
+        let gradient: TypedBuffer<u8> = pattern((4, 3), Pattern::Gradient);
+        assert_eq!(gradient.get(0, 0), 0);
+        assert_eq!(gradient.get(3, 2), 255);
+
+        let board: TypedBuffer<f32> = pattern((4, 4), Pattern::Checkerboard(2));
+        assert_eq!(board.row(0), &[255.0, 255.0, 0.0, 0.0]);
+        assert_eq!(board.row(2), &[0.0, 0.0, 255.0, 255.0]);
+
+        let a: TypedBuffer<u16> = pattern((8, 8), Pattern::Random(7));
+        let b: TypedBuffer<u16> = pattern((8, 8), Pattern::Random(7));
+        let c: TypedBuffer<u16> = pattern((8, 8), Pattern::Random(8));
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert!(a.data.iter().all(|&v| v < 256));
+    }
+
+    #[test]
+    fn nodata_holes() {
+        let mut buffer: TypedBuffer<i16> = pattern((100, 100), Pattern::Gradient);
+        punch_nodata_holes(&mut buffer, -1, 0.25, 3);
+        let holes = buffer.data.iter().filter(|&&v| v == -1).count();
+        assert!(holes > 2000 && holes < 3000);
+    }
+
+    #[test]
+    fn in_memory_dataset() {
+        let ds =
+            synthetic_dataset_with_holes::<u16>((16, 16), Pattern::Gradient, 999, 0.1, 1).unwrap();
+        let buffer = ds.read(1, Window::new((0, 0), (16, 16))).unwrap();
+
+        assert!(buffer.data.contains(&999));
+        ds.with_band(1, |band| assert_eq!(band.no_data_value(), Some(999)))
+            .unwrap();
+    }
+
+    #[test]
+    fn compare_with_nodata_modes() {
+        let a = TypedBuffer::new((3, 2), vec![1u8, 2, 3, 0, 5, 6]);
+        let b = TypedBuffer::new((3, 2), vec![1u8, 2, 4, 9, 0, 6]);
+        let nodata = (Some(0), Some(0));
+
+        let diff = compare_buffers(&a, &b, nodata, 0.0, NodataMode::Compare);
+        assert_eq!(diff.differing, 3);
+        assert_eq!(diff.max_delta, 9.0);
+        assert_eq!(diff.first, Some((2, 0, 3.0, 4.0)));
+
+        let diff = compare_buffers(&a, &b, nodata, 1.0, NodataMode::IgnoreEither);
+        assert!(diff.is_equal());
+
+        let diff = compare_buffers(&a, &b, nodata, 1.0, NodataMode::MatchMask);
+        assert_eq!(diff.differing, 2);
+        assert_eq!(diff.max_delta, f64::INFINITY);
+        assert_eq!(
+            diff.to_string(),
+            "2 of 6 pixels differ; max delta inf; first at (0, 1): 0 vs 9"
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::typed_rasterband::TypeError;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, Write};
+use std::path::Path;
+use tiff::decoder::{Decoder, DecodingResult};
+use tiff::encoder::{colortype, TiffEncoder};
+
+/// Pixel types that can be read from and written to single-band TIFFs
+/// without GDAL.
+pub trait TiffPixel: Copy + Sized {
+    fn from_decoding_result(result: DecodingResult) -> Option<Vec<Self>>;
+
+    fn encode<W: Write + Seek>(
+        encoder: &mut TiffEncoder<W>,
+        size: (usize, usize),
+        data: &[Self],
+    ) -> Result<()>;
+}
+
+macro_rules! impl_tiff_pixel {
+    ($t:ty, $variant:ident, $color:ty) => {
+        impl TiffPixel for $t {
+            fn from_decoding_result(result: DecodingResult) -> Option<Vec<$t>> {
+                match result {
+                    DecodingResult::$variant(data) => Some(data),
+                    _ => None,
+                }
+            }
+
+            fn encode<W: Write + Seek>(
+                encoder: &mut TiffEncoder<W>,
+                size: (usize, usize),
+                data: &[$t],
+            ) -> Result<()> {
+                encoder.write_image::<$color>(size.0 as u32, size.1 as u32, data)?;
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_tiff_pixel!(u8, U8, colortype::Gray8);
+impl_tiff_pixel!(u16, U16, colortype::Gray16);
+impl_tiff_pixel!(u32, U32, colortype::Gray32);
+impl_tiff_pixel!(f32, F32, colortype::Gray32Float);
+impl_tiff_pixel!(f64, F64, colortype::Gray64Float);
+
+impl<T: TiffPixel> TypedBuffer<T> {
+    /// Reads the first image of a plain greyscale TIFF using the pure-Rust
+    /// `tiff` crate. Georeferencing tags are ignored.
+    pub fn read_tiff<R: Read + Seek>(reader: R) -> Result<TypedBuffer<T>> {
+        let mut decoder = Decoder::new(reader)?;
+        let (width, height) = decoder.dimensions()?;
+        let data = T::from_decoding_result(decoder.read_image()?).ok_or(TypeError {})?;
+        Ok(TypedBuffer::new((width as usize, height as usize), data))
+    }
+
+    pub fn load_tiff<P: AsRef<Path>>(path: P) -> Result<TypedBuffer<T>> {
+        TypedBuffer::read_tiff(BufReader::new(File::open(path)?))
+    }
+
+    /// Writes the buffer as an uncompressed greyscale TIFF without GDAL.
+    pub fn write_tiff<W: Write + Seek>(&self, writer: W) -> Result<()> {
+        let mut encoder = TiffEncoder::new(writer)?;
+        T::encode(&mut encoder, self.size, &self.data)
+    }
+
+    pub fn save_tiff<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.write_tiff(BufWriter::new(File::create(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use std::io::Cursor;
+
+    #[test]
+    fn read_test_tiff() {
+        let buffer = TypedBuffer::<u16>::load_tiff("testdata/test_u16.tif").unwrap();
+
+        assert_eq!(buffer.size, (333, 333));
+        assert_eq!(buffer.get(100, 100), 6656);
+        assert!(TypedBuffer::<u8>::load_tiff("testdata/test_u16.tif").is_err());
+    }
+
+    #[test]
+    fn tiff_round_trip() {
+        let buffer = TypedBuffer::new((3, 2), vec![1.0f32, 2.5, -3.0, 4.0, 5.0, 6.0]);
+        let mut cursor = Cursor::new(Vec::new());
+        buffer.write_tiff(&mut cursor).unwrap();
+
+        cursor.set_position(0);
+        assert_eq!(TypedBuffer::read_tiff(cursor).unwrap(), buffer);
+    }
+}
@@ -0,0 +1,190 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::{Error, Result};
+use crate::shared::SharedTypedBand;
+use crate::typed_rasterband::GdalFrom;
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+#[cfg(feature = "ndarray")]
+use ndarray::Array3;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Geotransforms are compared to within this fraction of a pixel.
+const GEO_TRANSFORM_TOLERANCE: f64 = 1e-6;
+
+/// A stack of bands from separate files that share a grid, ordered by
+/// acquisition time.
+#[derive(Debug, Clone)]
+pub struct RasterTimeSeries<T> {
+    entries: Vec<(SystemTime, SharedTypedBand<T>)>,
+    size: (usize, usize),
+    geo_transform: Option<[f64; 6]>,
+}
+
+impl<T> RasterTimeSeries<T>
+where
+    T: Copy + GdalType + GdalFrom<f64>,
+{
+    /// Builds a time series from `(timestamp, path, band index)` entries,
+    /// checking that every band has pixel type `T`, the same size and the
+    /// same geotransform. Entries are sorted by timestamp.
+    pub fn new(mut entries: Vec<(SystemTime, PathBuf, isize)>) -> Result<RasterTimeSeries<T>> {
+        entries.sort_by_key(|entry| entry.0);
+
+        let mut series = RasterTimeSeries {
+            entries: Vec::with_capacity(entries.len()),
+            size: (0, 0),
+            geo_transform: None,
+        };
+        for (i, (time, path, band_index)) in entries.into_iter().enumerate() {
+            let band = SharedTypedBand::new(path, band_index)?;
+            let (size, gt) =
+                band.with_band(|b| (b.size(), b.owning_dataset().geo_transform().ok()))?;
+
+            if i == 0 {
+                series.size = size;
+                series.geo_transform = gt;
+            } else if size != series.size {
+                return Err(Error::Alignment(format!(
+                    "{} is {:?} pixels, expected {:?}",
+                    band.path().display(),
+                    size,
+                    series.size
+                )));
+            } else if !same_geo_transform(gt, series.geo_transform) {
+                return Err(Error::Alignment(format!(
+                    "{} has a different geotransform",
+                    band.path().display()
+                )));
+            }
+            series.entries.push((time, band));
+        }
+        Ok(series)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn timestamps(&self) -> Vec<SystemTime> {
+        self.entries.iter().map(|entry| entry.0).collect()
+    }
+
+    pub fn bands(&self) -> impl Iterator<Item = &SharedTypedBand<T>> {
+        self.entries.iter().map(|entry| &entry.1)
+    }
+
+    /// The size shared by every band.
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    pub fn geo_transform(&self) -> Option<[f64; 6]> {
+        self.geo_transform
+    }
+
+    /// Reads the value of pixel `(x, y)` at every time step.
+    pub fn read_pixel_series(&self, x: isize, y: isize) -> Result<Vec<T>> {
+        let window = Window::new((x, y), (1, 1));
+        self.bands()
+            .map(|band| Ok(band.read(window, (1, 1))?.data[0]))
+            .collect()
+    }
+
+    /// Reads `window` at every time step.
+    pub fn read_frames(&self, window: Window) -> Result<Vec<TypedBuffer<T>>> {
+        self.bands()
+            .map(|band| band.read(window, window.size))
+            .collect()
+    }
+
+    /// Reads `window` at every time step into a `(time, y, x)` array.
+    #[cfg(feature = "ndarray")]
+    pub fn read_cube(&self, window: Window) -> Result<Array3<T>> {
+        let mut data = Vec::with_capacity(self.len() * window.pixel_count());
+        for frame in self.read_frames(window)? {
+            if frame.size != window.size {
+                return Err(Error::Bounds(format!(
+                    "frame of size {:?} doesn't match window size {:?}",
+                    frame.size, window.size
+                )));
+            }
+            data.extend(frame.data);
+        }
+        let shape = (self.len(), window.size.1, window.size.0);
+        Array3::from_shape_vec(shape, data).map_err(|_| {
+            Error::Bounds(format!(
+                "{} frames don't fill a cube of shape {:?}",
+                self.len(),
+                shape
+            ))
+        })
+    }
+}
+
+pub(crate) fn same_geo_transform(a: Option<[f64; 6]>, b: Option<[f64; 6]>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            let pixel = a[1].abs().max(a[5].abs());
+            a.iter()
+                .zip(b.iter())
+                .all(|(x, y)| (x - y).abs() <= GEO_TRANSFORM_TOLERANCE * pixel)
+        }
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::timeseries::RasterTimeSeries;
+    #[cfg(feature = "ndarray")]
+    use crate::window::Window;
+    use std::path::PathBuf;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn read_time_series() {
+        let day = Duration::from_secs(86400);
+        let series = RasterTimeSeries::<u16>::new(vec![
+            (
+                UNIX_EPOCH + day,
+                PathBuf::from("testdata/test_u16_nodata.tif"),
+                1,
+            ),
+            (UNIX_EPOCH, PathBuf::from("testdata/test_u16.tif"), 1),
+        ])
+        .unwrap();
+
+        assert_eq!(series.timestamps(), vec![UNIX_EPOCH, UNIX_EPOCH + day]);
+        assert_eq!(
+            series.read_pixel_series(100, 100).unwrap(),
+            vec![6656, 6656]
+        );
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn read_small_cube() {
+        let series = RasterTimeSeries::<u16>::new(vec![
+            (UNIX_EPOCH, PathBuf::from("testdata/test_u16.tif"), 1),
+            (
+                UNIX_EPOCH + Duration::from_secs(60),
+                PathBuf::from("testdata/test_u16_nodata.tif"),
+                1,
+            ),
+        ])
+        .unwrap();
+
+        let cube = series.read_cube(Window::new((100, 100), (3, 2))).unwrap();
+        assert_eq!(cube.dim(), (2, 2, 3));
+        assert_eq!(cube[[0, 0, 0]], 6656);
+        assert_eq!(cube[[1, 0, 1]], 6764);
+
+        assert!(series.read_cube(Window::new((332, 0), (2, 2))).is_err());
+    }
+}
@@ -0,0 +1,46 @@
+use crate::window::Window;
+#[cfg(feature = "tracing")]
+use std::time::Instant;
+
+/// Runs `f`, an I/O `operation` touching `bytes` bytes of `window`, inside a
+/// span, and records how long it took.
+///
+/// Without the `tracing` feature, this just calls `f`.
+#[cfg(feature = "tracing")]
+pub(crate) fn instrument<R, F: FnOnce() -> R>(
+    operation: &'static str,
+    window: Window,
+    bytes: usize,
+    f: F,
+) -> R {
+    let span = tracing::debug_span!(
+        "raster_io",
+        operation = operation,
+        x = window.offset.0,
+        y = window.offset.1,
+        width = window.size.0,
+        height = window.size.1,
+        bytes = bytes,
+    );
+    let _entered = span.enter();
+    let start = Instant::now();
+    let result = f();
+    tracing::debug!(elapsed_us = start.elapsed().as_micros() as u64, "finished");
+    result
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn instrument<R, F: FnOnce() -> R>(
+    _operation: &'static str,
+    _window: Window,
+    _bytes: usize,
+    f: F,
+) -> R {
+    f()
+}
+
+/// The number of bytes in a buffer of `size` pixels of type `T`.
+pub(crate) fn buffer_bytes<T>(size: (usize, usize)) -> usize {
+    size.0 * size.1 * std::mem::size_of::<T>()
+}
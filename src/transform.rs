@@ -0,0 +1,47 @@
+/// Coefficients mapping pixel/line coordinates to map coordinates, in GDAL
+/// order.
+pub type GeoTransform = [f64; 6];
+
+/// Maps a pixel/line position to map coordinates.
+pub fn apply(gt: &GeoTransform, pixel: f64, line: f64) -> (f64, f64) {
+    (
+        gt[0] + pixel * gt[1] + line * gt[2],
+        gt[3] + pixel * gt[4] + line * gt[5],
+    )
+}
+
+/// Computes the transform mapping map coordinates back to pixel/line, or
+/// `None` if the transform is degenerate.
+pub fn invert(gt: &GeoTransform) -> Option<GeoTransform> {
+    let det = gt[1] * gt[5] - gt[2] * gt[4];
+    if det.abs() < 1e-15 {
+        return None;
+    }
+
+    let inv_det = 1.0 / det;
+    Some([
+        (gt[2] * gt[3] - gt[0] * gt[5]) * inv_det,
+        gt[5] * inv_det,
+        -gt[2] * inv_det,
+        (-gt[1] * gt[3] + gt[0] * gt[4]) * inv_det,
+        -gt[4] * inv_det,
+        gt[1] * inv_det,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invert_round_trip() {
+        let gt = [450000.0, 30.0, 0.5, 5410000.0, 0.25, -30.0];
+        let inv = invert(&gt).unwrap();
+
+        let (x, y) = apply(&gt, 12.0, 34.0);
+        let (px, py) = apply(&inv, x, y);
+        assert!((px - 12.0).abs() < 1e-9);
+        assert!((py - 34.0).abs() < 1e-9);
+        assert!(invert(&[0.0, 0.0, 0.0, 0.0, 0.0, 0.0]).is_none());
+    }
+}
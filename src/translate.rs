@@ -0,0 +1,172 @@
+use crate::config::NameValueList;
+use crate::dataset::TypedDataset;
+use crate::errors::{Error, Result};
+use crate::request::Resampling;
+use crate::typed_rasterband::GdalFrom;
+use crate::window::Window;
+use gdal::raster::dataset::Dataset;
+use gdal::raster::types::GdalType;
+use gdal_sys::{
+    CPLErr, GDALGetDataTypeName, GDALTranslate, GDALTranslateOptionsFree, GDALTranslateOptionsNew,
+};
+use std::ffi::{CStr, CString};
+use std::io;
+use std::ptr;
+
+/// Options for `TypedDataset::translate`, mirroring those of
+/// `gdal_translate`.
+#[derive(Debug, Clone, Default)]
+pub struct TranslateOptions {
+    /// The output driver, such as `"GTiff"` or `"COG"`. GDAL picks one from
+    /// the file extension if unset.
+    pub format: Option<String>,
+    /// The part of the source to copy.
+    pub src_window: Option<Window>,
+    /// The output size in pixels, if it should differ from the source.
+    pub out_size: Option<(usize, usize)>,
+    pub resampling: Option<Resampling>,
+    /// The source bands to copy, in order. All bands are copied if empty.
+    pub bands: Vec<isize>,
+    /// Linearly maps source values from `(src_min, src_max)` to
+    /// `(dst_min, dst_max)`.
+    pub scale: Option<((f64, f64), (f64, f64))>,
+    pub nodata: Option<f64>,
+    /// Driver-specific creation options, such as `("COMPRESS", "DEFLATE")`.
+    pub creation_options: Vec<(String, String)>,
+}
+
+impl TranslateOptions {
+    /// The `gdal_translate` arguments for these options, converting pixels
+    /// to `output_type`.
+    fn to_args(&self, output_type: &str) -> Vec<String> {
+        let mut args = vec!["-ot".to_string(), output_type.to_string()];
+        if let Some(format) = &self.format {
+            args.extend(vec!["-of".to_string(), format.clone()]);
+        }
+        if let Some(window) = self.src_window {
+            args.push("-srcwin".to_string());
+            args.extend(
+                [
+                    window.offset.0,
+                    window.offset.1,
+                    window.size.0 as isize,
+                    window.size.1 as isize,
+                ]
+                .iter()
+                .map(|v| v.to_string()),
+            );
+        }
+        if let Some((width, height)) = self.out_size {
+            args.extend(vec![
+                "-outsize".to_string(),
+                width.to_string(),
+                height.to_string(),
+            ]);
+        }
+        if let Some(resampling) = self.resampling {
+            args.extend(vec!["-r".to_string(), resampling.name().to_string()]);
+        }
+        for band in &self.bands {
+            args.extend(vec!["-b".to_string(), band.to_string()]);
+        }
+        if let Some(((src_min, src_max), (dst_min, dst_max))) = self.scale {
+            args.push("-scale".to_string());
+            args.extend(
+                [src_min, src_max, dst_min, dst_max]
+                    .iter()
+                    .map(|v| v.to_string()),
+            );
+        }
+        if let Some(nodata) = self.nodata {
+            args.extend(vec!["-a_nodata".to_string(), nodata.to_string()]);
+        }
+        for (key, value) in &self.creation_options {
+            args.extend(vec!["-co".to_string(), format!("{}={}", key, value)]);
+        }
+        args
+    }
+}
+
+/// GDAL's name for the pixel type `T`, as the utilities' `-ot` expects.
+pub(crate) fn gdal_type_name<T: GdalType>() -> String {
+    unsafe { CStr::from_ptr(GDALGetDataTypeName(T::gdal_type())) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64>> TypedDataset<T> {
+    /// Copies the dataset to `path` as `gdal_translate` would, converting
+    /// pixels to `U`.
+    pub fn translate<U>(&self, path: &str, options: &TranslateOptions) -> Result<TypedDataset<U>>
+    where
+        U: Copy + GdalType + GdalFrom<f64>,
+    {
+        let args = NameValueList::from_strings(options.to_args(&gdal_type_name::<U>()))?;
+        let c_path = CString::new(path).map_err(io::Error::from)?;
+
+        let c_dataset = unsafe {
+            let translate_options =
+                GDALTranslateOptionsNew(args.as_ptr() as *mut *mut _, ptr::null_mut());
+            if translate_options.is_null() {
+                return Err(Error::last_cpl_error(CPLErr::CE_Failure));
+            }
+            let c_dataset = GDALTranslate(
+                c_path.as_ptr(),
+                self.dataset()._c_ptr(),
+                translate_options,
+                ptr::null_mut(),
+            );
+            GDALTranslateOptionsFree(translate_options);
+            c_dataset
+        };
+        if c_dataset.is_null() {
+            return Err(Error::last_cpl_error(CPLErr::CE_Failure));
+        }
+        let mut output = TypedDataset::from_dataset(unsafe { Dataset::_with_c_ptr(c_dataset) })?;
+        self.record_derived(&mut output, "translate", &[("type", gdal_type_name::<U>())])?;
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dataset::TypedDataset;
+    use crate::request::Resampling;
+    use crate::translate::TranslateOptions;
+    use crate::window::Window;
+    use std::path::Path;
+
+    #[test]
+    fn translate_args() {
+        let options = TranslateOptions {
+            format: Some("GTiff".to_string()),
+            src_window: Some(Window::new((10, 20), (30, 40))),
+            resampling: Some(Resampling::Bilinear),
+            bands: vec![3, 1],
+            scale: Some(((0.0, 4095.0), (0.0, 255.0))),
+            creation_options: vec![("COMPRESS".to_string(), "DEFLATE".to_string())],
+            ..TranslateOptions::default()
+        };
+
+        assert_eq!(
+            options.to_args("Byte").join(" "),
+            "-ot Byte -of GTiff -srcwin 10 20 30 40 -r bilinear -b 3 -b 1 \
+             -scale 0 4095 0 255 -co COMPRESS=DEFLATE"
+        );
+    }
+
+    #[test]
+    fn translate_to_memory() {
+        let ds = TypedDataset::<u16>::open(Path::new("testdata/test_u16.tif")).unwrap();
+        let options = TranslateOptions {
+            format: Some("MEM".to_string()),
+            src_window: Some(Window::new((100, 100), (2, 1))),
+            ..TranslateOptions::default()
+        };
+        let out = ds.translate::<f32>("", &options).unwrap();
+
+        assert_eq!(out.size(), (2, 1));
+        let buffer = out.read(1, Window::new((0, 0), (2, 1))).unwrap();
+        assert_eq!(buffer.data, vec![6656.0, 6764.0]);
+    }
+}
@@ -0,0 +1,300 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::{Error, Result};
+use crate::source::RasterSource;
+use crate::srs::SpatialRef;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use gdal::raster::types::GdalType;
+use std::fmt;
+
+/// The most out-of-range pixel locations a `RangeReport` keeps.
+pub const MAX_REPORTED_LOCATIONS: usize = 1000;
+
+/// The outcome of `validate_range`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RangeReport {
+    /// The number of pixels checked, including nodata pixels.
+    pub checked: usize,
+    /// The number of pixels outside the range, including nodata pixels if
+    /// they weren't allowed.
+    pub out_of_range: usize,
+    /// The number of nodata pixels.
+    pub nodata: usize,
+    /// The column and row of the first `MAX_REPORTED_LOCATIONS` pixels out
+    /// of range, in block order.
+    pub locations: Vec<(usize, usize)>,
+}
+
+impl RangeReport {
+    /// Whether every pixel passed.
+    pub fn is_valid(&self) -> bool {
+        self.out_of_range == 0
+    }
+}
+
+impl<'a, T, A: Access> TypedRasterBand<'a, T, A>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Into<f64>,
+{
+    /// Checks that every pixel lies within `min..=max`, reading the band one
+    /// block at a time. NaN is always out of range, unless it's the nodata
+    /// value.
+    ///
+    /// With `nodata_ok`, nodata pixels pass whatever their value; otherwise
+    /// they're out of range like any other pixel that isn't within bounds.
+    pub fn validate_range(&self, min: f64, max: f64, nodata_ok: bool) -> Result<RangeReport> {
+        let nodata = self.no_data_value().map(Into::into);
+        let is_nodata = |v: f64| nodata.is_some_and(|n| v == n || (n.is_nan() && v.is_nan()));
+        let mut report = RangeReport::default();
+
+        for window in self.block_windows() {
+            let buffer: TypedBuffer<T> = self.read(window.offset, window.size, window.size)?.into();
+            for (col, row, v) in buffer.pixels() {
+                let v: f64 = v.into();
+                report.checked += 1;
+                let passes = if is_nodata(v) {
+                    report.nodata += 1;
+                    nodata_ok
+                } else {
+                    v >= min && v <= max
+                };
+                if !passes {
+                    report.out_of_range += 1;
+                    if report.locations.len() < MAX_REPORTED_LOCATIONS {
+                        report.locations.push((
+                            window.offset.0 as usize + col,
+                            window.offset.1 as usize + row,
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// How far apart, in pixels, the corners of two grids may be for
+/// `check_alignment` to treat them as the same.
+pub const ALIGNMENT_TOLERANCE: f64 = 1e-3;
+
+/// One way in which a source differs from the first source passed to
+/// `check_alignment`. `index` is the source's position in the slice.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mismatch {
+    Size {
+        index: usize,
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    GeoTransform {
+        index: usize,
+        expected: Option<[f64; 6]>,
+        found: Option<[f64; 6]>,
+    },
+    Projection {
+        index: usize,
+        expected: Option<String>,
+        found: Option<String>,
+    },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mismatch::Size {
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "source {} is {:?} pixels, not {:?}",
+                index, found, expected
+            ),
+            Mismatch::GeoTransform {
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "source {} has geotransform {:?}, not {:?}",
+                index, found, expected
+            ),
+            Mismatch::Projection { index, .. } => {
+                write!(f, "source {} is in a different CRS", index)
+            }
+        }
+    }
+}
+
+/// The outcome of `check_alignment`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AlignmentReport {
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl AlignmentReport {
+    pub fn is_aligned(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// `Ok` if the sources are aligned, and otherwise an `Error::Alignment`
+    /// listing every mismatch.
+    pub fn into_result(self) -> Result<()> {
+        if self.is_aligned() {
+            return Ok(());
+        }
+        let messages: Vec<String> = self.mismatches.iter().map(|m| m.to_string()).collect();
+        Err(Error::Alignment(messages.join("; ")))
+    }
+}
+
+/// Whether grids with geotransforms `a` and `b` and `size` pixels have
+/// corners within `tolerance` pixels of each other.
+fn same_grid(a: &[f64; 6], b: &[f64; 6], size: (usize, usize), tolerance: f64) -> bool {
+    let pixel = a[1].hypot(a[4]).min(a[2].hypot(a[5]));
+    let (w, h) = (size.0 as f64, size.1 as f64);
+    [(0.0, 0.0), (w, 0.0), (0.0, h), (w, h)]
+        .iter()
+        .all(|&(col, row)| {
+            let dx = (a[0] + col * a[1] + row * a[2]) - (b[0] + col * b[1] + row * b[2]);
+            let dy = (a[3] + col * a[4] + row * a[5]) - (b[3] + col * b[4] + row * b[5]);
+            dx.hypot(dy) <= tolerance * pixel
+        })
+}
+
+fn same_projection(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+    match (
+        SpatialRef::from_user_input(a),
+        SpatialRef::from_user_input(b),
+    ) {
+        (Some(a), Some(b)) => a.is_same(&b),
+        _ => false,
+    }
+}
+
+/// Checks that every source has the size, geotransform and CRS of the
+/// first, as operations that combine rasters pixel by pixel need.
+///
+/// Geotransforms match if the corners of the grids they describe are within
+/// `ALIGNMENT_TOLERANCE` pixels. CRSs match if their WKT is identical or GDAL
+/// considers them the same. Sources without a geotransform or CRS only match
+/// others without one.
+pub fn check_alignment<T: Copy>(sources: &[&dyn RasterSource<T>]) -> AlignmentReport {
+    let mut report = AlignmentReport::default();
+    let first = match sources.first() {
+        Some(first) => first,
+        None => return report,
+    };
+    let (size, gt, projection) = (first.size(), first.geo_transform(), first.projection());
+
+    for (index, source) in sources.iter().enumerate().skip(1) {
+        let found = source.size();
+        if found != size {
+            report.mismatches.push(Mismatch::Size {
+                index,
+                expected: size,
+                found,
+            });
+        }
+        let found = source.geo_transform();
+        let grid_matches = match (&gt, &found) {
+            (Some(a), Some(b)) => same_grid(a, b, size, ALIGNMENT_TOLERANCE),
+            (None, None) => true,
+            _ => false,
+        };
+        if !grid_matches {
+            report.mismatches.push(Mismatch::GeoTransform {
+                index,
+                expected: gt,
+                found,
+            });
+        }
+        let found = source.projection();
+        let crs_matches = match (&projection, &found) {
+            (Some(a), Some(b)) => same_projection(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        if !crs_matches {
+            report.mismatches.push(Mismatch::Projection {
+                index,
+                expected: projection.clone(),
+                found,
+            });
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::derived::DerivedBand;
+    use crate::source::RasterSource;
+    use crate::typed_rasterband::TypedRasterBand;
+    use crate::validate::{check_alignment, same_grid, Mismatch, MAX_REPORTED_LOCATIONS};
+    use gdal::raster::dataset::Dataset;
+    use std::path::Path;
+
+    #[test]
+    fn validate_nodata_band() {
+        let ds = Dataset::open(Path::new("testdata/test_u16_nodata.tif")).unwrap();
+        let band = ds.rasterband(1).unwrap();
+        let typed_band = TypedRasterBand::<u16>::from_rasterband(&band).unwrap();
+
+        let all = typed_band.validate_range(0.0, 65535.0, true).unwrap();
+        assert!(all.is_valid());
+        assert_eq!(all.checked, 333 * 333);
+
+        let strict = typed_band.validate_range(0.0, 65535.0, false).unwrap();
+        assert_eq!(strict.out_of_range, strict.nodata);
+
+        let none = typed_band.validate_range(1.0, 0.0, true).unwrap();
+        assert_eq!(none.out_of_range, none.checked - none.nodata);
+        assert!(none.locations.len() <= MAX_REPORTED_LOCATIONS);
+    }
+
+    #[test]
+    fn compare_grids() {
+        let gt = [500000.0, 30.0, 0.0, 4000000.0, 0.0, -30.0];
+        let nudged = [500000.01, 30.0, 0.0, 4000000.0, 0.0, -30.0];
+        let shifted = [500015.0, 30.0, 0.0, 4000000.0, 0.0, -30.0];
+        let finer = [500000.0, 30.0001, 0.0, 4000000.0, 0.0, -30.0];
+
+        assert!(same_grid(&gt, &nudged, (100, 100), 1e-3));
+        assert!(!same_grid(&gt, &shifted, (100, 100), 1e-3));
+        assert!(!same_grid(&gt, &finer, (1000, 1000), 1e-3));
+    }
+
+    #[test]
+    fn report_mismatches() {
+        let buffer = TypedBuffer::filled((4, 4), 0u8);
+        let small = TypedBuffer::filled((4, 3), 0u8);
+        let gt = [0.0, 1.0, 0.0, 0.0, 0.0, -1.0];
+        let georeferenced = DerivedBand::new((4, 4), |window| buffer.read_window(window))
+            .with_geo_transform(Some(gt));
+
+        let report = check_alignment::<u8>(&[&buffer, &buffer]);
+        assert!(report.is_aligned());
+
+        let report = check_alignment::<u8>(&[&buffer, &small, &georeferenced]);
+        assert_eq!(
+            report.mismatches,
+            vec![
+                Mismatch::Size {
+                    index: 1,
+                    expected: (4, 4),
+                    found: (4, 3),
+                },
+                Mismatch::GeoTransform {
+                    index: 2,
+                    expected: None,
+                    found: Some(gt),
+                },
+            ]
+        );
+        assert!(report.into_result().is_err());
+    }
+}
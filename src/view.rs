@@ -0,0 +1,122 @@
+use crate::buffer::TypedBuffer;
+use crate::errors::Result;
+use crate::source::RasterSource;
+use crate::typed_rasterband::{Access, GdalFrom, ReadOnly, TypedRasterBand};
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+
+/// A window of a band that reads as though it were a whole raster.
+///
+/// Windows passed to a view are relative to its top-left corner and are
+/// clipped to it, so nothing outside the view is ever read.
+pub struct BandView<'a, 'b, T: Copy + GdalType, A: Access = ReadOnly> {
+    band: &'b TypedRasterBand<'a, T, A>,
+    window: Window,
+}
+
+impl<'a, T: Copy + GdalType + GdalFrom<f64>, A: Access> TypedRasterBand<'a, T, A> {
+    /// A view of `window`, clipped to the band.
+    pub fn view<'b>(&'b self, window: Window) -> BandView<'a, 'b, T, A> {
+        let window = window
+            .intersection(&Window::full(self.size()))
+            .unwrap_or_else(|| Window::new((0, 0), (0, 0)));
+        BandView { band: self, window }
+    }
+}
+
+impl<'a, 'b, T: Copy + GdalType + GdalFrom<f64>, A: Access> BandView<'a, 'b, T, A> {
+    /// The window of the underlying band that the view covers.
+    pub fn window(&self) -> Window {
+        self.window
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        self.window.size
+    }
+
+    pub fn band(&self) -> &'b TypedRasterBand<'a, T, A> {
+        self.band
+    }
+
+    pub fn no_data_value(&self) -> Option<T> {
+        self.band.no_data_value()
+    }
+
+    /// The geotransform of the view, if the dataset is georeferenced.
+    pub fn geo_transform(&self) -> Option<[f64; 6]> {
+        let gt = self.band.owning_dataset().geo_transform().ok()?;
+        Some(self.window.geo_transform(&gt))
+    }
+
+    /// The WKT of the band's CRS, if it has one.
+    pub fn projection(&self) -> Option<String> {
+        RasterSource::projection(self.band)
+    }
+
+    /// Maps a window relative to the view onto the underlying band,
+    /// clipping it to the view.
+    fn to_band_window(&self, window: Window) -> Option<Window> {
+        let shifted = Window::new(
+            (
+                window.offset.0 + self.window.offset.0,
+                window.offset.1 + self.window.offset.1,
+            ),
+            window.size,
+        );
+        shifted.intersection(&self.window)
+    }
+
+    /// A view of `window` relative to this one, clipped to it.
+    pub fn view(&self, window: Window) -> BandView<'a, 'b, T, A> {
+        BandView {
+            band: self.band,
+            window: self
+                .to_band_window(window)
+                .unwrap_or_else(|| Window::new(self.window.offset, (0, 0))),
+        }
+    }
+
+    /// Reads `window` relative to the view. The window is clipped to the
+    /// view, so the buffer may be smaller than requested.
+    pub fn read(&self, window: Window) -> Result<TypedBuffer<T>> {
+        match self.to_band_window(window) {
+            Some(window) => Ok(self
+                .band
+                .read(window.offset, window.size, window.size)?
+                .into()),
+            None => Ok(TypedBuffer::new((0, 0), Vec::new())),
+        }
+    }
+
+    /// Reads the whole view.
+    pub fn read_all(&self) -> Result<TypedBuffer<T>> {
+        self.read(Window::full(self.size()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::typed_rasterband::TypedRasterBand;
+    use crate::window::Window;
+    use gdal::raster::dataset::Dataset;
+    use std::path::Path;
+
+    #[test]
+    fn read_through_view() {
+        let path = Path::new("testdata/test_u8.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u8>::from_rasterband(&band).unwrap();
+
+        let view = typed_band.view(Window::new((1, 1), (500, 500)));
+        assert_eq!(view.size(), (332, 332));
+
+        let nested = view.view(Window::new((-1, -1), (2, 2)));
+        assert_eq!(nested.window(), Window::new((1, 1), (1, 1)));
+        assert_eq!(nested.read_all().unwrap().data, vec![164]);
+
+        // Reads are clipped to the view.
+        let buffer = view.read(Window::new((330, 0), (10, 1))).unwrap();
+        assert_eq!(buffer.size, (2, 1));
+    }
+}
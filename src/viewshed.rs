@@ -0,0 +1,160 @@
+use crate::bitmask::BitMaskBuffer;
+use crate::buffer::TypedBuffer;
+use crate::errors::{Error, Result};
+use crate::transform;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use gdal::raster::types::GdalType;
+
+/// The pixels of `dem` visible from an eye `observer_height` above the
+/// centre of pixel `observer`, out to `max_distance`. Distances are in the
+/// units of `cell_size`, the width and height of a pixel, which should
+/// match the units of elevation.
+///
+/// Each pixel is tested by a line of sight to its centre that's blocked by
+/// any pixel along the way rising above it. Nodata pixels are never
+/// visible and never block the view.
+pub fn viewshed<T: Copy + Into<f64> + PartialEq>(
+    dem: &TypedBuffer<T>,
+    cell_size: (f64, f64),
+    observer: (usize, usize),
+    observer_height: f64,
+    max_distance: f64,
+    nodata: Option<T>,
+) -> BitMaskBuffer {
+    let (width, height) = dem.size;
+    let mut visible = BitMaskBuffer::filled(dem.size, false);
+    if observer.0 >= width || observer.1 >= height {
+        return visible;
+    }
+    let elevation = |x: usize, y: usize| {
+        let v = dem.get(x, y);
+        if Some(v) == nodata {
+            None
+        } else {
+            Some(v.into())
+        }
+    };
+    let eye = match elevation(observer.0, observer.1) {
+        Some(z) => z + observer_height,
+        None => return visible,
+    };
+    visible.set(observer.0, observer.1, true);
+
+    // Only pixels within `max_distance` along both axes can be in range.
+    let reach_x = (max_distance / cell_size.0).ceil().min(width as f64) as usize;
+    let reach_y = (max_distance / cell_size.1).ceil().min(height as f64) as usize;
+    let (ox, oy) = (observer.0 as isize, observer.1 as isize);
+    for ty in observer.1.saturating_sub(reach_y)..(observer.1 + reach_y + 1).min(height) {
+        for tx in observer.0.saturating_sub(reach_x)..(observer.0 + reach_x + 1).min(width) {
+            let (dx, dy) = (tx as isize - ox, ty as isize - oy);
+            let distance = (dx as f64 * cell_size.0).hypot(dy as f64 * cell_size.1);
+            if (dx, dy) == (0, 0) || distance > max_distance {
+                continue;
+            }
+            let target = match elevation(tx, ty) {
+                Some(z) => (z - eye) / distance,
+                None => continue,
+            };
+            let steps = dx.abs().max(dy.abs());
+            let blocked = (1..steps).any(|i| {
+                let t = i as f64 / steps as f64;
+                let x = (ox as f64 + t * dx as f64).round() as usize;
+                let y = (oy as f64 + t * dy as f64).round() as usize;
+                elevation(x, y).is_some_and(|z| (z - eye) / (t * distance) > target)
+            });
+            if !blocked {
+                visible.set(tx, ty, true);
+            }
+        }
+    }
+    visible
+}
+
+impl<'a, T, A: Access> TypedRasterBand<'a, T, A>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Into<f64> + PartialEq,
+{
+    /// The pixels of the band visible from `observer_height` above the map
+    /// coordinates `observer_xy`, out to `max_distance` map units. See
+    /// `viewshed`; the band must be in a projected CRS with elevations in
+    /// the same units.
+    pub fn viewshed(
+        &self,
+        observer_xy: (f64, f64),
+        observer_height: f64,
+        max_distance: f64,
+    ) -> Result<BitMaskBuffer> {
+        let gt = self.owning_dataset().geo_transform()?;
+        let inverse = transform::invert(&gt)
+            .ok_or_else(|| Error::GeoTransform("it can't be inverted".to_string()))?;
+        let (col, row) = transform::apply(&inverse, observer_xy.0, observer_xy.1);
+        let (width, height) = self.size();
+        if col < 0.0 || row < 0.0 || col >= width as f64 || row >= height as f64 {
+            return Err(Error::Bounds(format!(
+                "observer ({}, {}) is outside the band",
+                observer_xy.0, observer_xy.1
+            )));
+        }
+
+        let dem: TypedBuffer<T> = self.read_band()?.into();
+        Ok(viewshed(
+            &dem,
+            (gt[1].abs(), gt[5].abs()),
+            (col as usize, row as usize),
+            observer_height,
+            max_distance,
+            self.no_data_value(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bitmask::PixelMask;
+    use crate::buffer::TypedBuffer;
+    use crate::create::DatasetBuilder;
+    use crate::errors::Error;
+    use crate::viewshed::viewshed;
+
+    #[test]
+    fn wall_hides_pixels() {
+        // Flat ground with a wall in the fourth column.
+        let dem = TypedBuffer::new((7, 1), vec![0u8, 0, 0, 10, 0, 0, 0]);
+        let visible = viewshed(&dem, (1.0, 1.0), (0, 0), 2.0, 100.0, None);
+
+        assert!(visible.is_set(2, 0));
+        assert!(visible.is_set(3, 0));
+        assert!(!visible.is_set(4, 0));
+        assert!(!visible.is_set(6, 0));
+
+        let tall = viewshed(&dem, (1.0, 1.0), (0, 0), 50.0, 100.0, None);
+        assert!(tall.is_set(6, 0));
+    }
+
+    #[test]
+    fn limit_distance() {
+        let dem = TypedBuffer::filled((5, 5), 0.0f32);
+        let visible = viewshed(&dem, (10.0, 10.0), (2, 2), 1.5, 15.0, None);
+
+        assert!(visible.is_set(3, 3));
+        assert!(!visible.is_set(4, 2));
+        assert_eq!(visible.count_set(), 9);
+    }
+
+    #[test]
+    fn reject_bad_observers() {
+        let ds = DatasetBuilder::<u8>::new("MEM", "", (5, 5))
+            .geo_transform([100.0, 10.0, 0.0, 200.0, 0.0, -10.0])
+            .create()
+            .unwrap();
+        let result = ds.with_band(1, |band| band.viewshed((0.0, 0.0), 2.0, 100.0));
+        assert!(matches!(result.unwrap(), Err(Error::Bounds(_))));
+
+        let flat = DatasetBuilder::<u8>::new("MEM", "", (5, 5))
+            .geo_transform([0.0; 6])
+            .create()
+            .unwrap();
+        let result = flat.with_band(1, |band| band.viewshed((0.0, 0.0), 2.0, 100.0));
+        assert!(matches!(result.unwrap(), Err(Error::GeoTransform(_))));
+    }
+}
@@ -0,0 +1,123 @@
+use crate::any::AnyTypedBand;
+use crate::typed_rasterband::{GdalFrom, TypeError, TypedRasterBand};
+use gdal::raster::rasterband::RasterBand;
+use gdal::raster::types::GdalType;
+use std::fmt::Debug;
+
+/// The capabilities shared by every pixel type a band can be visited as.
+pub trait VisitablePixel:
+    Copy + GdalType + GdalFrom<f64> + Into<f64> + PartialOrd + Default + Debug + Send + Sync + 'static
+{
+}
+
+impl<T> VisitablePixel for T where
+    T: Copy
+        + GdalType
+        + GdalFrom<f64>
+        + Into<f64>
+        + PartialOrd
+        + Default
+        + Debug
+        + Send
+        + Sync
+        + 'static
+{
+}
+
+/// An algorithm over typed bands, dispatched on the pixel type at runtime.
+///
+/// Implement `visit` once generically; override a per-type method to
+/// specialize the algorithm for that type.
+pub trait BandVisitor {
+    type Output;
+
+    fn visit<T: VisitablePixel>(&mut self, band: &TypedRasterBand<T>) -> Self::Output;
+
+    fn visit_u8(&mut self, band: &TypedRasterBand<u8>) -> Self::Output {
+        self.visit(band)
+    }
+
+    fn visit_u16(&mut self, band: &TypedRasterBand<u16>) -> Self::Output {
+        self.visit(band)
+    }
+
+    fn visit_u32(&mut self, band: &TypedRasterBand<u32>) -> Self::Output {
+        self.visit(band)
+    }
+
+    fn visit_i16(&mut self, band: &TypedRasterBand<i16>) -> Self::Output {
+        self.visit(band)
+    }
+
+    fn visit_i32(&mut self, band: &TypedRasterBand<i32>) -> Self::Output {
+        self.visit(band)
+    }
+
+    fn visit_f32(&mut self, band: &TypedRasterBand<f32>) -> Self::Output {
+        self.visit(band)
+    }
+
+    fn visit_f64(&mut self, band: &TypedRasterBand<f64>) -> Self::Output {
+        self.visit(band)
+    }
+}
+
+impl<'a> AnyTypedBand<'a> {
+    pub fn accept<V: BandVisitor>(&self, visitor: &mut V) -> V::Output {
+        match self {
+            AnyTypedBand::U8(b) => visitor.visit_u8(b),
+            AnyTypedBand::U16(b) => visitor.visit_u16(b),
+            AnyTypedBand::U32(b) => visitor.visit_u32(b),
+            AnyTypedBand::I16(b) => visitor.visit_i16(b),
+            AnyTypedBand::I32(b) => visitor.visit_i32(b),
+            AnyTypedBand::F32(b) => visitor.visit_f32(b),
+            AnyTypedBand::F64(b) => visitor.visit_f64(b),
+        }
+    }
+}
+
+/// Extends untyped bands with visitor dispatch.
+pub trait RasterBandExt {
+    /// Calls the visitor method matching the band's pixel type.
+    fn accept<V: BandVisitor>(&self, visitor: &mut V) -> Result<V::Output, TypeError>;
+}
+
+impl<'a> RasterBandExt for RasterBand<'a> {
+    fn accept<V: BandVisitor>(&self, visitor: &mut V) -> Result<V::Output, TypeError> {
+        Ok(AnyTypedBand::from_rasterband(self)?.accept(visitor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::typed_rasterband::TypedRasterBand;
+    use crate::visitor::{BandVisitor, RasterBandExt, VisitablePixel};
+    use gdal::raster::dataset::Dataset;
+    use std::path::Path;
+
+    struct FirstPixel;
+
+    impl BandVisitor for FirstPixel {
+        type Output = String;
+
+        fn visit<T: VisitablePixel>(&mut self, band: &TypedRasterBand<T>) -> String {
+            let value = band.read((0, 0), (1, 1), (1, 1)).unwrap().data[0];
+            format!("{:?}", value)
+        }
+
+        fn visit_u8(&mut self, band: &TypedRasterBand<u8>) -> String {
+            format!("byte {}", self.visit(band))
+        }
+    }
+
+    #[test]
+    fn visit_bands() {
+        let ds_u8 = Dataset::open(Path::new("testdata/test_u8.tif")).unwrap();
+        let ds_u16 = Dataset::open(Path::new("testdata/test_u16.tif")).unwrap();
+
+        let band = ds_u8.rasterband(1).unwrap();
+        assert_eq!(band.accept(&mut FirstPixel).unwrap(), "byte 152");
+        let band = ds_u16.rasterband(1).unwrap();
+        assert_eq!(band.accept(&mut FirstPixel).unwrap(), "6885");
+    }
+}
@@ -0,0 +1,211 @@
+use crate::config::{DatasetOpenOptions, NameValueList};
+use crate::dataset::TypedDataset;
+use crate::errors::{Error, Result};
+use crate::typed_rasterband::GdalFrom;
+use gdal::raster::dataset::Dataset;
+use gdal::raster::driver::Driver;
+use gdal::raster::types::GdalType;
+use gdal_sys::{
+    vsi_l_offset, CPLErr, GDALCreateCopy, VSIFCloseL, VSIFileFromMemBuffer, VSIGetMemFileBuffer,
+    VSIMalloc, VSIUnlink,
+};
+use std::ffi::CString;
+use std::io;
+use std::path::Path;
+use std::process;
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_MEM_FILE: AtomicUsize = AtomicUsize::new(0);
+
+/// A `/vsimem/` name that no other file in this process uses.
+pub(crate) fn unique_mem_name(extension: &str) -> String {
+    let n = NEXT_MEM_FILE.fetch_add(1, Ordering::SeqCst);
+    format!("typed_rasterband/{}_{}{}", process::id(), n, extension)
+}
+
+/// The path of `inner` inside the zip archive at `archive`.
+pub fn vsizip_path(archive: &str, inner: &str) -> String {
+    format!("/vsizip/{}/{}", archive, inner.trim_start_matches('/'))
+}
+
+/// The path GDAL uses to read `url` with HTTP range requests.
+pub fn vsicurl_path(url: &str) -> String {
+    format!("/vsicurl/{}", url)
+}
+
+/// A file in GDAL's `/vsimem/` in-memory filesystem, deleted when dropped.
+#[derive(Debug)]
+pub struct MemFile {
+    path: String,
+}
+
+impl MemFile {
+    /// Creates `/vsimem/<name>` holding a copy of `bytes`.
+    pub fn from_bytes(name: &str, bytes: &[u8]) -> Result<MemFile> {
+        let path = format!("/vsimem/{}", name.trim_start_matches('/'));
+        let c_path = CString::new(path.as_str()).map_err(io::Error::from)?;
+        unsafe {
+            // GDAL takes ownership of the copy, so it must come from GDAL's
+            // allocator. Allocate at least a byte so the pointer isn't null.
+            let data = VSIMalloc(bytes.len().max(1)) as *mut u8;
+            if data.is_null() {
+                return Err(io::Error::from(io::ErrorKind::OutOfMemory).into());
+            }
+            ptr::copy_nonoverlapping(bytes.as_ptr(), data, bytes.len());
+            let fp = VSIFileFromMemBuffer(c_path.as_ptr(), data, bytes.len() as vsi_l_offset, 1);
+            if fp.is_null() {
+                return Err(Error::last_cpl_error(CPLErr::CE_Failure));
+            }
+            VSIFCloseL(fp);
+        }
+        Ok(MemFile { path })
+    }
+
+    /// Wraps an existing `/vsimem/` path, e.g. one a driver has written to,
+    /// so that it's deleted when dropped.
+    pub fn from_path(path: &str) -> MemFile {
+        MemFile {
+            path: path.to_string(),
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Copies the file's current contents. Datasets writing to the file
+    /// should be dropped first so that everything has been flushed.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        read_vsimem(&self.path)
+    }
+}
+
+impl Drop for MemFile {
+    fn drop(&mut self) {
+        if let Ok(c_path) = CString::new(self.path.as_str()) {
+            unsafe { VSIUnlink(c_path.as_ptr()) };
+        }
+    }
+}
+
+/// Copies the contents of the `/vsimem/` file at `path`.
+pub fn read_vsimem(path: &str) -> Result<Vec<u8>> {
+    let c_path = CString::new(path).map_err(io::Error::from)?;
+    let mut length: vsi_l_offset = 0;
+    let data = unsafe { VSIGetMemFileBuffer(c_path.as_ptr(), &mut length, 0) };
+    if data.is_null() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no in-memory file at {}", path),
+        )
+        .into());
+    }
+    Ok(unsafe { slice::from_raw_parts(data, length as usize) }.to_vec())
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64>> TypedDataset<T> {
+    /// Opens a dataset through one of GDAL's virtual filesystems, such as
+    /// `/vsimem/`, `/vsizip/` or `/vsicurl/`, passing driver open options.
+    pub fn open_vsi(uri: &str, open_options: &[(&str, &str)]) -> Result<TypedDataset<T>> {
+        TypedDataset::open_with_options(Path::new(uri), open_options)
+    }
+
+    /// Decodes a raster held in memory, in any format GDAL can open.
+    pub fn from_bytes(bytes: &[u8]) -> Result<TypedDataset<T>> {
+        TypedDataset::from_bytes_with(bytes, &DatasetOpenOptions::default())
+    }
+
+    /// Decodes a raster held in memory, opening it as described by
+    /// `options`.
+    pub fn from_bytes_with(bytes: &[u8], options: &DatasetOpenOptions) -> Result<TypedDataset<T>> {
+        let file = MemFile::from_bytes(&unique_mem_name(""), bytes)?;
+        let mut dataset = TypedDataset::open_with(Path::new(file.path()), options)?;
+        dataset.backing = Some(file);
+        Ok(dataset)
+    }
+
+    /// Encodes the dataset with the driver named `driver`, e.g. `"GTiff"` or
+    /// `"PNG"`, passing creation options such as `("COMPRESS", "DEFLATE")`.
+    pub fn to_bytes(&self, driver: &str, options: &[(&str, &str)]) -> Result<Vec<u8>> {
+        let driver = Driver::get(driver)?;
+        let file = MemFile::from_path(&format!("/vsimem/{}", unique_mem_name("")));
+        let c_path = CString::new(file.path()).map_err(io::Error::from)?;
+        let options = NameValueList::new(options)?;
+        let c_dataset = unsafe {
+            GDALCreateCopy(
+                driver._c_ptr(),
+                c_path.as_ptr(),
+                self.dataset()._c_ptr(),
+                0,
+                options.as_ptr() as *mut *mut _,
+                None,
+                ptr::null_mut(),
+            )
+        };
+        if c_dataset.is_null() {
+            return Err(Error::last_cpl_error(CPLErr::CE_Failure));
+        }
+        // Closing the copy flushes it to the in-memory file.
+        drop(unsafe { Dataset::_with_c_ptr(c_dataset) });
+        file.to_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::DatasetOpenOptions;
+    use crate::dataset::TypedDataset;
+    use crate::vsi::{vsizip_path, MemFile};
+    use crate::window::Window;
+    use std::fs;
+
+    #[test]
+    fn vsi_paths() {
+        assert_eq!(
+            vsizip_path("data/tiles.zip", "/a/b.tif"),
+            "/vsizip/data/tiles.zip/a/b.tif"
+        );
+    }
+
+    #[test]
+    fn open_vsimem_dataset() {
+        let bytes = fs::read("testdata/test_u8.tif").unwrap();
+        let file = MemFile::from_bytes("open_vsimem_dataset.tif", &bytes).unwrap();
+
+        {
+            let ds = TypedDataset::<u8>::open_vsi(file.path(), &[]).unwrap();
+            let buffer = ds.read(1, Window::new((0, 0), (2, 1))).unwrap();
+            assert_eq!(buffer.data, vec![152, 161]);
+        }
+        assert_eq!(file.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn byte_round_trip() {
+        let bytes = fs::read("testdata/test_u16.tif").unwrap();
+        let ds = TypedDataset::<u16>::from_bytes(&bytes).unwrap();
+        let encoded = ds.to_bytes("GTiff", &[("COMPRESS", "DEFLATE")]).unwrap();
+
+        let decoded = TypedDataset::<u16>::from_bytes(&encoded).unwrap();
+        let buffer = decoded.read(1, Window::new((100, 100), (2, 1))).unwrap();
+        assert_eq!(buffer.data, vec![6656, 6764]);
+    }
+
+    #[test]
+    fn from_bytes_with_options() {
+        let bytes = fs::read("testdata/test_u8.tif").unwrap();
+        let png_only = DatasetOpenOptions {
+            allowed_drivers: vec!["PNG".to_string()],
+            ..Default::default()
+        };
+        assert!(TypedDataset::<u8>::from_bytes_with(&bytes, &png_only).is_err());
+
+        let gtiff = DatasetOpenOptions {
+            allowed_drivers: vec!["GTiff".to_string()],
+            ..Default::default()
+        };
+        assert!(TypedDataset::<u8>::from_bytes_with(&bytes, &gtiff).is_ok());
+    }
+}
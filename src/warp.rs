@@ -0,0 +1,526 @@
+use crate::config::{ConfigGuard, NameValueList};
+use crate::dataset::TypedDataset;
+use crate::errors::{Error, Result};
+use crate::grid::{BoundingBox, Grid};
+use crate::request::{call_progress, Deadline, ProgressFn, Resampling};
+use crate::trace::{buffer_bytes, instrument};
+use crate::translate::gdal_type_name;
+use crate::typed_rasterband::GdalFrom;
+use crate::vsi::{unique_mem_name, MemFile};
+use crate::window::Window;
+use gdal::raster::dataset::Dataset;
+use gdal::raster::types::GdalType;
+use gdal_sys::{
+    CPLErr, GDALDatasetH, GDALVersionInfo, GDALWarp, GDALWarpAppOptionsFree, GDALWarpAppOptionsNew,
+    GDALWarpAppOptionsSetProgress,
+};
+use std::ffi::{CStr, CString};
+use std::io;
+use std::os::raw::{c_int, c_void};
+use std::ptr;
+use std::time::Duration;
+
+/// How many threads the warper uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumThreads {
+    Count(usize),
+    AllCpus,
+}
+
+/// A polygon that limits which pixels are warped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cutline {
+    /// The polygon as WKT.
+    pub wkt: String,
+    /// The spatial reference of the polygon, if not the source's.
+    pub srs: Option<String>,
+    /// Shrinks the output extent to the polygon's.
+    pub crop: bool,
+}
+
+/// Options for `TypedDataset::warp`, mirroring those of `gdalwarp`.
+#[derive(Debug, Clone, Default)]
+pub struct WarpOptions {
+    /// The output driver, such as `"GTiff"` or `"MEM"`.
+    pub format: Option<String>,
+    /// The output spatial reference, such as `"EPSG:3857"` or WKT.
+    pub target_srs: Option<String>,
+    /// The output pixel size in target units.
+    pub resolution: Option<(f64, f64)>,
+    /// The output size in pixels.
+    pub out_size: Option<(usize, usize)>,
+    /// The output extent in target units.
+    pub extent: Option<BoundingBox>,
+    /// The resampling method for every band not in `band_resampling`.
+    pub resampling: Option<Resampling>,
+    /// Resampling methods for particular bands. GDAL uses one method per
+    /// warp, so each of these bands is warped again on its own, which needs
+    /// GDAL 3.7 or later for `-srcband` and `-dstband`.
+    pub band_resampling: Vec<(isize, Resampling)>,
+    /// The warper's working memory, in megabytes.
+    pub working_memory_mb: Option<usize>,
+    pub num_threads: Option<NumThreads>,
+    pub cutline: Option<Cutline>,
+    /// Overrides the source nodata value.
+    pub src_nodata: Option<f64>,
+    /// The nodata value of the output.
+    pub dst_nodata: Option<f64>,
+    /// Driver-specific creation options, such as `("COMPRESS", "DEFLATE")`.
+    pub creation_options: Vec<(String, String)>,
+    /// Cancels the warp with an `io::ErrorKind::TimedOut` error if it takes
+    /// longer than this, including any per-band passes.
+    pub timeout: Option<Duration>,
+}
+
+impl WarpOptions {
+    /// Options that warp onto `grid`, which should be north-up, taking its
+    /// CRS, extent and size. Set the other fields with struct update syntax:
+    /// `WarpOptions { resampling, ..WarpOptions::for_grid(&grid) }`.
+    pub fn for_grid(grid: &Grid) -> WarpOptions {
+        WarpOptions {
+            target_srs: grid.crs.clone(),
+            out_size: Some(grid.size),
+            extent: Some(grid.bounds()),
+            ..WarpOptions::default()
+        }
+    }
+
+    /// These options with the CRS, extent and size of `grid`, failing with
+    /// `Error::GeoTransform` unless `grid` is north-up, since `gdalwarp`
+    /// only writes north-up outputs.
+    fn onto_grid(&self, grid: &Grid) -> Result<WarpOptions> {
+        if !grid.is_north_up() {
+            return Err(Error::GeoTransform(
+                "only north-up grids can be warped onto".to_string(),
+            ));
+        }
+        Ok(WarpOptions {
+            target_srs: grid.crs.clone().or_else(|| self.target_srs.clone()),
+            resolution: None,
+            out_size: Some(grid.size),
+            extent: Some(grid.bounds()),
+            ..self.clone()
+        })
+    }
+
+    /// The `gdalwarp` arguments for these options, converting pixels to
+    /// `output_type` and reading the cutline from `cutline_path`.
+    fn to_args(&self, output_type: &str, cutline_path: Option<&str>) -> Vec<String> {
+        let mut args = self.output_args(output_type);
+        if let Some(resampling) = self.resampling {
+            args.extend(vec!["-r".to_string(), resampling.name().to_string()]);
+        }
+        args.extend(self.warper_args(cutline_path));
+        args
+    }
+
+    /// The arguments that describe the output dataset, which are left out
+    /// when warping into an existing one.
+    fn output_args(&self, output_type: &str) -> Vec<String> {
+        let mut args = vec!["-ot".to_string(), output_type.to_string()];
+        if let Some(format) = &self.format {
+            args.extend(vec!["-of".to_string(), format.clone()]);
+        }
+        if let Some(srs) = &self.target_srs {
+            args.extend(vec!["-t_srs".to_string(), srs.clone()]);
+        }
+        if let Some((x, y)) = self.resolution {
+            args.extend(vec!["-tr".to_string(), x.to_string(), y.to_string()]);
+        }
+        if let Some((width, height)) = self.out_size {
+            args.extend(vec![
+                "-ts".to_string(),
+                width.to_string(),
+                height.to_string(),
+            ]);
+        }
+        if let Some(extent) = self.extent {
+            args.push("-te".to_string());
+            for v in &[extent.min_x, extent.min_y, extent.max_x, extent.max_y] {
+                args.push(v.to_string());
+            }
+        }
+        if let Some(nodata) = self.dst_nodata {
+            args.extend(vec!["-dstnodata".to_string(), nodata.to_string()]);
+        }
+        for (key, value) in &self.creation_options {
+            args.extend(vec!["-co".to_string(), format!("{}={}", key, value)]);
+        }
+        args
+    }
+
+    /// The arguments that control how pixels are warped.
+    fn warper_args(&self, cutline_path: Option<&str>) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(mb) = self.working_memory_mb {
+            args.extend(vec!["-wm".to_string(), mb.to_string()]);
+        }
+        if let Some(threads) = self.num_threads {
+            let value = match threads {
+                NumThreads::Count(n) => n.to_string(),
+                NumThreads::AllCpus => "ALL_CPUS".to_string(),
+            };
+            args.extend(vec![
+                "-multi".to_string(),
+                "-wo".to_string(),
+                format!("NUM_THREADS={}", value),
+            ]);
+        }
+        if let (Some(cutline), Some(path)) = (&self.cutline, cutline_path) {
+            args.extend(vec!["-cutline".to_string(), path.to_string()]);
+            if let Some(srs) = &cutline.srs {
+                args.extend(vec!["-cutline_srs".to_string(), srs.clone()]);
+            }
+            if cutline.crop {
+                args.push("-crop_to_cutline".to_string());
+            }
+        }
+        if let Some(nodata) = self.src_nodata {
+            args.extend(vec!["-srcnodata".to_string(), nodata.to_string()]);
+        }
+        args
+    }
+}
+
+/// The first GDAL version whose `gdalwarp` takes `-srcband` and `-dstband`,
+/// as GDAL encodes it in `VERSION_NUM`.
+const BAND_SELECTION_VERSION: i64 = 3_070_000;
+
+/// The version of the GDAL library loaded at runtime, such as `3070100`
+/// for 3.7.1.
+fn gdal_version_num() -> i64 {
+    let request = CString::new("VERSION_NUM").unwrap();
+    unsafe { CStr::from_ptr(GDALVersionInfo(request.as_ptr())) }
+        .to_string_lossy()
+        .parse()
+        .unwrap_or(0)
+}
+
+/// Runs `gdalwarp` with `args` from `sources`, either creating `path` or,
+/// if `target` isn't null, warping into it, until `deadline` if there is
+/// one.
+fn run_warp(
+    args: Vec<String>,
+    path: Option<&CString>,
+    target: GDALDatasetH,
+    sources: &[GDALDatasetH],
+    deadline: &mut Option<Deadline>,
+) -> Result<GDALDatasetH> {
+    let args = NameValueList::from_strings(args)?;
+    let mut sources = sources.to_vec();
+    let mut check = |_: f64| deadline.as_mut().is_none_or(Deadline::check);
+    let mut progress: &mut ProgressFn<'_> = &mut check;
+    let c_dataset = unsafe {
+        let warp_options = GDALWarpAppOptionsNew(args.as_ptr() as *mut *mut _, ptr::null_mut());
+        if warp_options.is_null() {
+            return Err(Error::last_cpl_error(CPLErr::CE_Failure));
+        }
+        GDALWarpAppOptionsSetProgress(
+            warp_options,
+            Some(call_progress),
+            &mut progress as *mut &mut ProgressFn<'_> as *mut c_void,
+        );
+        let c_dataset = GDALWarp(
+            path.map_or(ptr::null(), |p| p.as_ptr()),
+            target,
+            sources.len() as c_int,
+            sources.as_mut_ptr(),
+            warp_options,
+            ptr::null_mut(),
+        );
+        GDALWarpAppOptionsFree(warp_options);
+        c_dataset
+    };
+    if let Some(deadline) = deadline.as_ref().filter(|d| d.expired()) {
+        // A partial output we created is closed; a target belongs to the
+        // caller.
+        if target.is_null() && !c_dataset.is_null() {
+            drop(unsafe { Dataset::_with_c_ptr(c_dataset) });
+        }
+        return Err(deadline.error("warp"));
+    }
+    if c_dataset.is_null() {
+        return Err(Error::last_cpl_error(CPLErr::CE_Failure));
+    }
+    Ok(c_dataset)
+}
+
+/// Warps `sources` into one output at `path`, converting pixels to `U`.
+fn warp_sources<T, U>(
+    sources: &[&TypedDataset<T>],
+    path: &str,
+    options: &WarpOptions,
+) -> Result<TypedDataset<U>>
+where
+    T: Copy + GdalType + GdalFrom<f64>,
+    U: Copy + GdalType + GdalFrom<f64>,
+{
+    let first = match sources.first() {
+        Some(first) => first,
+        None => {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "no sources to warp").into())
+        }
+    };
+    if !options.band_resampling.is_empty() && gdal_version_num() < BAND_SELECTION_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "per-band resampling needs GDAL 3.7 or later",
+        )
+        .into());
+    }
+    let config: Vec<(String, String)> = sources
+        .iter()
+        .flat_map(|source| source.config_options().iter().cloned())
+        .collect();
+    let _guard = ConfigGuard::set(&config)?;
+    // gdalwarp reads cutlines from a vector dataset; a CSV file with a
+    // WKT column is the simplest one to write.
+    let cutline_file = match &options.cutline {
+        Some(cutline) => Some(MemFile::from_bytes(
+            &unique_mem_name(".csv"),
+            format!("WKT\n\"{}\"\n", cutline.wkt).as_bytes(),
+        )?),
+        None => None,
+    };
+    let cutline_path = cutline_file.as_ref().map(MemFile::path);
+    let output_type = gdal_type_name::<U>();
+    let c_path = CString::new(path).map_err(io::Error::from)?;
+    let c_sources: Vec<GDALDatasetH> = sources
+        .iter()
+        .map(|source| unsafe { source.dataset()._c_ptr() })
+        .collect();
+    let full = Window::new((0, 0), first.size());
+    let mut deadline = options.timeout.map(Deadline::new);
+    let _timeout_guard = match &deadline {
+        Some(deadline) => Some(ConfigGuard::set(&deadline.http_config())?),
+        None => None,
+    };
+
+    let dataset = instrument("warp", full, buffer_bytes::<T>(full.size), || {
+        let args = options.to_args(&output_type, cutline_path);
+        let c_dataset = run_warp(
+            args,
+            Some(&c_path),
+            ptr::null_mut(),
+            &c_sources,
+            &mut deadline,
+        )?;
+        let dataset = unsafe { Dataset::_with_c_ptr(c_dataset) };
+
+        for &(band, resampling) in &options.band_resampling {
+            let mut args = options.warper_args(cutline_path);
+            args.extend(vec![
+                "-r".to_string(),
+                resampling.name().to_string(),
+                "-srcband".to_string(),
+                band.to_string(),
+                "-dstband".to_string(),
+                band.to_string(),
+            ]);
+            run_warp(
+                args,
+                None,
+                unsafe { dataset._c_ptr() },
+                &c_sources,
+                &mut deadline,
+            )?;
+        }
+        Ok::<_, Error>(dataset)
+    })?;
+    let mut output = TypedDataset::from_dataset(dataset)?;
+    if let Some(source) = sources.iter().find(|source| source.history_enabled()) {
+        source.record_derived(&mut output, "warp", &[("type", output_type)])?;
+    }
+    Ok(output)
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64>> TypedDataset<T> {
+    /// Warps the dataset to `path` as `gdalwarp` would, converting pixels
+    /// to `U`.
+    pub fn warp<U>(&self, path: &str, options: &WarpOptions) -> Result<TypedDataset<U>>
+    where
+        U: Copy + GdalType + GdalFrom<f64>,
+    {
+        warp_sources(&[self], path, options)
+    }
+
+    /// Warps the dataset onto `grid`, which must be north-up, taking its
+    /// CRS, extent and size in place of those in `options`.
+    pub fn warp_to_grid<U>(
+        &self,
+        path: &str,
+        grid: &Grid,
+        options: &WarpOptions,
+    ) -> Result<TypedDataset<U>>
+    where
+        U: Copy + GdalType + GdalFrom<f64>,
+    {
+        warp_sources(&[self], path, &options.onto_grid(grid)?)
+    }
+}
+
+/// Warps every dataset in `sources` onto `grid`, which must be north-up, in
+/// one output at `path`, as `gdalwarp` does given several inputs. Where
+/// sources overlap, later ones are drawn over earlier ones, except where
+/// they are nodata. `grid` takes the place of the CRS, extent and size in
+/// `options`.
+///
+/// Fails if `sources` is empty.
+pub fn mosaic<T, U>(
+    sources: &[&TypedDataset<T>],
+    path: &str,
+    grid: &Grid,
+    options: &WarpOptions,
+) -> Result<TypedDataset<U>>
+where
+    T: Copy + GdalType + GdalFrom<f64>,
+    U: Copy + GdalType + GdalFrom<f64>,
+{
+    warp_sources(sources, path, &options.onto_grid(grid)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::create::DatasetBuilder;
+    use crate::dataset::TypedDataset;
+    use crate::errors::Error;
+    use crate::grid::Grid;
+    use crate::request::Resampling;
+    use crate::warp::{mosaic, Cutline, NumThreads, WarpOptions};
+    use crate::window::Window;
+    use std::path::Path;
+
+    #[test]
+    fn warp_args() {
+        let options = WarpOptions {
+            target_srs: Some("EPSG:3857".to_string()),
+            resampling: Some(Resampling::Cubic),
+            working_memory_mb: Some(512),
+            num_threads: Some(NumThreads::AllCpus),
+            cutline: Some(Cutline {
+                wkt: "POLYGON ((0 0,1 0,1 1,0 0))".to_string(),
+                srs: None,
+                crop: true,
+            }),
+            dst_nodata: Some(-1.0),
+            ..WarpOptions::default()
+        };
+
+        assert_eq!(
+            options.to_args("Float32", Some("/vsimem/c.csv")).join(" "),
+            "-ot Float32 -t_srs EPSG:3857 -dstnodata -1 -r cubic -wm 512 -multi \
+             -wo NUM_THREADS=ALL_CPUS -cutline /vsimem/c.csv -crop_to_cutline"
+        );
+    }
+
+    #[test]
+    fn warp_args_for_grid() {
+        let grid = Grid::new(
+            Some("EPSG:32610".to_string()),
+            [1000.0, 30.0, 0.0, 5000.0, 0.0, -30.0],
+            (10, 20),
+        );
+        let options = WarpOptions {
+            resampling: Some(Resampling::Bilinear),
+            ..WarpOptions::for_grid(&grid)
+        };
+
+        assert_eq!(
+            options.to_args("Byte", None).join(" "),
+            "-ot Byte -t_srs EPSG:32610 -ts 10 20 -te 1000 4400 1300 5000 -r bilinear"
+        );
+
+        // The grid replaces any output shape already in the options.
+        let options = WarpOptions {
+            resolution: Some((5.0, 5.0)),
+            resampling: Some(Resampling::Bilinear),
+            ..WarpOptions::default()
+        };
+        assert_eq!(
+            options
+                .onto_grid(&grid)
+                .unwrap()
+                .to_args("Byte", None)
+                .join(" "),
+            "-ot Byte -t_srs EPSG:32610 -ts 10 20 -te 1000 4400 1300 5000 -r bilinear"
+        );
+    }
+
+    #[test]
+    fn warp_to_memory() {
+        let ds = TypedDataset::<u8>::open(Path::new("testdata/test_u8.tif")).unwrap();
+        let options = WarpOptions {
+            format: Some("MEM".to_string()),
+            out_size: Some((111, 111)),
+            resampling: Some(Resampling::Average),
+            band_resampling: vec![(1, Resampling::Nearest)],
+            num_threads: Some(NumThreads::Count(2)),
+            ..WarpOptions::default()
+        };
+        let out = ds.warp::<u8>("", &options).unwrap();
+
+        assert_eq!(out.size(), (111, 111));
+        assert_eq!(out.band_count(), 1);
+    }
+
+    #[test]
+    fn resample_bands_separately() {
+        let ds = DatasetBuilder::<u8>::new("MEM", "", (2, 1))
+            .bands(2)
+            .geo_transform([0.0, 1.0, 0.0, 1.0, 0.0, -1.0])
+            .create()
+            .unwrap();
+        let source = TypedBuffer::new((2, 1), vec![0u8, 100]);
+        for band in 1..=2 {
+            ds.write(band, Window::new((0, 0), (2, 1)), &source)
+                .unwrap();
+        }
+        let options = WarpOptions {
+            format: Some("MEM".to_string()),
+            out_size: Some((8, 1)),
+            resampling: Some(Resampling::Nearest),
+            band_resampling: vec![(2, Resampling::Bilinear)],
+            ..WarpOptions::default()
+        };
+        let out = ds.warp::<u8>("", &options).unwrap();
+        let read = |band| out.read(band, Window::new((0, 0), (8, 1))).unwrap().data;
+
+        // Nearest keeps only the source values; bilinear blends them.
+        assert!(read(1).iter().all(|&v| v == 0 || v == 100));
+        assert!(read(2).iter().any(|&v| v > 0 && v < 100));
+    }
+
+    #[test]
+    fn mosaic_onto_grid() {
+        let tile = |x0: f64, value: u8| {
+            DatasetBuilder::<u8>::new("MEM", "", (2, 1))
+                .geo_transform([x0, 1.0, 0.0, 1.0, 0.0, -1.0])
+                .projection("EPSG:32610")
+                .fill(value)
+                .create()
+                .unwrap()
+        };
+        let (left, right) = (tile(0.0, 10), tile(2.0, 20));
+        let grid = Grid::new(
+            Some("EPSG:32610".to_string()),
+            [0.0, 1.0, 0.0, 1.0, 0.0, -1.0],
+            (4, 1),
+        );
+        let options = WarpOptions {
+            format: Some("MEM".to_string()),
+            ..WarpOptions::default()
+        };
+        let out = mosaic::<u8, u8>(&[&left, &right], "", &grid, &options).unwrap();
+        let row = out.read(1, Window::new((0, 0), (4, 1))).unwrap();
+        assert_eq!(row.data, vec![10, 10, 20, 20]);
+
+        let mut rotated = grid.clone();
+        rotated.transform[2] = 0.5;
+        assert!(matches!(
+            left.warp_to_grid::<u8>("", &rotated, &options),
+            Err(Error::GeoTransform(_))
+        ));
+        assert!(mosaic::<u8, u8>(&[], "", &grid, &options).is_err());
+    }
+}
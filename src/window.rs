@@ -0,0 +1,83 @@
+/// A rectangular region of a raster in pixel coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Window {
+    pub offset: (isize, isize),
+    pub size: (usize, usize),
+}
+
+impl Window {
+    pub fn new(offset: (isize, isize), size: (usize, usize)) -> Window {
+        Window { offset, size }
+    }
+
+    /// The window covering an entire raster of the given size.
+    pub fn full(raster_size: (usize, usize)) -> Window {
+        Window::new((0, 0), raster_size)
+    }
+
+    pub fn pixel_count(&self) -> usize {
+        self.size.0 * self.size.1
+    }
+
+    /// The overlapping region of two windows, if they overlap at all.
+    pub fn intersection(&self, other: &Window) -> Option<Window> {
+        let x0 = self.offset.0.max(other.offset.0);
+        let y0 = self.offset.1.max(other.offset.1);
+        let x1 = (self.offset.0 + self.size.0 as isize).min(other.offset.0 + other.size.0 as isize);
+        let y1 = (self.offset.1 + self.size.1 as isize).min(other.offset.1 + other.size.1 as isize);
+
+        if x1 > x0 && y1 > y0 {
+            Some(Window::new(
+                (x0, y0),
+                ((x1 - x0) as usize, (y1 - y0) as usize),
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Derives the geotransform of this window from the geotransform of the
+    /// raster it belongs to.
+    pub fn geo_transform(&self, gt: &[f64; 6]) -> [f64; 6] {
+        let (x, y) = (self.offset.0 as f64, self.offset.1 as f64);
+        [
+            gt[0] + x * gt[1] + y * gt[2],
+            gt[1],
+            gt[2],
+            gt[3] + x * gt[4] + y * gt[5],
+            gt[4],
+            gt[5],
+        ]
+    }
+}
+
+impl From<((isize, isize), (usize, usize))> for Window {
+    fn from(w: ((isize, isize), (usize, usize))) -> Window {
+        Window::new(w.0, w.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_intersection() {
+        let a = Window::new((0, 0), (10, 10));
+        let b = Window::new((5, 8), (10, 10));
+
+        assert_eq!(a.intersection(&b), Some(Window::new((5, 8), (5, 2))));
+        assert_eq!(a.intersection(&Window::new((10, 0), (4, 4))), None);
+    }
+
+    #[test]
+    fn window_geo_transform() {
+        let gt = [450000.0, 30.0, 0.0, 5410000.0, 0.0, -30.0];
+        let w = Window::new((10, 20), (5, 5));
+
+        assert_eq!(
+            w.geo_transform(&gt),
+            [450300.0, 30.0, 0.0, 5409400.0, 0.0, -30.0]
+        );
+    }
+}
@@ -0,0 +1,326 @@
+use crate::blocks::block_window;
+use crate::buffer::TypedBuffer;
+use crate::dataset::TypedDataset;
+use crate::errors::{check_cpl_err, Result};
+use crate::typed_rasterband::{GdalFrom, ReadWrite, TypedRasterBand};
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+use gdal_sys::{GDALFlushCache, GDALFlushRasterCache};
+use std::collections::HashMap;
+
+impl<'a, T: Copy + GdalType + GdalFrom<f64>> TypedRasterBand<'a, T, ReadWrite> {
+    /// Writes any blocks GDAL has cached for this band to disk.
+    pub fn flush_cache(&self) -> Result<()> {
+        check_cpl_err(unsafe { GDALFlushRasterCache(self.rasterband()._c_ptr()) })
+    }
+
+    /// Creates a `BandWriter` that buffers writes to this band.
+    pub fn writer<'b>(&'b self) -> BandWriter<'a, 'b, T> {
+        BandWriter::new(self)
+    }
+
+    /// Creates a `RowBandWriter` that writes this band from top to bottom.
+    pub fn row_writer<'b>(&'b self) -> RowBandWriter<'a, 'b, T> {
+        RowBandWriter::new(self)
+    }
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64>> TypedDataset<T> {
+    /// Writes any blocks GDAL has cached for this dataset to disk.
+    pub fn flush_cache(&self) {
+        unsafe { GDALFlushCache(self.dataset()._c_ptr()) }
+    }
+}
+
+/// A block that has been written to, along with which of its pixels have.
+struct DirtyBlock<T> {
+    window: Window,
+    buffer: TypedBuffer<T>,
+    written: Vec<bool>,
+}
+
+/// Buffers writes to a band in memory, one buffer per natural block, and
+/// writes each touched block once when flushed.
+///
+/// Later writes replace earlier ones where they overlap. Blocks that were
+/// only partly written are read back and merged when flushing, so a tile is
+/// decoded and encoded once no matter how many writes touched it.
+///
+/// Dropping the writer flushes it, ignoring errors; call `flush` to see them.
+pub struct BandWriter<'a, 'b, T: Copy + GdalType + GdalFrom<f64>> {
+    band: &'b TypedRasterBand<'a, T, ReadWrite>,
+    blocks: HashMap<(usize, usize), DirtyBlock<T>>,
+}
+
+impl<'a, 'b, T: Copy + GdalType + GdalFrom<f64>> BandWriter<'a, 'b, T> {
+    pub fn new(band: &'b TypedRasterBand<'a, T, ReadWrite>) -> BandWriter<'a, 'b, T> {
+        BandWriter {
+            band,
+            blocks: HashMap::new(),
+        }
+    }
+
+    pub fn band(&self) -> &'b TypedRasterBand<'a, T, ReadWrite> {
+        self.band
+    }
+
+    /// The number of blocks waiting to be flushed.
+    pub fn dirty_blocks(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Buffers a write of `buffer` to `window`, which must lie within the
+    /// band and match the buffer's size.
+    pub fn write(&mut self, window: Window, buffer: &TypedBuffer<T>) {
+        assert_eq!(window.size, buffer.size, "buffer size doesn't match window");
+        if window.pixel_count() == 0 {
+            return;
+        }
+        let (raster_size, block_size) = (self.band.size(), self.band.block_size());
+        assert_eq!(
+            window.intersection(&Window::full(raster_size)),
+            Some(window),
+            "write window extends outside the raster"
+        );
+
+        let x0 = window.offset.0 as usize;
+        let y0 = window.offset.1 as usize;
+        for by in y0 / block_size.1..=(y0 + window.size.1 - 1) / block_size.1 {
+            for bx in x0 / block_size.0..=(x0 + window.size.0 - 1) / block_size.0 {
+                let block = self.blocks.entry((bx, by)).or_insert_with(|| {
+                    let window = block_window(raster_size, block_size, (bx, by));
+                    DirtyBlock {
+                        window,
+                        buffer: TypedBuffer::filled(window.size, T::from(0.0)),
+                        written: vec![false; window.pixel_count()],
+                    }
+                });
+
+                let overlap = window.intersection(&block.window).unwrap();
+                for y in 0..overlap.size.1 {
+                    let src_x = (overlap.offset.0 - window.offset.0) as usize;
+                    let src_y = (overlap.offset.1 - window.offset.1) as usize + y;
+                    let dst_x = (overlap.offset.0 - block.window.offset.0) as usize;
+                    let dst_y = (overlap.offset.1 - block.window.offset.1) as usize + y;
+
+                    let start = dst_y * block.window.size.0 + dst_x;
+                    let end = start + overlap.size.0;
+                    block.buffer.data[start..end]
+                        .copy_from_slice(&buffer.row(src_y)[src_x..src_x + overlap.size.0]);
+                    for written in &mut block.written[start..end] {
+                        *written = true;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Writes every dirty block to the band, then flushes GDAL's cache.
+    ///
+    /// If history is enabled, a flush that wrote any blocks records one
+    /// `write` entry for them all.
+    pub fn flush(&mut self) -> Result<()> {
+        let count = self.write_blocks()?;
+        if count > 0 {
+            self.band
+                .record_history("write", &[("blocks", count.to_string())])?;
+        }
+        Ok(())
+    }
+
+    /// Writes every dirty block and flushes GDAL's cache, giving the number
+    /// of blocks written. A block stays dirty until it has been written, so
+    /// after an error the blocks not yet written are still there to retry.
+    pub(crate) fn write_blocks(&mut self) -> Result<usize> {
+        let mut keys: Vec<_> = self.blocks.keys().copied().collect();
+        keys.sort_by_key(|&(bx, by)| (by, bx));
+
+        for &key in &keys {
+            let block = self.blocks.get_mut(&key).unwrap();
+            if block.written.iter().any(|&w| !w) {
+                let existing =
+                    self.band
+                        .read(block.window.offset, block.window.size, block.window.size)?;
+                for ((pixel, &written), &old) in block
+                    .buffer
+                    .data
+                    .iter_mut()
+                    .zip(&block.written)
+                    .zip(&existing.data)
+                {
+                    if !written {
+                        *pixel = old;
+                    }
+                }
+            }
+            self.band
+                .write_slice(block.window, &block.buffer.data, block.window.size)?;
+            self.blocks.remove(&key);
+        }
+        self.band.flush_cache()?;
+        Ok(keys.len())
+    }
+}
+
+impl<'a, 'b, T: Copy + GdalType + GdalFrom<f64>> Drop for BandWriter<'a, 'b, T> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Writes a band from top to bottom as rows arrive, such as from a sensor
+/// feed, without holding more than one strip of blocks in memory.
+///
+/// Rows are buffered until a full strip of blocks is ready, which is then
+/// written and flushed so that GDAL's cache never holds more than a strip of
+/// dirty blocks. Striped outputs, such as a GeoTIFF created with a small
+/// `BLOCKYSIZE`, suit this best.
+///
+/// If history is enabled, the writer records one `write` entry once the
+/// band is complete or the writer finishes.
+///
+/// Dropping the writer writes any rows left over, ignoring errors; call
+/// `finish` to see them.
+pub struct RowBandWriter<'a, 'b, T: Copy + GdalType + GdalFrom<f64>> {
+    band: &'b TypedRasterBand<'a, T, ReadWrite>,
+    /// The first row not yet written to the band.
+    next_row: usize,
+    pending: Vec<T>,
+    /// Whether the rows written have been recorded in the band's history.
+    recorded: bool,
+}
+
+impl<'a, 'b, T: Copy + GdalType + GdalFrom<f64>> RowBandWriter<'a, 'b, T> {
+    pub fn new(band: &'b TypedRasterBand<'a, T, ReadWrite>) -> RowBandWriter<'a, 'b, T> {
+        RowBandWriter {
+            band,
+            next_row: 0,
+            pending: Vec::new(),
+            recorded: false,
+        }
+    }
+
+    /// The number of rows received so far, written or not.
+    pub fn rows(&self) -> usize {
+        self.next_row + self.pending.len() / self.band.size().0.max(1)
+    }
+
+    /// Appends `rows`, which must be as wide as the band and not run past its
+    /// last row, writing every strip that it completes.
+    pub fn push_rows(&mut self, rows: &TypedBuffer<T>) -> Result<()> {
+        let (width, height) = self.band.size();
+        assert_eq!(rows.size.0, width, "rows must be as wide as the band");
+        assert!(
+            self.rows() + rows.size.1 <= height,
+            "rows run past the end of the band"
+        );
+        self.pending.extend_from_slice(&rows.data);
+
+        let strip = self.band.block_size().1.max(1);
+        while self.pending.len() >= strip * width {
+            self.write_pending(strip)?;
+        }
+        if self.rows() == height {
+            self.finish_strip()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the first `count` pending rows and flushes them out of the
+    /// cache.
+    fn write_pending(&mut self, count: usize) -> Result<()> {
+        let width = self.band.size().0;
+        let window = Window::new((0, self.next_row as isize), (width, count));
+        self.band
+            .write_slice(window, &self.pending[..count * width], window.size)?;
+        self.pending.drain(..count * width);
+        self.next_row += count;
+        self.band.flush_cache()
+    }
+
+    /// Writes any pending rows and, once, records the rows written in the
+    /// band's history.
+    fn finish_strip(&mut self) -> Result<()> {
+        let width = self.band.size().0.max(1);
+        if !self.pending.is_empty() {
+            self.write_pending(self.pending.len() / width)?;
+        }
+        if self.next_row > 0 && !self.recorded {
+            self.recorded = true;
+            self.band
+                .record_history("write", &[("rows", self.next_row.to_string())])?;
+        }
+        Ok(())
+    }
+
+    /// Writes any rows left over from an incomplete strip.
+    pub fn finish(mut self) -> Result<()> {
+        self.finish_strip()
+    }
+}
+
+impl<'a, 'b, T: Copy + GdalType + GdalFrom<f64>> Drop for RowBandWriter<'a, 'b, T> {
+    fn drop(&mut self) {
+        let _ = self.finish_strip();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::TypedBuffer;
+    use crate::typed_rasterband::{ReadWrite, TypedRasterBand};
+    use crate::window::Window;
+    use gdal::raster::dataset::Buffer;
+    use gdal::raster::driver::Driver;
+
+    #[test]
+    fn coalesce_small_writes() {
+        let driver = Driver::get("MEM").unwrap();
+        let ds = driver
+            .create_with_band_type::<u8>("", 4, 4, 1)
+            .expect("failed to create dataset");
+        let band = ds.rasterband(1).unwrap();
+        let typed_band = TypedRasterBand::<u8, ReadWrite>::from_writable_rasterband(&band).unwrap();
+        typed_band
+            .write((0, 0), (4, 4), &Buffer::new((4, 4), vec![7; 16]))
+            .unwrap();
+
+        let mut writer = typed_band.writer();
+        writer.write(
+            Window::new((0, 1), (2, 1)),
+            &TypedBuffer::new((2, 1), vec![1, 2]),
+        );
+        writer.write(
+            Window::new((1, 1), (2, 1)),
+            &TypedBuffer::new((2, 1), vec![3, 4]),
+        );
+        writer.flush().unwrap();
+        assert_eq!(writer.dirty_blocks(), 0);
+
+        let row = typed_band.read((0, 1), (4, 1), (4, 1)).unwrap();
+        assert_eq!(row.data, vec![1, 3, 4, 7]);
+    }
+
+    #[test]
+    fn write_rows_in_order() {
+        let driver = Driver::get("MEM").unwrap();
+        let ds = driver
+            .create_with_band_type::<u8>("", 3, 3, 1)
+            .expect("failed to create dataset");
+        let band = ds.rasterband(1).unwrap();
+        let typed_band = TypedRasterBand::<u8, ReadWrite>::from_writable_rasterband(&band).unwrap();
+
+        let mut writer = typed_band.row_writer();
+        writer
+            .push_rows(&TypedBuffer::new((3, 2), vec![1, 2, 3, 4, 5, 6]))
+            .unwrap();
+        writer
+            .push_rows(&TypedBuffer::new((3, 1), vec![7, 8, 9]))
+            .unwrap();
+        assert_eq!(writer.rows(), 3);
+        writer.finish().unwrap();
+
+        let all = typed_band.read((0, 0), (3, 3), (3, 3)).unwrap();
+        assert_eq!(all.data, (1..=9).collect::<Vec<u8>>());
+    }
+}
@@ -0,0 +1,277 @@
+use crate::buffer::TypedBuffer;
+use crate::create::DatasetBuilder;
+use crate::dataset::TypedDataset;
+use crate::errors::{Error, Result};
+use crate::transform;
+use crate::typed_rasterband::{Access, GdalFrom, TypedRasterBand};
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+pub struct XyzOptions {
+    /// Separates the columns of each line.
+    pub delimiter: char,
+    /// Decimal places of coordinates, or as many as needed if `None`.
+    pub coordinate_precision: Option<usize>,
+    /// Decimal places of values, or as many as needed if `None`.
+    pub value_precision: Option<usize>,
+    /// Omit pixels where the band is nodata.
+    pub skip_nodata: bool,
+    /// Start with an `x,y,value` header line.
+    pub header: bool,
+    /// Number of raster rows to read at a time.
+    pub rows_per_batch: usize,
+}
+
+impl Default for XyzOptions {
+    fn default() -> XyzOptions {
+        XyzOptions {
+            delimiter: ' ',
+            coordinate_precision: None,
+            value_precision: None,
+            skip_nodata: true,
+            header: false,
+            rows_per_batch: 256,
+        }
+    }
+}
+
+fn format_number(value: f64, precision: Option<usize>) -> String {
+    match precision {
+        Some(precision) => format!("{:.*}", precision, value),
+        None => value.to_string(),
+    }
+}
+
+impl<'a, T, A: Access> TypedRasterBand<'a, T, A>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Into<f64> + PartialEq,
+{
+    /// Writes one `x y value` line per pixel to `path`, with the map
+    /// coordinates of the pixel centre. Returns the number of pixels
+    /// written.
+    pub fn export_xyz<P: AsRef<Path>>(&self, path: P, options: &XyzOptions) -> Result<usize> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let count = self.write_xyz(&mut writer, options)?;
+        writer.flush()?;
+        Ok(count)
+    }
+
+    /// Writes the band as XYZ lines to `writer`, in row-major order.
+    pub fn write_xyz<W: Write>(&self, writer: &mut W, options: &XyzOptions) -> Result<usize> {
+        assert!(options.rows_per_batch > 0, "rows per batch must be nonzero");
+
+        let gt = self.owning_dataset().geo_transform()?;
+        let d = options.delimiter;
+        if options.header {
+            writeln!(writer, "x{}y{}value", d, d)?;
+        }
+        let no_data = if options.skip_nodata {
+            self.no_data_value()
+        } else {
+            None
+        };
+        let (width, height) = self.size();
+        let mut count = 0;
+
+        for y0 in (0..height).step_by(options.rows_per_batch) {
+            let rows = options.rows_per_batch.min(height - y0);
+            let buffer: TypedBuffer<T> = self
+                .read((0, y0 as isize), (width, rows), (width, rows))?
+                .into();
+            for (col, row, value) in buffer.pixels() {
+                if Some(value) == no_data {
+                    continue;
+                }
+                let (x, y) = transform::apply(&gt, col as f64 + 0.5, (y0 + row) as f64 + 0.5);
+                writeln!(
+                    writer,
+                    "{}{}{}{}{}",
+                    format_number(x, options.coordinate_precision),
+                    d,
+                    format_number(y, options.coordinate_precision),
+                    d,
+                    format_number(value.into(), options.value_precision)
+                )?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+}
+
+/// The regular grid that XYZ points are expected to lie on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridSpec {
+    /// The map coordinates of the centre of the top-left pixel.
+    pub origin: (f64, f64),
+    /// The distance between pixel centres along x and y. The y spacing is
+    /// usually negative, with rows running south.
+    pub spacing: (f64, f64),
+    pub size: (usize, usize),
+    /// How far a point may be from a pixel centre, as a fraction of the
+    /// spacing.
+    pub tolerance: f64,
+}
+
+impl GridSpec {
+    pub fn geo_transform(&self) -> [f64; 6] {
+        [
+            self.origin.0 - self.spacing.0 / 2.0,
+            self.spacing.0,
+            0.0,
+            self.origin.1 - self.spacing.1 / 2.0,
+            0.0,
+            self.spacing.1,
+        ]
+    }
+
+    /// The pixel a point falls on, or `None` if it's off the grid.
+    fn pixel(&self, x: f64, y: f64) -> Option<(usize, usize)> {
+        let col = (x - self.origin.0) / self.spacing.0;
+        let row = (y - self.origin.1) / self.spacing.1;
+        let (c, r) = (col.round(), row.round());
+        if (col - c).abs() > self.tolerance || (row - r).abs() > self.tolerance {
+            return None;
+        }
+        if c < 0.0 || r < 0.0 || c >= self.size.0 as f64 || r >= self.size.1 as f64 {
+            return None;
+        }
+        Some((c as usize, r as usize))
+    }
+}
+
+/// What `from_xyz` found while gridding points.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XyzReport {
+    pub points: usize,
+    /// Points that fell on a pixel an earlier point had already filled.
+    pub duplicates: usize,
+    /// Pixels no point fell on, which are left as the fill value.
+    pub gaps: usize,
+}
+
+fn parse_error(line: usize, msg: &str) -> Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("line {}: {}", line, msg),
+    )
+    .into()
+}
+
+/// Reads `x y value` lines from `reader` onto `grid`, creating an in-memory
+/// dataset whose nodata value is `fill`.
+///
+/// Columns may be separated by whitespace, commas or semicolons, and a
+/// leading header line is skipped. Points that don't lie on the grid are an
+/// error.
+pub fn from_xyz<T, R>(reader: R, grid: &GridSpec, fill: T) -> Result<(TypedDataset<T>, XyzReport)>
+where
+    T: Copy + GdalType + GdalFrom<f64> + Into<f64>,
+    R: BufRead,
+{
+    let mut buffer = TypedBuffer::filled(grid.size, fill);
+    let mut filled = vec![false; grid.size.0 * grid.size.1];
+    let mut report = XyzReport {
+        points: 0,
+        duplicates: 0,
+        gaps: 0,
+    };
+
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let fields: Vec<&str> = line
+            .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+            .filter(|f| !f.is_empty())
+            .collect();
+        if fields.is_empty() {
+            continue;
+        }
+        let numbers: std::result::Result<Vec<f64>, _> =
+            fields.iter().map(|f| f.parse::<f64>()).collect();
+        let numbers = match numbers {
+            Ok(numbers) if numbers.len() == 3 => numbers,
+            Err(_) if i == 0 => continue,
+            _ => return Err(parse_error(i + 1, "expected three numbers")),
+        };
+
+        let (col, row) = grid.pixel(numbers[0], numbers[1]).ok_or_else(|| {
+            Error::Bounds(format!(
+                "point ({}, {}) on line {} is not on the grid",
+                numbers[0],
+                numbers[1],
+                i + 1
+            ))
+        })?;
+        let index = row * grid.size.0 + col;
+        if filled[index] {
+            report.duplicates += 1;
+        }
+        filled[index] = true;
+        buffer.data[index] = T::from(numbers[2]);
+        report.points += 1;
+    }
+    report.gaps = filled.iter().filter(|&&f| !f).count();
+
+    let dataset = DatasetBuilder::new("MEM", "", grid.size)
+        .nodata(fill)
+        .geo_transform(grid.geo_transform())
+        .create()?;
+    dataset.write(1, Window::full(grid.size), &buffer)?;
+    Ok((dataset, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::typed_rasterband::TypedRasterBand;
+    use crate::window::Window;
+    use crate::xyz::{from_xyz, GridSpec, XyzOptions};
+    use gdal::raster::dataset::Dataset;
+    use std::path::Path;
+
+    #[test]
+    fn write_xyz_lines() {
+        let path = Path::new("testdata/test_u8.tif");
+        let ds = Dataset::open(path).expect("failed to open test dataset");
+        let band = ds.rasterband(1).expect("failed to read band");
+        let typed_band = TypedRasterBand::<u8>::from_rasterband(&band).unwrap();
+
+        let options = XyzOptions {
+            delimiter: ',',
+            coordinate_precision: Some(2),
+            header: true,
+            ..XyzOptions::default()
+        };
+        let mut out = Vec::new();
+        let count = typed_band.write_xyz(&mut out, &options).unwrap();
+        assert_eq!(count, 333 * 333);
+
+        let text = String::from_utf8(out).unwrap();
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some("x,y,value"));
+        assert!(lines.next().unwrap().ends_with(",152"));
+    }
+
+    #[test]
+    fn grid_xyz_points() {
+        let grid = GridSpec {
+            origin: (100.0, 200.0),
+            spacing: (10.0, -10.0),
+            size: (2, 2),
+            tolerance: 0.01,
+        };
+        let text = "x,y,z\n100,200,1\n110,200,2\n100.05,190,3\n100,190,4\n";
+        let (ds, report) = from_xyz(text.as_bytes(), &grid, -1i16).unwrap();
+        assert_eq!(report.points, 4);
+        assert_eq!(report.duplicates, 1);
+        assert_eq!(report.gaps, 1);
+        let buffer = ds.read(1, Window::new((0, 0), (2, 2))).unwrap();
+        assert_eq!(buffer.data, vec![1, 2, 4, -1]);
+
+        let off_grid = "105,200,1\n";
+        assert!(from_xyz(off_grid.as_bytes(), &grid, 0u8).is_err());
+    }
+}
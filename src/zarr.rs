@@ -0,0 +1,186 @@
+use crate::dataset::TypedDataset;
+use crate::errors::Result;
+use crate::npy::NpyPixel;
+use crate::typed_rasterband::GdalFrom;
+use crate::window::Window;
+use gdal::raster::types::GdalType;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ZarrCompressor {
+    None,
+    #[cfg(feature = "flate2")]
+    Zlib {
+        level: u32,
+    },
+}
+
+impl ZarrCompressor {
+    fn metadata(self) -> String {
+        match self {
+            ZarrCompressor::None => "null".to_string(),
+            #[cfg(feature = "flate2")]
+            ZarrCompressor::Zlib { level } => format!("{{\"id\": \"zlib\", \"level\": {}}}", level),
+        }
+    }
+
+    fn compress(self, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        match self {
+            ZarrCompressor::None => Ok(bytes),
+            #[cfg(feature = "flate2")]
+            ZarrCompressor::Zlib { level } => {
+                use flate2::write::ZlibEncoder;
+                use flate2::Compression;
+                use std::io::Write;
+
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::new(level));
+                encoder.write_all(&bytes)?;
+                Ok(encoder.finish()?)
+            }
+        }
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_number(v: f64) -> String {
+    if v.is_nan() {
+        "\"NaN\"".to_string()
+    } else if v.is_infinite() {
+        if v > 0.0 {
+            "\"Infinity\"".to_string()
+        } else {
+            "\"-Infinity\"".to_string()
+        }
+    } else {
+        format!("{}", v)
+    }
+}
+
+impl<T: Copy + GdalType + GdalFrom<f64> + NpyPixel> TypedDataset<T> {
+    /// Writes the dataset as a Zarr v2 array with dimensions
+    /// `(band, y, x)` in a directory store at `store_path`. Chunks span one
+    /// band and `chunk_shape` pixels; chunks at the edges of the raster are
+    /// padded with the nodata value of the first band, or zero.
+    ///
+    /// The geotransform and projection are stored as array attributes, along
+    /// with `_ARRAY_DIMENSIONS` so the array can be opened with xarray.
+    pub fn export_zarr<P: AsRef<Path>>(
+        &self,
+        store_path: P,
+        chunk_shape: (usize, usize),
+        compressor: ZarrCompressor,
+    ) -> Result<()> {
+        assert!(
+            chunk_shape.0 > 0 && chunk_shape.1 > 0,
+            "chunk shape must be nonzero"
+        );
+
+        let store_path = store_path.as_ref();
+        let (width, height) = self.size();
+        let bands = self.band_count() as usize;
+        let no_data = self.dataset().rasterband(1)?.no_data_value();
+
+        fs::create_dir_all(store_path)?;
+        let zarray = format!(
+            "{{\n  \"zarr_format\": 2,\n  \"shape\": [{}, {}, {}],\n  \"chunks\": [1, {}, {}],\n  \
+             \"dtype\": \"{}\",\n  \"compressor\": {},\n  \"fill_value\": {},\n  \
+             \"order\": \"C\",\n  \"filters\": null\n}}\n",
+            bands,
+            height,
+            width,
+            chunk_shape.1,
+            chunk_shape.0,
+            T::DESCR,
+            compressor.metadata(),
+            no_data.map_or("null".to_string(), json_number),
+        );
+        fs::write(store_path.join(".zarray"), zarray)?;
+
+        let mut zattrs = String::from("{\n  \"_ARRAY_DIMENSIONS\": [\"band\", \"y\", \"x\"]");
+        if let Some(gt) = self.geo_transform() {
+            let gt: Vec<String> = gt.iter().map(|&v| json_number(v)).collect();
+            zattrs.push_str(&format!(",\n  \"geotransform\": [{}]", gt.join(", ")));
+        }
+        let projection = self.projection();
+        if !projection.is_empty() {
+            zattrs.push_str(&format!(",\n  \"crs_wkt\": {}", json_string(&projection)));
+        }
+        zattrs.push_str("\n}\n");
+        fs::write(store_path.join(".zattrs"), zattrs)?;
+
+        let fill = T::from(no_data.unwrap_or(0.0));
+        for band in 0..bands {
+            for (cy, y0) in (0..height).step_by(chunk_shape.1).enumerate() {
+                for (cx, x0) in (0..width).step_by(chunk_shape.0).enumerate() {
+                    let size = (
+                        chunk_shape.0.min(width - x0),
+                        chunk_shape.1.min(height - y0),
+                    );
+                    let window = Window::new((x0 as isize, y0 as isize), size);
+                    let buffer = self.read(band as isize + 1, window)?;
+
+                    let mut bytes = Vec::with_capacity(chunk_shape.0 * chunk_shape.1 * T::SIZE);
+                    for row in 0..chunk_shape.1 {
+                        for col in 0..chunk_shape.0 {
+                            let v = if row < size.1 && col < size.0 {
+                                buffer.get(col, row)
+                            } else {
+                                fill
+                            };
+                            v.write_le(&mut bytes);
+                        }
+                    }
+
+                    let key = format!("{}.{}.{}", band, cy, cx);
+                    fs::write(store_path.join(key), compressor.compress(bytes)?)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dataset::TypedDataset;
+    use crate::zarr::ZarrCompressor;
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn zarr_json_escaping() {
+        assert_eq!(super::json_string("a\"b\\c"), "\"a\\\"b\\\\c\"");
+        assert_eq!(super::json_number(f64::NAN), "\"NaN\"");
+    }
+
+    #[test]
+    fn export_zarr_chunks() {
+        let ds = TypedDataset::<u8>::open(Path::new("testdata/test_u8.tif")).unwrap();
+        let store = env::temp_dir().join("gdal_typed_rasterband_test.zarr");
+        ds.export_zarr(&store, (128, 128), ZarrCompressor::None)
+            .unwrap();
+
+        let zarray = fs::read_to_string(store.join(".zarray")).unwrap();
+        assert!(zarray.contains("\"shape\": [1, 333, 333]"));
+        assert_eq!(fs::read(store.join("0.2.2")).unwrap().len(), 128 * 128);
+        assert_eq!(fs::read(store.join("0.0.0")).unwrap()[0], 152);
+    }
+}